@@ -0,0 +1,1526 @@
+//! Generates the code that runs the examples and writes the sheet, and the
+//! WebAssembly module's code that runs the rows' parsers on any input.
+
+use crate::{
+    examples::{fixture_names, split_output_marker},
+    transform::{
+        features_cfg, signature, Row, Transformed, Url, BYTE_REFERENCE_ANCHOR, PRIMITIVE_TYPES,
+    },
+    Result,
+};
+use nom_cheatsheet_core::headings;
+use nom_cheatsheet_shared::{
+    markdown_cell_code, markdown_cell_code_lines, markdown_format_code, visible_whitespace,
+};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::PathBuf,
+};
+use syn::{parse_quote, visit_mut::VisitMut, Expr, ExprLit, Item, Lit, Local, Pat, Stmt};
+
+/// The columns of the tables in the template, in the order they're in there,
+/// and then the ones that only get added if they're asked for
+pub static COLUMN_NAMES: &[&str] = &[
+    "parser",
+    "usage",
+    "input",
+    "output",
+    "description",
+    "winnow",
+    "signature",
+];
+
+/// How many of `COLUMN_NAMES` the tables in the template can have, which
+/// are the ones shown by default
+pub const TEMPLATE_COLUMNS: usize = 5;
+
+/// Makes a table row out of the chosen columns, with the cells of
+/// `COLUMN_NAMES` in `cells`, and the ones of other columns in `extra`. The
+/// level marker goes at the end of the last one, which is where the HTML
+/// looks for it.
+fn table_row(
+    columns: &[&str],
+    cells: [&str; 7],
+    extra: &HashMap<&str, &str>,
+    marker: &str,
+) -> String {
+    let cells = columns
+        .iter()
+        .map(
+            |column| match COLUMN_NAMES.iter().position(|name| name == column) {
+                Some(index) => cells[index],
+                None => extra.get(column).copied().unwrap_or_default(),
+            },
+        )
+        .collect::<Vec<_>>();
+    format!("| {}{marker} |", cells.join(" | "))
+}
+
+/// The columns of a table in the template, which are named like in
+/// `COLUMN_NAMES`, except for the first one, which is the parser whatever
+/// it's called. Other columns are passed through from the template.
+fn template_columns<'a>(names: &[&'a str]) -> Vec<&'a str> {
+    let mut columns = vec![COLUMN_NAMES[0]];
+    columns.extend(&names[1..]);
+    columns
+}
+
+/// Picks the chosen columns out of the columns of a table in the template,
+/// which keep their names and alignments from there
+fn table_header(columns: &[&str], names: &[&str], header: &str) -> String {
+    let template_columns = template_columns(names);
+    let separators = header
+        .lines()
+        .nth(1)
+        .unwrap_or_default()
+        .split('|')
+        .map(str::trim)
+        .filter(|separator| !separator.is_empty())
+        .collect::<Vec<_>>();
+    let (names, separators): (Vec<_>, Vec<_>) = columns
+        .iter()
+        .map(
+            |column| match template_columns.iter().position(|known| known == column) {
+                Some(index) => (names[index], separators[index]),
+                None => (*column, "---"),
+            },
+        )
+        .unzip();
+    format!("| {} |\n|{}|\n", names.join(" | "), separators.join("|"))
+}
+
+/// The `Numbers` that the output cell of a row gets formatted with
+fn numbers_code(numbers: Option<&str>) -> TokenStream {
+    match numbers {
+        Some(numbers) => {
+            let variant = format_ident!("{}{}", numbers[..1].to_uppercase(), &numbers[1..]);
+            quote! { Numbers::#variant }
+        }
+        None => quote! { Numbers::Default },
+    }
+}
+
+/// Splits an `-> Type` output type annotation off the end of a usage cell.
+/// Closures can have `->` in them as well, so this only counts as an
+/// annotation if both sides parse on their own.
+fn split_output_type(usage: &str) -> (&str, Option<&str>) {
+    if let Some(index) = usage.rfind("->") {
+        let expr = usage[..index].trim_end();
+        let output_type = usage[index + 2..].trim();
+        if syn::parse_str::<Expr>(expr).is_ok() && syn::parse_str::<syn::Type>(output_type).is_ok()
+        {
+            return (expr, Some(output_type));
+        }
+    }
+    (usage, None)
+}
+
+/// The `Link` of the sheet's rows for a combinator, with its signature if
+/// it's a function of nom
+fn link_code(url: &Url, signature: Option<&str>) -> TokenStream {
+    let Url {
+        module,
+        name,
+        docsurl,
+        summary,
+        ..
+    } = url;
+    let summary = match summary {
+        Some(summary) => quote! { Some(#summary) },
+        None => quote! { None },
+    };
+    let signature = match signature {
+        Some(signature) => quote! { Some(#signature) },
+        None => quote! { None },
+    };
+    quote! {
+        Link {
+            module: #module,
+            name: #name,
+            url: std::borrow::Cow::Borrowed(#docsurl),
+            summary: #summary,
+            signature: #signature,
+        }
+    }
+}
+
+/// The `winnow` cell of a row, with links to winnow's docs
+fn winnow_cell(row: &Row) -> String {
+    row.winnow
+        .iter()
+        .map(
+            |Url {
+                 module,
+                 name,
+                 docsurl,
+                 ..
+             }| format!("{module}::[{name}]({docsurl})"),
+        )
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+/// The `signature` cell of a row, with a line for each combinator of the
+/// first cell. It's empty if none of them are nom's functions.
+fn signature_cell(row: &Row) -> String {
+    if row.signatures.iter().all(Option::is_none) {
+        return String::new();
+    }
+    row.signatures
+        .iter()
+        .map(|signature| signature.map(markdown_cell_code).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+/// What kind of input a row's parser gets
+#[derive(Clone, Copy, PartialEq)]
+enum InputKind {
+    Text,
+    Bytes,
+    /// Bit parsers take a tuple of the bytes and the bit in the first byte
+    /// to start at, like `(&[0b1010_0000], 0)`
+    Bits,
+    /// nom_locate's spans wrap text or bytes, like
+    /// `LocatedSpan::new("a\nb")`
+    Span,
+}
+
+/// Turns an input cell into the expression that's fed to the parser, and
+/// what kind of input that is.
+fn input_code(input: &str) -> Result<(Expr, InputKind)> {
+    let mut input_code: Expr = syn::parse_str(input)?;
+    if let Expr::Tuple(tuple) = &mut input_code {
+        if let Some(bytes) = tuple.elems.first_mut() {
+            let (bytes_code, _) = input_code_of(bytes.clone());
+            *bytes = bytes_code;
+        }
+        return Ok((input_code, InputKind::Bits));
+    }
+    if let Expr::Call(call) = &mut input_code {
+        if call.func.to_token_stream().to_string() == "LocatedSpan :: new" {
+            if let Some(fragment) = call.args.first_mut() {
+                let (fragment_code, _) = input_code_of(fragment.clone());
+                *fragment = fragment_code;
+            }
+            // The rows don't import it
+            call.func = parse_quote! { nom_locate::LocatedSpan::new };
+            return Ok((input_code, InputKind::Span));
+        }
+    }
+    Ok(input_code_of(input_code))
+}
+
+/// The text or bytes that an input is made of, as the type that nom's
+/// traits are implemented for
+fn input_code_of(mut input_code: Expr) -> (Expr, InputKind) {
+    let mut kind = InputKind::Text;
+    // Some traits are implemented for slices, but not for references to
+    // arrays. So we add `[..]` to those, to make them slices.
+    if let Expr::Reference(reference) = &input_code {
+        if let Expr::Array(_) = reference.expr.as_ref() {
+            input_code = parse_quote! { #input_code[..] };
+            kind = InputKind::Bytes;
+        }
+    }
+    // And byte strings are &[u8; N], but we want to treat them as &[u8]
+    if let Expr::Lit(ExprLit {
+        lit: Lit::ByteStr(_),
+        ..
+    }) = &input_code
+    {
+        input_code = parse_quote! { #input_code as &[u8] };
+        kind = InputKind::Bytes;
+    }
+    (input_code, kind)
+}
+
+/// Whether an expression refers to `input` anywhere
+fn mentions_input(tokens: TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == "input",
+        TokenTree::Group(group) => mentions_input(group.stream()),
+        _ => false,
+    })
+}
+
+/// Wraps the sub-expressions of a usage that were listed in the `trace`
+/// attribute with the `trace` helper, which records their output as steps.
+/// Sub-expressions are matched by their tokens, so whitespace doesn't matter.
+struct Tracer<'a> {
+    traces: Vec<(String, &'a str)>,
+    found: HashSet<&'a str>,
+}
+
+impl VisitMut for Tracer<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        let tokens = expr.to_token_stream().to_string();
+        if let Some((_, label)) = self.traces.iter().find(|(trace, _)| *trace == tokens) {
+            let label = *label;
+            // As a closure, which works the same with nom 7's `Parser` as
+            // with nom 8's. Its argument isn't named `input`, as that would
+            // make the usage look like it applies the parser itself.
+            *expr = parse_quote! {
+                trace(#label, &trace_steps, {
+                    let mut parser = #expr;
+                    move |traced| nom::Parser::parse(&mut parser, traced)
+                })
+            };
+            self.found.insert(label);
+            return;
+        }
+        syn::visit_mut::visit_expr_mut(self, expr);
+    }
+}
+
+fn trace_usage(usage: &str, traces: &[&str]) -> Result<String> {
+    let mut block: syn::Block = syn::parse_str(&format!("{{ {usage} }}"))?;
+    let traces = traces
+        .iter()
+        .map(|trace| {
+            Ok((
+                syn::parse_str::<Expr>(trace)?.to_token_stream().to_string(),
+                *trace,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut tracer = Tracer {
+        traces,
+        found: HashSet::new(),
+    };
+    tracer.visit_block_mut(&mut block);
+    for (_, trace) in &tracer.traces {
+        assert!(
+            tracer.found.contains(trace),
+            "`{trace}` from `trace` doesn't appear in `{usage}`"
+        );
+    }
+    let statements = block.stmts;
+    Ok(quote! { #(#statements)* }.to_string())
+}
+
+/// Whether a multi-statement usage is only item definitions followed by a
+/// parser, e.g. a function that returns a parser and a call to it. `let`
+/// bindings that don't touch the input count as definitions too.
+fn is_definitions(block: &syn::Block) -> bool {
+    let (last, definitions) = block.stmts.split_last().unwrap();
+    matches!(last, Stmt::Expr(expr, None) if !mentions_input(expr.to_token_stream()))
+        && definitions.iter().all(|statement| match statement {
+            Stmt::Item(_) => true,
+            Stmt::Local(local) => !mentions_input(local.to_token_stream()),
+            _ => false,
+        })
+}
+
+/// The helpers from `nom_cheatsheet_shared` that examples can use
+static SHEET_HELPERS: &[&str] = &["my_alpha1", "number"];
+
+/// The methods of nom's `Parser` trait, which needs to be imported to call
+/// them
+static PARSER_METHODS: &[&str] = &["and", "and_then", "flat_map", "into", "map", "or", "parse"];
+
+/// What goes into the `#[test]` function of a row. It's put together once
+/// all rows are known, as its usage can use combinators that only other
+/// rows import.
+struct TestParts {
+    name: String,
+    /// The row's own imports
+    uses: Vec<syn::ItemUse>,
+    /// The imports of the row's combinators, with the name that the usage
+    /// calls each by. Rows often show several combinators, but only use one.
+    combinator_uses: Vec<(String, syn::ItemUse)>,
+    /// Binds each input, runs the usage on it, and checks the result
+    body: TokenStream,
+    /// Binds each input, runs the usage on it, and prints the result, for
+    /// the program that the Rust Playground gets
+    program_body: TokenStream,
+    /// Every identifier in the body
+    idents: BTreeSet<String>,
+    /// Whether the body calls methods of `Parser`
+    calls_methods: bool,
+}
+
+/// Collects the names in a test's body that could need an import, which are
+/// the first segments of paths, and whether it calls any `Parser` methods,
+/// like `.parse(input)`. Types named like the combinators for primitives,
+/// like `char` in `|c: char|`, are the primitives.
+#[derive(Default)]
+struct Idents {
+    idents: BTreeSet<String>,
+    calls_methods: bool,
+    in_type: bool,
+}
+
+impl VisitMut for Idents {
+    fn visit_path_mut(&mut self, path: &mut syn::Path) {
+        if let Some(first) = path
+            .segments
+            .first()
+            .filter(|_| path.leading_colon.is_none())
+        {
+            let ident = first.ident.to_string();
+            if !(self.in_type && PRIMITIVE_TYPES.contains(&ident.as_str())) {
+                self.idents.insert(ident);
+            }
+        }
+        syn::visit_mut::visit_path_mut(self, path);
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut syn::Type) {
+        let in_type = std::mem::replace(&mut self.in_type, true);
+        syn::visit_mut::visit_type_mut(self, ty);
+        self.in_type = in_type;
+    }
+
+    // Macros aren't parsed, so everything in them might be a name
+    fn visit_macro_mut(&mut self, mac: &mut syn::Macro) {
+        syn::visit_mut::visit_macro_mut(self, mac);
+        let mut tokens = vec![mac.tokens.clone()];
+        while let Some(stream) = tokens.pop() {
+            for token in stream {
+                match token {
+                    TokenTree::Ident(ident) => {
+                        self.idents.insert(ident.to_string());
+                    }
+                    TokenTree::Group(group) => tokens.push(group.stream()),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn visit_expr_method_call_mut(&mut self, call: &mut syn::ExprMethodCall) {
+        let method = call.method.to_string();
+        if PARSER_METHODS.contains(&method.as_str()) && !call.args.is_empty() {
+            self.calls_methods = true;
+        }
+        syn::visit_mut::visit_expr_method_call_mut(self, call);
+    }
+}
+
+/// The names that a `use` brings into scope
+fn use_names(tree: &syn::UseTree, names: &mut BTreeSet<String>) {
+    match tree {
+        syn::UseTree::Path(path) => use_names(&path.tree, names),
+        syn::UseTree::Name(name) => {
+            names.insert(name.ident.to_string());
+        }
+        syn::UseTree::Rename(rename) => {
+            names.insert(rename.rename.to_string());
+        }
+        syn::UseTree::Glob(_) => {}
+        syn::UseTree::Group(group) => {
+            for tree in &group.items {
+                use_names(tree, names);
+            }
+        }
+    }
+}
+
+/// The imports that the test of a row needs. Rows that use fixtures or
+/// helpers from the sheet don't get a test, as those aren't there outside of
+/// it.
+fn test_uses(
+    test: &TestParts,
+    uses: &HashMap<String, Item>,
+    sheet_names: &BTreeSet<String>,
+) -> Option<Vec<syn::ItemUse>> {
+    if !test.idents.is_disjoint(sheet_names) {
+        return None;
+    }
+    let mut imported = BTreeSet::new();
+    for item in &test.uses {
+        use_names(&item.tree, &mut imported);
+    }
+    let mut items = test.uses.clone();
+    // Traits like `ParserExt` only show up in the methods they add
+    for (name, item) in &test.combinator_uses {
+        if test.idents.contains(name) || name.starts_with(char::is_uppercase) {
+            use_names(&item.tree, &mut imported);
+            items.push(item.clone());
+        }
+    }
+    for ident in &test.idents {
+        if let Some(Item::Use(item)) = uses.get(ident).filter(|_| !imported.contains(ident)) {
+            let mut item = item.clone();
+            item.attrs.clear();
+            items.push(item);
+        }
+    }
+    if test.idents.contains("IResult") && !imported.contains("IResult") {
+        items.push(parse_quote! { use nom::IResult; });
+    }
+    if test.calls_methods && !imported.contains("Parser") {
+        items.push(parse_quote! { use nom::Parser; });
+    }
+    items.sort_by_key(|item| item.to_token_stream().to_string());
+    items.dedup_by_key(|item| item.to_token_stream().to_string());
+    Some(items)
+}
+
+/// The `#[test]` function of a row, with `__expected_N__` strings where the
+/// `Debug` output of the result for the Nth input goes
+fn test_code(
+    test: &TestParts,
+    uses: &HashMap<String, Item>,
+    sheet_names: &BTreeSet<String>,
+) -> Option<String> {
+    let items = test_uses(test, uses, sheet_names)?;
+    // Names of types and traits, like `ErrorTree`, in snake case
+    let mut name = "test".to_string();
+    for c in test.name.chars() {
+        if c.is_uppercase() || name == "test" {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    let name = format_ident!("{name}");
+    let body = &test.body;
+    let file: syn::File = parse_quote! {
+        #[test]
+        fn #name() {
+            #(#items)*
+            #body
+        }
+    };
+    Some(prettyplease::unparse(&file))
+}
+
+/// A program that prints what the example of a row gives, for running it on
+/// the Rust Playground. That only has nom itself, so rows that use other
+/// crates don't get one.
+fn program_code(
+    test: &TestParts,
+    uses: &HashMap<String, Item>,
+    sheet_names: &BTreeSet<String>,
+) -> Option<String> {
+    let items = test_uses(test, uses, sheet_names)?;
+    let body = &test.program_body;
+    let file: syn::File = parse_quote! {
+        #(#items)*
+
+        fn main() {
+            #body
+        }
+    };
+    let code = prettyplease::unparse(&file);
+    (!code.contains("nom_supreme") && !code.contains("nom_locate")).then_some(code)
+}
+
+/// Whether a usage makes a parser by constructing a struct that implements
+/// `Parser`, like `Keyword("let")`, `Digits { min: 2 }`, or
+/// `Keyword::new("let")`, which can't be called like a function
+fn is_constructor(expr: &Expr) -> bool {
+    let uppercase =
+        |segment: &syn::PathSegment| segment.ident.to_string().starts_with(char::is_uppercase);
+    match expr {
+        Expr::Struct(_) => true,
+        Expr::Path(path) => path.path.segments.last().is_some_and(uppercase),
+        Expr::Call(call) => match call.func.as_ref() {
+            Expr::Path(path) => {
+                let segments = path.path.segments.iter().collect::<Vec<_>>();
+                segments
+                    .iter()
+                    .rev()
+                    .take(2)
+                    .any(|segment| uppercase(segment))
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Applies the parser that a usage makes to `input`. Functions that return
+/// parsers get called with it, while parser structs, and all parsers from
+/// nom 8 on, get `.parse(input)`.
+fn apply_to_input(expr: &Expr, method_calls: bool) -> TokenStream {
+    if !method_calls && !is_constructor(expr) {
+        return quote! { #expr(input) };
+    }
+    match expr {
+        Expr::Path(_) | Expr::Call(_) | Expr::MethodCall(_) | Expr::Struct(_) => {
+            quote! { #expr.parse(input) }
+        }
+        _ => quote! { (#expr).parse(input) },
+    }
+}
+
+/// The statements that bind the `output` of a usage to what it returns for
+/// `input`. Also returns whether they record steps along the way.
+fn usage_assignment(
+    usage_code: &str,
+    output_type_code: &syn::Type,
+    method_calls: bool,
+) -> (TokenStream, bool) {
+    // Usages can also be several statements. If those are just definitions of
+    // helpers, like a function returning a parser, the last expression gets
+    // applied to the input as usual. Otherwise the statements are run one
+    // after the other, and intermediate values get shown as steps.
+    let block = syn::parse_str::<syn::Block>(&format!("{{ {usage_code} }}"))
+        .ok()
+        .filter(|block| block.stmts.len() > 1);
+    match block {
+        Some(mut block) if is_definitions(&block) => {
+            let Some(Stmt::Expr(expr, None)) = block.stmts.pop() else {
+                unreachable!();
+            };
+            let definitions = block.stmts;
+            let applied = apply_to_input(&expr, method_calls);
+            let assignment = quote! {
+                #(#definitions)*
+                let output: #output_type_code = #applied;
+            };
+            (assignment, false)
+        }
+        Some(block) => stepwise_assignment(block, output_type_code),
+        None => {
+            if let Ok(Stmt::Local(local)) = syn::parse_str::<Stmt>(&format!("{usage_code};")) {
+                assert!(local
+                    .pat
+                    .to_token_stream()
+                    .to_string()
+                    .starts_with("output"));
+                let pat = &local.pat;
+                let init = local.init.expect("`let output` needs a value");
+                let applied = match mentions_input(init.expr.to_token_stream()) {
+                    true => init.expr.to_token_stream(),
+                    false => apply_to_input(&init.expr, method_calls),
+                };
+                (quote! { let #pat = #applied; }, false)
+            } else {
+                let expr: Expr = syn::parse_str(usage_code).unwrap();
+                // Usages like `tag("abc").parse(input)` already apply the
+                // parser themselves.
+                let assignment = if mentions_input(expr.to_token_stream()) {
+                    quote! {
+                        let output: #output_type_code = #expr;
+                    }
+                } else {
+                    let applied = apply_to_input(&expr, method_calls);
+                    quote! {
+                        let output: #output_type_code = #applied;
+                    }
+                };
+                (assignment, false)
+            }
+        }
+    }
+}
+
+/// Turns the statements of a multi-statement usage into a closure that is
+/// applied to the input. Along the way it records the value of every
+/// `let (input, name) = ...` binding, so they can be shown as steps. Also
+/// returns whether there were any such steps.
+fn stepwise_assignment(block: syn::Block, output_type: &syn::Type) -> (TokenStream, bool) {
+    let mut statements = Vec::new();
+    let mut has_steps = false;
+    for statement in block.stmts {
+        let step = match &statement {
+            Stmt::Local(Local {
+                pat: Pat::Tuple(tuple),
+                ..
+            }) if tuple.elems.len() == 2 => match &tuple.elems[1] {
+                Pat::Ident(ident) => Some(ident.ident.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        statements.push(statement.to_token_stream());
+        if let Some(name) = step {
+            let label = name.to_string();
+            statements.push(quote! {
+                steps.push(format_step(#label, &#name));
+            });
+            has_steps = true;
+        }
+    }
+    // The closure is there so that `?` can be used in the statements
+    let closure = quote! {
+        #[allow(clippy::redundant_closure_call)]
+        let output: #output_type = (|input| -> #output_type { #(#statements)* })(input);
+    };
+    if has_steps {
+        (quote! { let mut steps = Vec::new(); #closure }, true)
+    } else {
+        (closure, false)
+    }
+}
+
+/// What the generated code for all rows is made of
+#[derive(Default)]
+struct Generated {
+    /// All the statements that go into `generate()`, which calls the
+    /// functions of the sections for most of the sheet
+    statements: Vec<Stmt>,
+    /// Every row with an example gets a function of its own, so that
+    /// compiler warnings can be traced back to the row that caused them. The
+    /// labels are there so that those rows can be pointed out to a human.
+    /// The functions of the section that's being generated go in here,
+    /// until they go in its file.
+    row_functions: Vec<Item>,
+    row_labels: Vec<String>,
+    /// The same examples also get built into a WebAssembly module, where
+    /// each row has a function that runs its parser on whatever input it
+    /// gets
+    runners: Vec<Item>,
+    run_arms: Vec<TokenStream>,
+    /// And a test to copy, if it can have one
+    tests: Vec<Option<TestParts>>,
+    /// The code that makes each row's output cell, for showing it
+    code: Vec<String>,
+    /// Rows that are in more than one template are most likely copies
+    label_templates: HashMap<String, usize>,
+    /// The modules that the sections are in, with their files included
+    section_modules: Vec<TokenStream>,
+    /// What the cache keeps the output of each row under, and each row's
+    /// level and what it can be filtered by, for running the examples of
+    /// the rows that get written
+    row_hashes: Vec<String>,
+    row_levels: Vec<TokenStream>,
+    row_filter_names: Vec<TokenStream>,
+    /// What all rows can be filtered by together
+    filter_names: BTreeSet<String>,
+    /// The arms of the `match` that runs a row's example by its index
+    output_arms: Vec<TokenStream>,
+}
+
+impl Generated {
+    /// Adds the code for a row that has an example, and returns the
+    /// statements that write it to the markdown
+    #[allow(clippy::too_many_lines)]
+    fn example_row(
+        &mut self,
+        row: &Row,
+        usage: &str,
+        columns: &[&str],
+        fixtures_code: &str,
+        template_paths: &[PathBuf],
+    ) -> Result<Vec<Stmt>> {
+        let mut row_statements: Vec<Stmt> = Vec::new();
+        let urls = &row.combinators;
+        let imports = &row.imports;
+        let (cfg, not_cfg) = features_cfg(&row.requires);
+        let level_code = level_code(row.level);
+        let level_marker = level_marker(row.level);
+        // A row can have several inputs, to show how the same parser behaves
+        // on different ones, like on text and on bytes, or on input that it
+        // doesn't match.
+        let input_codes = row
+            .inputs
+            .iter()
+            .map(|input| input_code(&input.code))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Some examples need an explicit output type because it can't be
+        // inferred. Those end in a `-> Type` annotation, which becomes the
+        // type of the `output` binding. Writing out the whole
+        // "let output: Type = ..." still works too.
+        let (usage, output_type) = split_output_type(usage);
+        let mut usage_code = usage.to_string();
+        let untraced_usage_code = usage_code.clone();
+        if !row.traces.is_empty() {
+            usage_code = trace_usage(&usage_code, &row.traces)?;
+        }
+        let output_type_code: syn::Type = match output_type {
+            Some(output_type) => syn::parse_str(output_type)?,
+            None => parse_quote! { IResult<_, _> },
+        };
+        let (assignment, mut has_steps) =
+            usage_assignment(&usage_code, &output_type_code, row.method_calls);
+        // The test runs the usage as written, without tracing
+        let (test_assignment, test_has_steps) = match row.traces.is_empty() {
+            true => (assignment.clone(), has_steps),
+            false => usage_assignment(&untraced_usage_code, &output_type_code, row.method_calls),
+        };
+        let assignment = if row.traces.is_empty() {
+            assignment
+        } else {
+            assert!(
+                !has_steps,
+                "`trace` can't be combined with steps from `let` bindings: {usage}"
+            );
+            has_steps = true;
+            quote! {
+                let trace_steps = RefCell::new(Vec::new());
+                #assignment
+                let steps = trace_steps.into_inner();
+            }
+        };
+
+        let numbers = numbers_code(row.numbers);
+        let whitespace = match row.visible_whitespace {
+            true => quote! { Whitespace::Visible },
+            false => quote! { Whitespace::Plain },
+        };
+        let mut output_parts = Vec::new();
+        if has_steps {
+            output_parts.push(quote! { format_steps(&steps) });
+        }
+        output_parts.push(quote! { format_iresult(&input, &output, #numbers, #whitespace) });
+        // Optionally show what nom's generic parameters were inferred as,
+        // which is hard to figure out from the docs alone for some
+        // combinators.
+        if row.types {
+            output_parts.push(quote! { format_types(&output) });
+        }
+        let format_output = match output_parts.as_slice() {
+            [part] => quote! { #part },
+            parts => quote! { [#(#parts),*].join("<br>") },
+        };
+
+        // Each input gets its own block, so that the types are inferred
+        // separately for each of them. The `Debug` output of the result is
+        // for the row's test.
+        // Inputs that are marked to fail stop the sheet from being generated
+        // if they don't. They get the row's label from a variable, so that
+        // the code, which the row's cache key is made from, doesn't change
+        // when rows are added above it.
+        let index = self.row_labels.len();
+        let outputs = input_codes
+            .iter()
+            .zip(&row.inputs)
+            .map(|((input_code, _), input)| {
+                let check = input.fails.then(|| {
+                    let code = &*input.code;
+                    quote! { expect_error(&output, label, #code)?; }
+                });
+                // The streaming versions get the same input, in a block of
+                // their own where their imports shadow the complete ones
+                let streaming_uses = &row.streaming_uses;
+                let format_output = match streaming_uses.is_empty() {
+                    true => quote! { #format_output },
+                    false => quote! {
+                        {
+                            let complete = #format_output;
+                            let streaming = {
+                                #(#streaming_uses)*
+                                #assignment
+                                #format_output
+                            };
+                            if streaming == complete {
+                                format!("{complete}<br><br>Streaming: the same")
+                            } else {
+                                format!("{complete}<br><br>Streaming:<br>{streaming}")
+                            }
+                        }
+                    },
+                };
+                quote! {
+                    {
+                        let input = #input_code;
+                        #assignment
+                        #check
+                        (#format_output, format!("{output:?}"))
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        let output = quote! { join_outputs(vec![#(#outputs),*]) };
+        // The same, as readable code, without what only checks the outputs
+        let mut import_items = row.imports.items.clone();
+        for item in &mut import_items {
+            if let Item::Use(item) = item {
+                item.attrs.clear();
+            }
+        }
+        let cells = input_codes
+            .iter()
+            .map(|(input_code, _)| {
+                quote! {
+                    let input = #input_code;
+                    #assignment
+                    #format_output
+                }
+            })
+            .collect::<Vec<_>>();
+        let cell = match cells.as_slice() {
+            [cell] => cell.clone(),
+            cells => quote! { [#({ #cells }),*].join("<br><br>") },
+        };
+        let code_file: syn::File = parse_quote! {
+            #(#import_items)*
+
+            fn output_cell() -> String {
+                #cell
+            }
+        };
+        self.code.push(prettyplease::unparse(&code_file));
+
+        let usage = match output_type {
+            Some(output_type) => format!("let output: {output_type} = {usage}"),
+            None => usage.to_string(),
+        };
+        let usage_cell = markdown_cell_code_lines(&usage);
+        // For the copy button that the HTML puts on the code
+        let usage_cell = format!("<!-- usage -->{usage_cell}");
+        let input = row
+            .inputs
+            .iter()
+            .map(|input| markdown_format_code(&input.code))
+            .collect::<Vec<_>>()
+            .join("<br>");
+        // Byte inputs link to the ASCII table
+        let input_cell = row
+            .inputs
+            .iter()
+            .zip(&input_codes)
+            .map(|(input, (_, kind))| {
+                let input = match row.visible_whitespace {
+                    true => markdown_cell_code(&visible_whitespace(&input.code)),
+                    false => markdown_cell_code(&input.code),
+                };
+                match kind {
+                    InputKind::Bytes => format!("[{input}]({BYTE_REFERENCE_ANCHOR})"),
+                    InputKind::Text | InputKind::Bits | InputKind::Span => input,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("<br>");
+        let row_ident = format_ident!("row_{index}");
+        self.tests.push((!test_has_steps).then(|| {
+            let body = input_codes
+                .iter()
+                .enumerate()
+                .map(|(input_index, (input_code, _))| {
+                    let expected = format!("__expected_{input_index}__");
+                    quote! {
+                        let input = #input_code;
+                        #test_assignment
+                        assert_eq!(format!("{output:?}"), #expected);
+                    }
+                })
+                .collect::<TokenStream>();
+            let program_body = input_codes
+                .iter()
+                .map(|(input_code, _)| {
+                    quote! {
+                        let input = #input_code;
+                        #test_assignment
+                        println!("{output:?}");
+                    }
+                })
+                .collect::<TokenStream>();
+            let mut idents = Idents::default();
+            idents.visit_block_mut(&mut parse_quote! { { #body } });
+            TestParts {
+                name: urls
+                    .first()
+                    .map_or_else(|| "example".to_string(), |url| url.name.clone()),
+                uses: row.test_uses.clone(),
+                combinator_uses: row.test_combinator_uses.clone(),
+                body,
+                program_body,
+                idents: idents.idents,
+                calls_methods: idents.calls_methods,
+            }
+        }));
+        // The first input decides whether the parser gets text or bytes in
+        // the WebAssembly module.
+        let (first_input_code, first_kind) = &input_codes[0];
+        let steps = if has_steps {
+            quote! { Some(steps) }
+        } else {
+            quote! { None }
+        };
+        // Bit parsers start at the first bit of whatever they get
+        let decode_input = match first_kind {
+            InputKind::Bits => quote! { let input = (bytes, 0_usize); },
+            InputKind::Text | InputKind::Bytes => quote! {
+                let Some(input) = decode_like(bytes, &(#first_input_code)) else {
+                    return invalid_input();
+                };
+            },
+            // And spans get the text or bytes wrapped up like the row's own
+            InputKind::Span => quote! {
+                let Some(fragment) = decode_like(bytes, &(#first_input_code).into_fragment()) else {
+                    return invalid_input();
+                };
+                let input = nom_locate::LocatedSpan::new(fragment);
+            },
+        };
+        let run_ident = format_ident!("run_{index}");
+        self.runners.push(parse_quote! {
+            #cfg
+            fn #run_ident(bytes: &[u8]) -> Value {
+                #imports
+                #decode_input
+                #assignment
+                run_result(&input, &output, #numbers, #whitespace, #steps)
+            }
+        });
+        self.run_arms.push(quote! {
+            #cfg
+            #index => #run_ident(bytes),
+        });
+        let links = urls.iter().map(|url| link_code(url, signature(url)));
+        let winnow_links = row.winnow.iter().map(|url| link_code(url, None));
+        let winnow_cell = winnow_cell(row);
+        let signature_cell = signature_cell(row);
+        let tags = &row.tags;
+        let plain_inputs = row.inputs.iter().map(|input| &*input.code);
+        let full_description = row.template_description;
+        let section = row.section;
+        let permalink = &row.permalink;
+        let description = row.description.as_str();
+        let first_cell = row.first_cell.as_str();
+        let edit = match &row.edit {
+            Some(url) => quote! { Some(#url) },
+            None => quote! { None },
+        };
+        // Everything the output depends on in the template, for finding it
+        // in the example cache
+        let mut hasher = Sha256::new();
+        hasher.update(imports.to_token_stream().to_string());
+        hasher.update(output.to_string());
+        hasher.update(fixtures_code);
+        let row_hash = format!("{:x}", hasher.finalize());
+        // Rows that say what their output should be stop the sheet from
+        // being generated if it isn't, which makes them tests of nom
+        let expect_output = row.expected.map(|expected| {
+            quote! { expect_output(&output, #expected, label)?; }
+        });
+        let label = quote! { let label = ROW_LABELS[#index]; };
+        let output_label = row
+            .inputs
+            .iter()
+            .any(|input| input.fails)
+            .then(|| label.clone());
+        let label = expect_output.is_some().then_some(label);
+        let cells = columns.iter().map(|column| match *column {
+            "parser" => quote! { #first_cell },
+            "usage" => quote! { #usage_cell },
+            "input" => quote! { #input_cell },
+            "output" => quote! { &output_cell },
+            "description" => quote! { #description },
+            "winnow" => quote! { #winnow_cell },
+            "signature" => quote! { #signature_cell },
+            extra => {
+                let cell = row.extra.get(extra).copied().unwrap_or_default();
+                quote! { #cell }
+            }
+        });
+        // The examples all run before the sheet gets written, in parallel,
+        // so running the row's example is a function of its own
+        let output_ident = format_ident!("row_{index}_output");
+        let section_module = format_ident!("section_{}", self.section_modules.len());
+        self.row_hashes.push(row_hash);
+        self.row_levels.push(level_code.clone());
+        let filter_names = filter_names(row);
+        self.row_filter_names.push(quote! { &[#(#filter_names),*] });
+        self.output_arms.push(quote! {
+            #cfg
+            #index => #section_module::#output_ident(),
+        });
+        self.row_functions.push(parse_quote! {
+            #cfg
+            fn #row_ident(
+                markdown: &mut Vec<u8>,
+                rows: &mut Vec<Row>,
+                timings: bool,
+                outputs: &mut [Option<Evaluated>],
+            ) -> std::io::Result<()> {
+                #label
+                let (output, debugs, elapsed) = outputs[#index]
+                    .take()
+                    .expect("Every row that gets written has been run");
+                #expect_output
+                let timing = if timings { format_timing(elapsed) } else { String::new() };
+                let output_cell = format!("{output}{timing}");
+                let cells: &[&str] = &[#(#cells),*];
+                let offset = markdown.len();
+                writeln!(markdown, "| {}{} |", cells.join(" | "), #level_marker)?;
+                rows.push(Row {
+                    id: #index,
+                    section: #section,
+                    combinators: vec![#(#links),*],
+                    usage: #usage,
+                    inputs: vec![#(#plain_inputs),*],
+                    output,
+                    description: #full_description,
+                    anchor: #permalink,
+                    level: #level_code,
+                    elapsed_ns: u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX),
+                    test: TEST_TEMPLATES[#index].map(|test| fill_test(test, &debugs)),
+                    program: PROGRAMS[#index],
+                    code: ROW_CODE[#index],
+                    edit: #edit,
+                    winnow: vec![#(#winnow_links),*],
+                    tags: vec![#(#tags),*],
+                    offset,
+                });
+                Ok(())
+            }
+        });
+        self.row_functions.push(parse_quote! {
+            #cfg
+            pub(super) fn #output_ident() -> std::io::Result<Output> {
+                #imports
+                #output_label
+                Ok(#output)
+            }
+        });
+        let name = match row.regex {
+            Some(regex) => regex.to_string(),
+            None => urls.first().map_or(String::new(), |url| url.name.clone()),
+        };
+        let label = format!(
+            "{name}: {} on {}",
+            usage.replace('\n', " "),
+            input.replace("<br>", " and ")
+        );
+        if let Some(other) = self.label_templates.insert(label.clone(), row.template) {
+            assert!(
+                other == row.template,
+                "Row `{label}` is in both {} and {}",
+                template_paths[other].display(),
+                template_paths[row.template].display()
+            );
+        }
+        self.row_labels.push(label);
+        row_statements.push(parse_quote! {
+            #cfg
+            #row_ident(markdown, rows, timings, outputs)?;
+        });
+        if !row.requires.is_empty() {
+            let features = row
+                .requires
+                .iter()
+                .map(|feature| markdown_format_code(feature))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let output =
+                format!("<kbd>Needs {features}</kbd><br>Not available with current features");
+            let row = table_row(
+                columns,
+                [
+                    first_cell,
+                    &usage_cell,
+                    &input_cell,
+                    &output,
+                    description,
+                    &winnow_cell,
+                    &signature_cell,
+                ],
+                &row.extra,
+                &level_marker,
+            );
+            row_statements.push(parse_quote! {
+                #not_cfg
+                writeln!(markdown, "{}", #row)?;
+            });
+        }
+        Ok(row_statements)
+    }
+}
+
+/// What `--only` and `--exclude` can pick a row by: the crates or top level
+/// modules of nom that its combinators are in, like `bytes`, and its tags
+fn filter_names(row: &Row) -> Vec<String> {
+    let mut names = Vec::new();
+    for url in &row.combinators {
+        let module = url.module.split("::").next().unwrap().to_string();
+        if !names.contains(&module) {
+            names.push(module);
+        }
+    }
+    names.extend(row.tags.iter().map(|tag| tag.to_string()));
+    names
+}
+
+/// The `Level` variant for one of `LEVELS`
+fn level_code(level: &str) -> TokenStream {
+    let level_variant = format_ident!("{}{}", level[..1].to_uppercase(), &level[1..]);
+    quote! { Level::#level_variant }
+}
+
+/// The markdown keeps the levels that aren't the default, so that the HTML
+/// can hide rows by level
+fn level_marker(level: &str) -> String {
+    if level == "intermediate" {
+        String::new()
+    } else {
+        format!(" <!-- level = {level} -->")
+    }
+}
+
+/// The generated files, which go in `OUT_DIR`
+pub struct Code {
+    /// `generated.rs`, with `generate()`, which writes the sheet
+    pub generated: syn::File,
+    /// The files of the sections' modules, `generated_section_<N>.rs`
+    pub sections: Vec<syn::File>,
+    /// `wasm_rows.rs`, for the WebAssembly module
+    pub wasm: syn::File,
+}
+
+/// The statements that write some text of the sheet, with the outputs of
+/// code blocks where their markers are. Those are mostly whole parsers,
+/// which need nom's `alloc` feature, so they only run with it.
+fn text_statements(text: &str) -> Vec<Stmt> {
+    let mut statements = Vec::new();
+    let mut rest = text;
+    while let Some((before, name, after)) = split_output_marker(rest) {
+        let module = format_ident!("{name}");
+        statements.push(parse_quote! {
+            write!(markdown, "{}", #before)?;
+        });
+        statements.push(parse_quote! {
+            #[cfg(feature = "alloc")]
+            write!(markdown, "{}", block_outputs::#module::output())?;
+        });
+        rest = after;
+    }
+    statements.push(parse_quote! {
+        write!(markdown, "{}", #rest)?;
+    });
+    statements
+}
+
+/// The code for the sheet and the code for the WebAssembly module, with the
+/// columns picked by their names, or the ones of the template
+pub fn generate(
+    transformed: &Transformed,
+    columns: Option<&[&str]>,
+    fixtures_modules: &[Item],
+    output_modules: &[Item],
+    template_paths: &[PathBuf],
+) -> Result<Code> {
+    let mut generated = Generated::default();
+    // Every row's cache key has the fixtures of its template in it, which
+    // are the same for all of them, and slow to turn into text for each
+    let fixtures_codes = fixtures_modules
+        .iter()
+        .map(|module| module.to_token_stream().to_string())
+        .collect::<Vec<_>>();
+    let mut section_files = Vec::new();
+    for template in &transformed.templates {
+        for section in &template.sections {
+            let section_start = generated.statements.len();
+            // Tables only get the columns they have in the template, and the
+            // ones that aren't in any
+            let template_columns = template_columns(section.columns);
+            let columns = &match columns {
+                Some(columns) => columns
+                    .iter()
+                    .copied()
+                    .filter(|column| {
+                        COLUMN_NAMES[TEMPLATE_COLUMNS..].contains(column)
+                            || template_columns.contains(column)
+                    })
+                    .collect::<Vec<_>>(),
+                None => template_columns.clone(),
+            };
+            // The table header gets taken out again if none of the rows are
+            // at the level being generated. It's kept as it is in the
+            // template, unless columns need to be left out or moved around.
+            let header = if *columns == template_columns {
+                section.header.to_string()
+            } else {
+                table_header(columns, section.columns, section.header)
+            };
+            let header_len = header.len();
+            generated.statements.extend(text_statements(&section.text));
+            generated.statements.push(parse_quote! {
+                let table_start = markdown.len();
+            });
+            generated.statements.push(parse_quote! {
+                write!(markdown, "{}", #header)?;
+            });
+
+            for row in &section.rows {
+                let level_code = level_code(row.level);
+                let level_marker = level_marker(row.level);
+                // Everything for this row goes in one block, so that the
+                // level-specific variants of the sheet can leave it out
+                let mut row_statements: Vec<Stmt> = match (row.inputs.is_empty(), row.usage) {
+                    (true, None) => {
+                        let row = table_row(
+                            columns,
+                            [
+                                row.first_cell.as_str(),
+                                "",
+                                "",
+                                "",
+                                row.description.as_str(),
+                                &winnow_cell(row),
+                                &signature_cell(row),
+                            ],
+                            &row.extra,
+                            &level_marker,
+                        );
+                        vec![parse_quote! {
+                            {
+                                writeln!(markdown, "{}", #row)?;
+                            }
+                        }]
+                    }
+                    (false, None) | (true, Some(_)) => {
+                        panic!("Both usage and input must be present, or neither.");
+                    }
+                    (false, Some(usage)) => generated.example_row(
+                        row,
+                        usage,
+                        columns,
+                        &fixtures_codes[row.template],
+                        template_paths,
+                    )?,
+                };
+                // Continuations are only for the description
+                if columns.contains(&"description") {
+                    for continuation in &row.continuations {
+                        let row = table_row(
+                            columns,
+                            ["", "", "", "", continuation, "", ""],
+                            &HashMap::new(),
+                            &level_marker,
+                        );
+                        row_statements.push(parse_quote! {
+                            writeln!(markdown, "{}", #row)?;
+                        });
+                    }
+                }
+                let filter_names = filter_names(row);
+                generated.filter_names.extend(filter_names.iter().cloned());
+                generated.statements.push(parse_quote! {
+                    if #level_code <= max_level && filter.keeps(&[#(#filter_names),*]) {
+                        #(#row_statements)*
+                    }
+                });
+            }
+            generated.statements.push(parse_quote! {
+                if markdown.len() == table_start + #header_len {
+                    markdown.truncate(table_start);
+                }
+            });
+
+            // Each section goes in a module of its own, in a file of its
+            // own, so that the sheet isn't one huge function and the
+            // sections can be compiled in parallel
+            let statements = generated.statements.split_off(section_start);
+            let row_functions = std::mem::take(&mut generated.row_functions);
+            let section_module = format_ident!("section_{}", section_files.len());
+            let file_name = format!("generated_{section_module}.rs");
+            let doc = match headings(&section.text).next_back() {
+                Some(heading) => format!(" Writes the \"{heading}\" section of the sheet"),
+                None => " Writes a section of the sheet".to_string(),
+            };
+            section_files.push(parse_quote! {
+                use super::*;
+
+                #[doc = #doc]
+                // Without the features its rows need, a section can have
+                // nothing left that runs examples or adds rows
+                #[allow(clippy::too_many_lines, clippy::ptr_arg, unused_variables)]
+                pub fn generate(
+                    markdown: &mut Vec<u8>,
+                    rows: &mut Vec<Row>,
+                    max_level: Level,
+                    filter: &Filter,
+                    timings: bool,
+                    outputs: &mut [Option<Evaluated>],
+                ) -> std::io::Result<()> {
+                    #(#statements)*
+                    Ok(())
+                }
+
+                #(#row_functions)*
+            });
+            generated.statements.push(parse_quote! {
+                #section_module::generate(&mut markdown, &mut rows, max_level, filter, timings, &mut outputs)?;
+            });
+            generated.section_modules.push(quote! {
+                mod #section_module {
+                    include!(concat!(env!("OUT_DIR"), "/", #file_name));
+                }
+            });
+        }
+        generated
+            .statements
+            .extend(text_statements(&template.remainder));
+    }
+    generated
+        .statements
+        .extend(text_statements(&transformed.closing));
+
+    let Generated {
+        statements,
+        section_modules,
+        row_labels,
+        row_hashes,
+        row_levels,
+        row_filter_names,
+        filter_names,
+        output_arms,
+        runners,
+        run_arms,
+        tests,
+        code,
+        ..
+    } = generated;
+    let sheet_names = fixtures_modules
+        .iter()
+        .flat_map(fixture_names)
+        .chain(SHEET_HELPERS.iter().map(|name| name.to_string()))
+        .collect::<BTreeSet<_>>();
+    let test_templates = tests.iter().map(|test| {
+        match test
+            .as_ref()
+            .and_then(|test| test_code(test, &transformed.uses, &sheet_names))
+        {
+            Some(code) => quote! { Some(#code) },
+            None => quote! { None },
+        }
+    });
+    let test_templates = quote! { #(#test_templates),* };
+    let programs = tests.iter().map(|test| {
+        match test
+            .as_ref()
+            .and_then(|test| program_code(test, &transformed.uses, &sheet_names))
+        {
+            Some(code) => quote! { Some(#code) },
+            None => quote! { None },
+        }
+    });
+    let anchor_docs = transformed
+        .anchor_docs
+        .iter()
+        .map(|(anchor, docsurl)| quote! { (#anchor, #docsurl) });
+    let mut uses = transformed.uses.values().cloned().collect::<Vec<_>>();
+    uses.sort_by_key(|item| item.to_token_stream().to_string());
+
+    let generated_file: syn::File = parse_quote! {
+        #(#uses)*
+        #[allow(unused_imports)]
+        use nom::Parser;
+        use std::{cell::RefCell, io::Write};
+        use fixtures::*;
+        use nom_cheatsheet_shared::{format_step, my_alpha1, number, trace};
+        use super::{
+            Cache, Evaluated, Filter, IResult, Level, Link, Numbers, Output, Row, Sheet, Whitespace, evaluate_rows,
+            expect_error, expect_output, fill_test, format_iresult, format_steps, format_timing,
+            format_types, join_outputs, str,
+        };
+
+        /// Generates the sheet, leaving out the rows for levels above
+        /// `max_level`. With `timings`, the output cells also say how long
+        /// running the example took. Outputs come from `cache` when it has
+        /// them, so timings should come with a disabled cache. The examples
+        /// run in parallel, unless they're being timed.
+        pub fn generate(max_level: Level, timings: bool, cache: &mut Cache) -> std::io::Result<Sheet> {
+            generate_filtered(max_level, &Filter::default(), timings, cache)
+        }
+
+        /// Generates the sheet like `generate`, with only the rows that
+        /// `filter` keeps, and only the sections that have any of them
+        pub fn generate_filtered(
+            max_level: Level,
+            filter: &Filter,
+            timings: bool,
+            cache: &mut Cache,
+        ) -> std::io::Result<Sheet> {
+            let mut outputs = evaluate_rows(
+                ROW_HASHES,
+                |index| ROW_LEVELS[index] <= max_level && filter.keeps(ROW_FILTER_NAMES[index]),
+                row_output,
+                !timings,
+                cache,
+            )?;
+            let mut markdown = Vec::new();
+            let mut rows = Vec::new();
+            #(#statements)*
+            let sheet = Sheet { markdown, rows };
+            Ok(if filter.is_empty() {
+                sheet
+            } else {
+                sheet.without_empty_sections().without_dangling_links(ANCHOR_DOCS)
+            })
+        }
+
+        /// The docs that links to the anchors of rows go to instead when a
+        /// filter took those rows out
+        static ANCHOR_DOCS: &[(&str, &str)] = &[#(#anchor_docs),*];
+
+        /// Describes each `row_N` function, in order
+        pub static ROW_LABELS: &[&str] = &[#(#row_labels),*];
+
+        /// What the example cache keeps the output of each row under
+        static ROW_HASHES: &[&str] = &[#(#row_hashes),*];
+
+        /// The level of each row, for running only the examples of those
+        /// that get written
+        static ROW_LEVELS: &[Level] = &[#(#row_levels),*];
+
+        /// What `--only` and `--exclude` can pick each row by
+        static ROW_FILTER_NAMES: &[&[&str]] = &[#(#row_filter_names),*];
+
+        /// What `--only` and `--exclude` can pick rows by, in order
+        pub static FILTER_NAMES: &[&str] = &[#(#filter_names),*];
+
+        /// Runs the example of a row, or returns `None` if it isn't compiled
+        /// in because of the crate features
+        #[allow(clippy::too_many_lines)]
+        fn row_output(index: usize) -> Option<std::io::Result<Output>> {
+            Some(match index {
+                #(#output_arms)*
+                _ => return None,
+            })
+        }
+
+        /// The test of each row, in order, with placeholders for what it
+        /// expects
+        static TEST_TEMPLATES: &[Option<&str>] = &[#test_templates];
+
+        /// The program of each row for the Rust Playground, in order
+        static PROGRAMS: &[Option<&str>] = &[#(#programs),*];
+
+        /// The code that makes the output cell of each row, in order
+        static ROW_CODE: &[&str] = &[#(#code),*];
+
+        #(#fixtures_modules)*
+
+        /// The code blocks of the templates whose output the sheet shows,
+        /// which run while the sheet is generated
+        #[cfg(feature = "alloc")]
+        mod block_outputs {
+            #(#output_modules)*
+        }
+
+        #(#section_modules)*
+    };
+
+    let wasm_file: syn::File = parse_quote! {
+        #(#uses)*
+        #[allow(unused_imports)]
+        use nom::Parser;
+        use std::cell::RefCell;
+        use fixtures::*;
+        use nom_cheatsheet_shared::{format_step, my_alpha1, number, trace};
+        use super::{IResult, Numbers, Value, Whitespace, decode_like, invalid_input, run_result, str};
+
+        /// Describes each row, in order. The index is the id to run it by.
+        pub static ROW_LABELS: &[&str] = &[#(#row_labels),*];
+
+        /// Runs the parser of a row on `bytes`, or returns `None` if there's
+        /// no such row
+        #[allow(clippy::too_many_lines)]
+        pub fn run_row(index: usize, bytes: &[u8]) -> Option<Value> {
+            Some(match index {
+                #(#run_arms)*
+                _ => return None,
+            })
+        }
+
+        #(#fixtures_modules)*
+
+        #(#runners)*
+    };
+    Ok(Code {
+        generated: generated_file,
+        sections: section_files,
+        wasm: wasm_file,
+    })
+}
@@ -0,0 +1,448 @@
+//! Turns the Rust code blocks of the templates into examples, so that they
+//! get tested along with everything else, and collects the fixtures that the
+//! rows can use.
+
+use crate::Result;
+use nom::{branch::alt, multi::many1};
+use nom_cheatsheet_core::{parse_code_block, parse_outside_code_blocks, CodeBlock, Component};
+use nom_cheatsheet_shared::markdown_format_code;
+use quote::format_ident;
+use std::{collections::HashMap, fs, path::Path};
+use syn::{parse_quote, Item};
+
+/// What a Rust code block can be marked with after `rust,`, like rustdoc's
+/// attributes for doc tests
+static CODE_BLOCK_ATTRIBUTES: &[&str] = &[
+    "compile_fail",
+    "fixture",
+    "no_run",
+    "recipe",
+    "should_panic",
+    "show-output",
+];
+
+/// What the lines of a `rust,recipe` block with the inputs to run it on
+/// start with, like `// input: "#2F14DF"`
+static RECIPE_INPUT: &str = "// input: ";
+
+/// What the blocks of recipes and `rust,show-output` blocks are followed by
+/// in the document, until the generated code writes their output there
+static OUTPUT_MARKER: &str = "<!-- output of ";
+
+/// The test that goes at the end of an example, for what the code block was
+/// marked with
+fn example_tests(attribute: Option<&str>) -> &'static str {
+    match attribute {
+        // Referring to `main` keeps it from being dead code
+        Some("no_run") => {
+            r"
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_main_compiles() {
+        let _ = main;
+    }
+}"
+        }
+        Some("should_panic") => {
+            r"
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic]
+    fn test_main() {
+        main();
+    }
+}"
+        }
+        _ => {
+            r"
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_main() {
+        main();
+    }
+}"
+        }
+    }
+}
+
+/// An example that checks that the code of a `rust,compile_fail` block
+/// doesn't compile. That can't be done with the code in the example itself,
+/// so it gets its own crate that depends on the same nom, which the test
+/// checks with cargo. Errors that rustc has a code for, like `E0308`, are the
+/// ones that the code was wrong, rather than e.g. the dependencies missing.
+fn compile_fail_example(code: &str, name: &str) -> String {
+    let mut hashes = "#".to_string();
+    while code.contains(&format!("\"{hashes}")) {
+        hashes.push('#');
+    }
+    format!(
+        r#"fn main() {{}}
+
+#[cfg(test)]
+mod tests {{
+    use std::{{fs, path::Path, process::Command}};
+
+    const CODE: &str = r{hashes}"{code}"{hashes};
+
+    #[test]
+    fn test_compile_fail() {{
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let dir = manifest_dir.join("target/compile-fail/{name}");
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"{name}\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n\
+             [dependencies]\nnom = \"7\"\nnom-supreme = \"0.8\"\n\n[workspace]\n",
+        )
+        .unwrap();
+        // The same versions as the sheet, without going online for them
+        fs::copy(manifest_dir.join("Cargo.lock"), dir.join("Cargo.lock")).unwrap();
+        fs::write(dir.join("src/main.rs"), CODE).unwrap();
+        let output = Command::new(env!("CARGO"))
+            .args(["check", "--offline", "--quiet"])
+            .current_dir(&dir)
+            .env("CARGO_TARGET_DIR", manifest_dir.join("target/compile-fail/target"))
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            !output.status.success() && stderr.contains("error[E"),
+            "The code of a `compile_fail` block should fail to compile:\n{{stderr}}"
+        );
+    }}
+}}
+"#
+    )
+}
+
+/// A `rust,recipe` block, split into its code and the inputs that its last
+/// function gets run on
+fn split_recipe(code: &str) -> (String, Vec<&str>) {
+    let mut inputs = Vec::new();
+    let mut kept = String::new();
+    for line in code.lines() {
+        match line.trim_start().strip_prefix(RECIPE_INPUT) {
+            Some(input) => inputs.push(input.trim()),
+            None => {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+    }
+    (kept, inputs)
+}
+
+/// The example for a recipe, which runs its parser on the inputs
+fn recipe_example(code: &str, parser: &syn::Ident, inputs: &[&str]) -> String {
+    format!(
+        "// Recipes' structs are usually only there to be printed\n\
+         #![allow(dead_code)]\n\n\
+         {code}\n\
+         fn main() {{\n    \
+             for input in [{}] {{\n        \
+                 println!(\"{{:?}}\", {parser}(input));\n    \
+             }}\n\
+         }}\n\
+         {}",
+        inputs.join(", "),
+        example_tests(None)
+    )
+}
+
+/// The module that a recipe goes in in the generated code, named `name`, with
+/// an `output` function that runs the recipe's last function on each of the
+/// inputs, for the list under the block
+fn recipe_module(name: &str, code: &str, inputs: &[&str]) -> Result<(Item, syn::Ident)> {
+    let file: syn::File = syn::parse_str(code)?;
+    let parser = file
+        .items
+        .iter()
+        .rev()
+        .find_map(|item| match item {
+            Item::Fn(function) => Some(function.sig.ident.clone()),
+            _ => None,
+        })
+        .ok_or_else(|| format!("Recipe `{name}` needs a function to run its inputs through"))?;
+    let shown = inputs.iter().map(|input| markdown_format_code(input));
+    let inputs = inputs
+        .iter()
+        .map(|input| syn::parse_str::<syn::Expr>(input))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let items = file.items;
+    let name = format_ident!("{name}");
+    let module = parse_quote! {
+        #[allow(dead_code)]
+        pub mod #name {
+            #(#items)*
+
+            /// Runs the recipe on its inputs, a list item each
+            pub fn output() -> String {
+                let mut lines = Vec::new();
+                #(
+                    let input = #inputs;
+                    let result = #parser(input);
+                    lines.push(format!(
+                        "- {}: {}",
+                        #shown,
+                        nom_cheatsheet_shared::format_iresult(
+                            &input,
+                            &result,
+                            nom_cheatsheet_shared::Numbers::Default,
+                            nom_cheatsheet_shared::Whitespace::Plain,
+                        )
+                    ));
+                )*
+                lines.join("\n")
+            }
+        }
+    };
+    Ok((module, parser))
+}
+
+/// The module that a `rust,show-output` block goes in in the generated code,
+/// named `name`, with an `output` function that runs its `main` and returns
+/// what that printed as a `text` block. `print!` and `println!` get shadowed
+/// by macros that write to a string instead of stdout for that.
+fn show_output_module(name: &str, code: &str) -> Result<Item> {
+    let items = syn::parse_str::<syn::File>(code)?.items;
+    let name = format_ident!("{name}");
+    Ok(parse_quote! {
+        #[allow(dead_code, unused_macros)]
+        pub mod #name {
+            std::thread_local! {
+                static STDOUT: std::cell::RefCell<String> = const { std::cell::RefCell::new(String::new()) };
+            }
+
+            macro_rules! print {
+                ($($arg:tt)*) => {
+                    STDOUT.with(|stdout| {
+                        use std::fmt::Write as _;
+                        write!(stdout.borrow_mut(), $($arg)*).unwrap();
+                    })
+                };
+            }
+
+            macro_rules! println {
+                ($($arg:tt)*) => {
+                    STDOUT.with(|stdout| {
+                        use std::fmt::Write as _;
+                        writeln!(stdout.borrow_mut(), $($arg)*).unwrap();
+                    })
+                };
+            }
+
+            #(#items)*
+
+            /// Runs the block and returns what it printed, in a block of its
+            /// own
+            pub fn output() -> String {
+                STDOUT.with(|stdout| stdout.borrow_mut().clear());
+                main();
+                let stdout = STDOUT.with(|stdout| stdout.take());
+                if stdout.is_empty() {
+                    return String::new();
+                }
+                let mut fence = "```".to_string();
+                while stdout.contains(&fence) {
+                    fence.push('`');
+                }
+                format!("{fence}text\n{}\n{fence}", stdout.trim_end_matches('\n'))
+            }
+        }
+    })
+}
+
+/// Splits the text of a document at the first output marker, into the text
+/// before it, the name of the block's module, and the text after it
+pub fn split_output_marker(text: &str) -> Option<(&str, &str, &str)> {
+    let (before, after) = text.split_once(OUTPUT_MARKER)?;
+    let (name, after) = after.split_once(" -->")?;
+    Some((before, name, after))
+}
+
+/// Writes the Rust code blocks to examples so they get tested, and returns the
+/// document with all the code blocks turned into plain `rust` ones, along with
+/// the code of any `rust,fixture` blocks and the modules of the blocks whose
+/// output the sheet shows. Fixtures aren't programs on their own, but define
+/// structs and helper parsers that the table rows can use. Blocks marked
+/// `no_run` only get compiled, `should_panic` ones have to panic, and
+/// `compile_fail` ones must not compile, which makes it possible to show
+/// mistakes.
+///
+/// Recipes are complete parsers, like one for CSV lines, with lines like
+/// `// input: "a,b"` for what to run their last function on. Those lines are
+/// left out of the block, and the sheet shows the outputs under it instead.
+/// Blocks marked `show-output` are run as they are, and the sheet shows what
+/// they print under them.
+///
+/// The example files are named `{prefix}{index}.rs`, so that several
+/// templates don't overwrite each other's.
+pub fn do_code_blocks<'a>(
+    input: &'a str,
+    prefix: &str,
+) -> Result<(String, Vec<&'a str>, Vec<Item>)> {
+    let (input, mut components) =
+        many1(alt((parse_code_block, parse_outside_code_blocks)))(input).unwrap();
+    assert_eq!(input, "");
+    let mut fixtures = Vec::new();
+    let mut output_modules = Vec::new();
+    // The blocks whose output the sheet shows, with the markers for where
+    // it goes after them, and recipes without their inputs
+    let mut output_blocks = HashMap::new();
+    for (index, component) in components.iter_mut().enumerate() {
+        let Component::CodeBlock(code_block) = component else {
+            continue;
+        };
+        if code_block.language == "ignore" {
+            code_block.language = "rust";
+            continue;
+        }
+        let (language, attribute) = match code_block.language.split_once(',') {
+            Some((language, attribute)) => (language, Some(attribute)),
+            None => (code_block.language, None),
+        };
+        if language != "rust" && language != "rs" {
+            continue;
+        }
+        if let Some(attribute) = attribute {
+            assert!(
+                CODE_BLOCK_ATTRIBUTES.contains(&attribute),
+                "Unknown code block attribute `{attribute}`, expected one of \
+                 {CODE_BLOCK_ATTRIBUTES:?}"
+            );
+            code_block.language = "rust";
+        }
+        if attribute == Some("fixture") {
+            fixtures.push(code_block.code);
+            continue;
+        }
+        let path = format!("examples/{prefix}{index}.rs");
+        let path = Path::new(&path);
+        if attribute == Some("recipe") {
+            let name = format!("{prefix}{index}");
+            let (code, inputs) = split_recipe(code_block.code);
+            assert!(
+                !inputs.is_empty(),
+                "Recipe `{name}` needs lines like `{RECIPE_INPUT}\"a,b\"` to run it on"
+            );
+            let (module, parser) = recipe_module(&name, &code, &inputs)?;
+            fs::write(path, recipe_example(&code, &parser, &inputs))?;
+            output_modules.push(module);
+            output_blocks.insert(
+                index,
+                format!("```rust\n{code}\n```\n\n{OUTPUT_MARKER}{name} -->"),
+            );
+            continue;
+        }
+        if attribute == Some("show-output") {
+            let name = format!("{prefix}{index}");
+            output_modules.push(show_output_module(&name, code_block.code)?);
+            output_blocks.insert(
+                index,
+                format!(
+                    "```rust\n{}\n```\n\n{OUTPUT_MARKER}{name} -->",
+                    code_block.code
+                ),
+            );
+        }
+        let code = if attribute == Some("compile_fail") {
+            compile_fail_example(code_block.code, &format!("{prefix}{index}"))
+        } else {
+            format!("{}{}", code_block.code, example_tests(attribute))
+        };
+        fs::write(path, code)?;
+    }
+    let output = components
+        .into_iter()
+        .enumerate()
+        .map(|(index, component)| match component {
+            Component::Text(text) => text.to_string(),
+            Component::CodeBlock(_) if output_blocks.contains_key(&index) => {
+                output_blocks.remove(&index).unwrap()
+            }
+            Component::CodeBlock(CodeBlock { language, code }) => {
+                format!("```{language}\n{code}\n```")
+            }
+        })
+        .collect();
+    Ok((output, fixtures, output_modules))
+}
+
+/// Puts all the fixtures in a module of their own, so their imports don't
+/// clash with the ones for the table rows. Everything in there is made `pub`
+/// so the rows can get at it through a glob import, and so are the fields of
+/// structs, for rows that build them.
+pub fn fixtures_module(name: &syn::Ident, fixtures: &[&str]) -> Result<Item> {
+    let mut items = Vec::new();
+    for fixture in fixtures {
+        let file: syn::File = syn::parse_str(fixture)?;
+        for mut item in file.items {
+            let pub_visibility: syn::Visibility = parse_quote! { pub };
+            match &mut item {
+                Item::Const(item) => item.vis = pub_visibility,
+                Item::Enum(item) => item.vis = pub_visibility,
+                Item::Fn(item) => item.vis = pub_visibility,
+                Item::Static(item) => item.vis = pub_visibility,
+                Item::Struct(item) => {
+                    item.vis = pub_visibility.clone();
+                    for field in &mut item.fields {
+                        field.vis = pub_visibility.clone();
+                    }
+                }
+                Item::Type(item) => item.vis = pub_visibility,
+                _ => {}
+            }
+            items.push(item);
+        }
+    }
+    // Fixture structs are usually only there to be printed, and dead code
+    // analysis doesn't count reads by derived `Debug` impls.
+    Ok(parse_quote! {
+        #[allow(dead_code)]
+        mod #name {
+            #(#items)*
+        }
+    })
+}
+
+/// The module that the fixtures of a template go in. The first template's
+/// are glob imported at the top of the generated code, the others' only by
+/// their own rows.
+pub fn fixtures_ident(template: usize) -> syn::Ident {
+    match template {
+        0 => format_ident!("fixtures"),
+        _ => format_ident!("fixtures_{template}"),
+    }
+}
+
+/// The names of the items in a fixtures module
+pub fn fixture_names(module: &Item) -> Vec<String> {
+    let Item::Mod(syn::ItemMod {
+        content: Some((_, items)),
+        ..
+    }) = module
+    else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Const(item) => Some(item.ident.to_string()),
+            Item::Enum(item) => Some(item.ident.to_string()),
+            Item::Fn(item) => Some(item.sig.ident.to_string()),
+            Item::Static(item) => Some(item.ident.to_string()),
+            Item::Struct(item) => Some(item.ident.to_string()),
+            Item::Type(item) => Some(item.ident.to_string()),
+            _ => None,
+        })
+        .collect()
+}
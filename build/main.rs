@@ -0,0 +1,348 @@
+//! Turns the templates into the code that generates the sheet. That happens
+//! in passes: each template gets parsed into its prose and its tables by
+//! `nom-cheatsheet-core`, the rows get looked up and their prose linked
+//! across all templates, and then the code gets generated from that.
+
+mod codegen;
+mod examples;
+mod transform;
+
+use examples::{do_code_blocks, fixtures_ident, fixtures_module};
+use nom_cheatsheet_core::Sheet;
+pub use nom_cheatsheet_core::{Error, Result};
+use nom_cheatsheet_shared::{expand_includes, split_front_matter, FrontMatter};
+use sha2::{Digest, Sha256};
+use std::{
+    env,
+    fs::{self, read_to_string},
+    path::{Path, PathBuf},
+    process::Command,
+};
+use transform::{transform, Settings, Source, KNOWN_ATTRIBUTES};
+
+/// The color schemes that the front matter can pick for the HTML
+static THEMES: &[&str] = &["auto", "light", "dark"];
+
+/// Lists the dependencies of `package` in `Cargo.lock`, with the versions
+/// they were locked to. Cargo only puts versions in the dependency lists if
+/// several of them are locked, so otherwise this looks up the only package
+/// with that name.
+fn locked_dependencies<'a>(lock: &'a str, package: &str) -> Vec<(&'a str, &'a str)> {
+    let field = |block: &'a str, key: &str| {
+        block.lines().find_map(|line| {
+            line.strip_prefix(key)?
+                .strip_prefix(" = \"")?
+                .strip_suffix('"')
+        })
+    };
+    let blocks = lock.split("[[package]]").skip(1).collect::<Vec<_>>();
+    let Some(package_block) = blocks
+        .iter()
+        .find(|block| field(block, "name") == Some(package))
+    else {
+        return Vec::new();
+    };
+    package_block
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix('"')?.split('"').next())
+        .filter_map(|dependency| {
+            let mut words = dependency.split_whitespace();
+            let name = words.next()?;
+            let version = words.next().or_else(|| {
+                blocks
+                    .iter()
+                    .find(|block| field(block, "name") == Some(name))
+                    .and_then(|block| field(block, "version"))
+            })?;
+            Some((name, version))
+        })
+        .collect()
+}
+
+fn main() -> Result<()> {
+    println!("cargo:rerun-if-changed=build");
+    // The version of nom that the examples get built with, which is also
+    // what the output gets filed under with `--versioned`
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    let lock = read_to_string("Cargo.lock")?;
+    let dependencies = locked_dependencies(&lock, "nom-cheatsheet");
+    // The build script has its own nom 7 for reading the templates, so with
+    // nom 8, which needs the `nom8` feature, there are two of them locked
+    let nom_major = match env::var_os("CARGO_FEATURE_NOM8") {
+        Some(_) => "8",
+        None => "7",
+    };
+    let (_, nom_version) = dependencies
+        .iter()
+        .find(|(name, version)| *name == "nom" && version.split('.').next() == Some(nom_major))
+        .ok_or_else(|| format!("Can't find nom {nom_major} in Cargo.lock"))?;
+    println!("cargo:rustc-env=NOM_VERSION={nom_version}");
+    if transform::NOM_ITEMS.lines().next() != Some(&format!("# nom {nom_version}")) {
+        println!(
+            "cargo:warning=build/nom-items.txt isn't for nom {nom_version}, run \
+             `cargo run -- update-items` to link to the right docs pages"
+        );
+    }
+    if transform::NOM_SIGNATURES.lines().next() != Some(&format!("# nom {nom_version}")) {
+        println!(
+            "cargo:warning=build/nom-signatures.txt isn't for nom {nom_version}, run \
+             `cargo run -- update-items` to show the right signatures"
+        );
+    }
+    if transform::NOM_SUMMARIES.lines().next() != Some(&format!("# nom {nom_version}")) {
+        println!(
+            "cargo:warning=build/nom-summaries.txt isn't for nom {nom_version}, run \
+             `cargo run -- update-items` to show the right summaries of the docs"
+        );
+    }
+    if transform::NOM_FEATURES.lines().next() != Some(&format!("# nom {nom_version}")) {
+        println!(
+            "cargo:warning=build/nom-features.txt isn't for nom {nom_version}, run \
+             `cargo run -- update-items` to show the right features"
+        );
+    }
+    // These go into the build manifest, so that it's clear what made a sheet
+    let dependencies = dependencies
+        .iter()
+        .map(|(name, version)| format!("{name} {version}"))
+        .collect::<Vec<_>>();
+    println!(
+        "cargo:rustc-env=NOM_CHEATSHEET_DEPENDENCIES={}",
+        dependencies.join(",")
+    );
+    // Cached example outputs are only used with the compiler that made them
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc).arg("-V").output()?.stdout;
+    println!(
+        "cargo:rustc-env=NOM_CHEATSHEET_RUSTC_VERSION={}",
+        String::from_utf8_lossy(&rustc_version).trim()
+    );
+    // Long descriptions make for very long lines in the raw markdown, which
+    // some review tooling doesn't like. If this is set, descriptions are
+    // wrapped to this many characters, with the overflow going into
+    // continuation rows that only have a description cell.
+    println!("cargo:rerun-if-env-changed=NOM_CHEATSHEET_WRAP_WIDTH");
+    let wrap_width = match env::var("NOM_CHEATSHEET_WRAP_WIDTH") {
+        Ok(width) => Some(width.parse::<usize>()?),
+        Err(_) => None,
+    };
+    // Numbers in the output cells can be in one base throughout, instead of
+    // values in decimal and bytes in remainders in hex. Rows can still pick
+    // their own with the `numbers` attribute.
+    println!("cargo:rerun-if-env-changed=NOM_CHEATSHEET_NUMBERS");
+    let numbers = env::var("NOM_CHEATSHEET_NUMBERS").ok();
+
+    // Inputs and remainders full of `\n`, `\t`, and spaces can show those as
+    // symbols, for the whole sheet or with the `whitespace` attribute per row
+    println!("cargo:rerun-if-env-changed=NOM_CHEATSHEET_WHITESPACE");
+    let whitespace = env::var("NOM_CHEATSHEET_WHITESPACE").ok();
+
+    // Rows of combinators that come in `complete` and `streaming` versions
+    // can show both outputs, for the whole sheet or with the `streaming`
+    // attribute per row
+    println!("cargo:rerun-if-env-changed=NOM_CHEATSHEET_STREAMING");
+    let streaming = env::var("NOM_CHEATSHEET_STREAMING").ok();
+    // Several templates can be merged into one sheet, e.g. the core one and
+    // one with an organization's own helper parsers. Each is parsed on its
+    // own, and everything but the first goes in before the first one's
+    // closing `# ` heading.
+    println!("cargo:rerun-if-env-changed=NOM_CHEATSHEET_TEMPLATES");
+    let template_paths = match env::var_os("NOM_CHEATSHEET_TEMPLATES") {
+        Some(paths) => env::split_paths(&paths).collect::<Vec<_>>(),
+        None => vec![PathBuf::from("src/nom-cheatsheet-template.md")],
+    };
+    assert!(
+        !template_paths.is_empty(),
+        "NOM_CHEATSHEET_TEMPLATES is empty"
+    );
+    // Templates can also be split into files that they include, which all
+    // need watching
+    let mut hasher = Sha256::new();
+    let mut templates = Vec::new();
+    let mut template_files = Vec::new();
+    for path in &template_paths {
+        let expanded =
+            expand_includes(path).map_err(|e| format!("In template {}: {e}", path.display()))?;
+        for file in &expanded.files {
+            println!("cargo:rerun-if-changed={}", file.display());
+        }
+        hasher.update(&expanded.text);
+        template_files.extend(expanded.files.iter().cloned());
+        templates.push(expanded);
+    }
+    // The sheet as a whole can have settings in the front matter of the
+    // first template, which the other templates go into
+    let mut front_matter = FrontMatter::default();
+    let mut bodies = Vec::new();
+    for (number, (template, path)) in templates.iter().zip(&template_paths).enumerate() {
+        let (template_front_matter, body) = split_front_matter(&template.text)
+            .map_err(|e| format!("In {}: {e}", path.display()))?;
+        if number == 0 {
+            front_matter = template_front_matter;
+        } else if template_front_matter != FrontMatter::default() {
+            return Err(format!(
+                "In {}: only the first template can have front matter",
+                path.display()
+            )
+            .into());
+        }
+        bodies.push(body);
+    }
+    println!(
+        "cargo:rustc-env=NOM_CHEATSHEET_TITLE={}",
+        front_matter.title.unwrap_or("Nom Cheatsheet")
+    );
+    let theme = front_matter.theme.unwrap_or("auto");
+    assert!(
+        THEMES.contains(&theme),
+        "Unknown theme `{theme}`, expected one of {THEMES:?}"
+    );
+    println!("cargo:rustc-env=NOM_CHEATSHEET_THEME={theme}");
+    // The prose says which version of nom it's about, which the footer
+    // shows, and which should be the one that the examples get built with
+    if let Some(written_for) = front_matter.nom {
+        if *nom_version != written_for && !nom_version.starts_with(&format!("{written_for}.")) {
+            println!(
+                "cargo:warning=The template is written for nom {written_for}, but the \
+                 examples get built with nom {nom_version}"
+            );
+        }
+    }
+    println!(
+        "cargo:rustc-env=NOM_CHEATSHEET_WRITTEN_FOR={}",
+        front_matter.nom.unwrap_or_default()
+    );
+    // The columns to show and their order, e.g. without the usage for a
+    // quick reference. All output formats are made from the same markdown,
+    // so they all get the same columns. The `winnow` and `signature` columns
+    // aren't in the templates, so they're only there when they're asked
+    // for. Without this, each table has the columns of the template, in the
+    // same order. Columns that only some tables have, like `notes`, can be
+    // picked as well. The environment variable goes over the front matter,
+    // for trying out other columns without editing the template.
+    println!("cargo:rerun-if-env-changed=NOM_CHEATSHEET_COLUMNS");
+    let column_names = env::var("NOM_CHEATSHEET_COLUMNS").ok();
+    let columns = column_names
+        .as_deref()
+        .or(front_matter.columns)
+        .map(|names| names.split(',').map(str::trim).collect::<Vec<_>>());
+
+    // The same template can make the sheets for several versions of nom,
+    // with rows for combinators that are only in some of them limited to
+    // those with the `nom` attribute
+    let nom_major = nom_version.split('.').next().unwrap().to_string();
+    // Docs links go to the docs of the version of nom that the examples get
+    // built with, so they don't drift from what the examples do, unless
+    // another version or a mirror is asked for
+    println!("cargo:rerun-if-env-changed=NOM_CHEATSHEET_DOCS_VERSION");
+    let docs_version =
+        env::var("NOM_CHEATSHEET_DOCS_VERSION").unwrap_or_else(|_| nom_version.to_string());
+    let mut nom_docs = front_matter.docs.map_or_else(
+        || format!("https://docs.rs/nom/{docs_version}/nom/"),
+        str::to_string,
+    );
+    if !nom_docs.ends_with('/') {
+        nom_docs.push('/');
+    }
+    // For `--docs-base-url` to move the links elsewhere
+    println!("cargo:rustc-env=NOM_CHEATSHEET_DOCS_BASE={nom_docs}");
+    // And the rows link to where their line of the template can be edited
+    let mut edit = front_matter
+        .edit
+        .unwrap_or("https://github.com/docwilco/nom-cheatsheet/blob/main/")
+        .to_string();
+    if !edit.ends_with('/') {
+        edit.push('/');
+    }
+    let settings = Settings {
+        wrap_width,
+        numbers,
+        whitespace,
+        streaming,
+        nom_major,
+        nom_docs,
+        edit,
+    };
+    println!(
+        "cargo:rustc-env=NOM_CHEATSHEET_TEMPLATE_SHA256={:x}",
+        hasher.finalize()
+    );
+    // For watching the templates while serving the sheet
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    println!(
+        "cargo:rustc-env=NOM_CHEATSHEET_TEMPLATE_PATHS={}",
+        env::join_paths(template_files.iter().map(|path| manifest_dir.join(path)))
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+
+    let mut documents = Vec::new();
+    let mut fixtures_modules = Vec::new();
+    let mut output_modules = Vec::new();
+    for (number, body) in bodies.iter().enumerate() {
+        let prefix = match number {
+            0 => "example".to_string(),
+            _ => format!("template{number}_example"),
+        };
+        let (document, fixtures, blocks) = do_code_blocks(body, &prefix)?;
+        fixtures_modules.push(fixtures_module(&fixtures_ident(number), &fixtures)?);
+        output_modules.extend(blocks);
+        documents.push(document);
+    }
+    let mut sheets = Vec::new();
+    for (document, path) in documents.iter().zip(&template_paths) {
+        let in_path = |e| format!("In {}: {e}", path.display());
+        let sheet = Sheet::parse(document).map_err(in_path)?;
+        sheet.check_attributes(KNOWN_ATTRIBUTES).map_err(in_path)?;
+        sheets.push(sheet);
+    }
+
+    if let Some(columns) = &columns {
+        for name in columns {
+            let in_tables = sheets
+                .iter()
+                .flat_map(|sheet| &sheet.sections)
+                .any(|section| section.columns[1..].contains(name));
+            assert!(
+                codegen::COLUMN_NAMES.contains(name) || in_tables,
+                "Unknown column `{name}`, expected some of {:?} or the columns of the tables",
+                codegen::COLUMN_NAMES
+            );
+        }
+    }
+
+    let sources = templates
+        .iter()
+        .zip(&bodies)
+        .zip(&documents)
+        .map(|((expanded, body), document)| Source {
+            expanded,
+            body,
+            document,
+        })
+        .collect::<Vec<_>>();
+    let transformed = transform(&sheets, &sources, &template_paths, &settings)?;
+    let code = codegen::generate(
+        &transformed,
+        columns.as_deref(),
+        &fixtures_modules,
+        &output_modules,
+        &template_paths,
+    )?;
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let formatted = prettyplease::unparse(&code.generated);
+    fs::write(out_dir.join("generated.rs"), formatted)?;
+    for (number, section) in code.sections.iter().enumerate() {
+        let formatted = prettyplease::unparse(section);
+        fs::write(
+            out_dir.join(format!("generated_section_{number}.rs")),
+            formatted,
+        )?;
+    }
+    let formatted = prettyplease::unparse(&code.wasm);
+    fs::write(out_dir.join("wasm_rows.rs"), formatted)?;
+
+    Ok(())
+}
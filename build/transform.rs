@@ -0,0 +1,1473 @@
+//! Looks up everything about the rows of the parsed templates that the
+//! generated code needs: the docs URLs of their combinators, what their
+//! attributes mean, where mentions of combinators in the prose link to, and
+//! which imports the rows need, on their own and all together.
+
+use crate::{examples::fixtures_ident, Result};
+use nom_cheatsheet_core::{self as parse, escape_cell, headings, AttributeValue};
+use nom_cheatsheet_shared::{markdown_cell_code, Expanded};
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::{Component, PathBuf},
+};
+use syn::{parse_quote, Item};
+
+/// Crates besides nom that rows can use, by their path and their name on
+/// docs.rs. Their combinators are written with the crate path in front, like
+/// `nom_supreme::tag::complete::tag`.
+static OTHER_CRATES: &[(&str, &str)] =
+    &[("nom_supreme", "nom-supreme"), ("nom_locate", "nom_locate")];
+
+/// The kinds of nom's items, like `fn bytes::complete::tag`, which is what
+/// their docs pages are named after. `cargo run -- update-items` writes
+/// these from nom's rustdoc JSON, starting with the version of nom they're
+/// from.
+pub static NOM_ITEMS: &str = include_str!("nom-items.txt");
+
+/// The signatures of nom's functions, like `bytes::complete::tag fn tag(tag)`,
+/// which `cargo run -- update-items` writes along with `NOM_ITEMS`
+pub static NOM_SIGNATURES: &str = include_str!("nom-signatures.txt");
+
+/// The first paragraphs of the docs of nom's items, like
+/// `bytes::complete::tag Recognizes a pattern`, for tooltips in the HTML,
+/// which `cargo run -- update-items` writes along with `NOM_ITEMS`
+pub static NOM_SUMMARIES: &str = include_str!("nom-summaries.txt");
+
+/// The features of nom that its items need, like `multi::many0 alloc`, for
+/// the badges next to them, which `cargo run -- update-items` writes along
+/// with `NOM_ITEMS`
+pub static NOM_FEATURES: &str = include_str!("nom-features.txt");
+
+/// Crates that rows only link to, by their path and their name on docs.rs,
+/// like winnow for the `winnow` attribute
+static LINKED_CRATES: &[(&str, &str)] = &[("winnow", "winnow")];
+
+/// Items of `OTHER_CRATES` whose docs page isn't a `fn.` or an `enum.` one,
+/// going by case
+static ITEM_KINDS: &[(&str, &str)] = &[
+    ("nom_locate::LocatedSpan", "struct"),
+    ("nom_supreme::error::ErrorTree", "type"),
+    ("nom_supreme::parser_ext::ParserExt", "trait"),
+];
+
+#[derive(Clone, Debug)]
+pub struct Url {
+    pub module: String,
+    pub name: String,
+    pub docsurl: String,
+    /// The first paragraph of the docs, for nom's items that have any
+    pub summary: Option<&'static str>,
+    /// The features of nom that it needs, like `alloc`
+    pub features: Vec<&'static str>,
+}
+
+impl Url {
+    /// Whether this is a method of a trait, like `Parser::map`, rather than
+    /// a function in a module
+    pub fn is_method(&self) -> bool {
+        self.module
+            .rsplit("::")
+            .next()
+            .is_some_and(|part| part.starts_with(char::is_uppercase))
+    }
+
+    /// Finds the docs of the combinator at `path`, like
+    /// `bytes::complete::tag`, which is in nom unless it starts with one of
+    /// `OTHER_CRATES` or `LINKED_CRATES`. Nom's docs are at `nom_docs`. The
+    /// kinds of nom's items are in `NOM_ITEMS`, for the others it's the case
+    /// of their name unless they're in `ITEM_KINDS`, and their summaries and
+    /// features are in `NOM_SUMMARIES` and `NOM_FEATURES`.
+    fn resolve(path: &str, nom_docs: &str) -> Url {
+        let mut parts = path.split("::").collect::<Vec<_>>();
+        let name = parts.pop().unwrap().to_string();
+        let path = parts.join("::");
+        let (mut url, nom_kind, summary, features): (String, _, _, _) = match OTHER_CRATES
+            .iter()
+            .chain(LINKED_CRATES)
+            .find(|(crate_path, _)| parts.first() == Some(crate_path))
+        {
+            Some((crate_path, crate_name)) => {
+                parts.remove(0);
+                (
+                    format!("https://docs.rs/{crate_name}/latest/{crate_path}/"),
+                    None,
+                    None,
+                    Vec::new(),
+                )
+            }
+            None => {
+                let item_path = [&parts[..], &[name.as_str()]].concat().join("::");
+                let kind = NOM_ITEMS
+                    .lines()
+                    .filter_map(|line| line.split_once(' '))
+                    .find(|(_, item)| *item == item_path)
+                    .map(|(kind, _)| kind);
+                let summary = NOM_SUMMARIES
+                    .lines()
+                    .filter_map(|line| line.split_once(' '))
+                    .find(|(item, _)| *item == item_path)
+                    .map(|(_, summary)| summary);
+                let features = NOM_FEATURES
+                    .lines()
+                    .filter_map(|line| line.split_once(' '))
+                    .find(|(item, _)| *item == item_path)
+                    .map(|(_, features)| features.split(' ').collect())
+                    .unwrap_or_default();
+                (nom_docs.to_string(), kind, summary, features)
+            }
+        };
+        // Methods of a trait, like `Parser::map`, are on the trait's page
+        let method_of = parts
+            .pop_if(|part| part.starts_with(char::is_uppercase))
+            .map(str::to_string);
+        for part in parts {
+            url.push_str(part);
+            url.push('/');
+        }
+        let full_path = format!("{path}::{name}");
+        if let Some(trait_name) = method_of {
+            url.push_str(&format!("trait.{trait_name}.html#method.{name}"));
+            return Url {
+                module: path,
+                name,
+                docsurl: url,
+                summary,
+                features,
+            };
+        }
+        if let Some(kind) = nom_kind.or_else(|| {
+            ITEM_KINDS
+                .iter()
+                .find(|(item, _)| *item == full_path)
+                .map(|(_, kind)| *kind)
+        }) {
+            url.push_str(kind);
+            url.push('.');
+        } else if name.chars().next().unwrap().is_lowercase() {
+            url.push_str("fn.");
+        } else {
+            url.push_str("enum.");
+        }
+        url.push_str(&name);
+        url.push_str(".html");
+        Url {
+            module: path,
+            name,
+            docsurl: url,
+            summary,
+            features,
+        }
+    }
+}
+
+/// The attributes that rows can have at the end of their description
+pub static KNOWN_ATTRIBUTES: &[&str] = &[
+    "deprecated",
+    "level",
+    "nom",
+    "numbers",
+    "repeats",
+    "requires",
+    "since",
+    "streaming",
+    "tags",
+    "trace",
+    "types",
+    "whitespace",
+    "winnow",
+];
+
+/// The levels that rows can be for with `level = ...`, in order
+static LEVELS: &[&str] = &["beginner", "intermediate", "advanced"];
+
+/// The crate features that rows can require with `requires = [...]`
+static KNOWN_FEATURES: &[&str] = &["alloc", "std", "nightly"];
+
+/// The bases that numbers in output cells can be written in
+static NUMBERS: &[&str] = &["decimal", "hex", "binary"];
+
+/// How whitespace in the input and remainder cells can be shown
+static WHITESPACE: &[&str] = &["plain", "visible"];
+
+/// Whether rows only show the `complete` versions of their combinators, or
+/// the `streaming` ones next to them too
+static STREAMING: &[&str] = &["complete", "both"];
+
+/// Whether a row shows its whitespace with symbols, checking that it's one
+/// of `WHITESPACE`
+fn visible_whitespace_for(whitespace: Option<&str>) -> Result<bool> {
+    match whitespace {
+        Some(whitespace) if !WHITESPACE.contains(&whitespace) => {
+            Err(format!("Unknown whitespace `{whitespace}`, expected one of {WHITESPACE:?}").into())
+        }
+        Some(whitespace) => Ok(whitespace == "visible"),
+        None => Ok(false),
+    }
+}
+
+/// Splits the text after the last table of the first template at its last
+/// top level heading, like `# Fin`, so other templates can go in before it
+fn split_closing(text: &str) -> (&str, &str) {
+    let start = if text.starts_with("# ") {
+        Some(0)
+    } else {
+        text.rfind("\n# ").map(|index| index + 1)
+    };
+    text.split_at(start.unwrap_or(text.len()))
+}
+
+/// Where prose that mentions a combinator links to. That's the row showing
+/// it for the least advanced readers, the first one if there are several.
+/// Unless that row is left out of some of the sheets the prose is in, then
+/// it's the docs.
+struct MentionTarget {
+    /// Counting all rows of all templates
+    row: usize,
+    path: String,
+    anchor: String,
+    docsurl: String,
+    /// The index in `LEVELS` of the row
+    level: usize,
+}
+
+/// Combinators that are named after a primitive type, which prose mostly
+/// means the type by, like in "casting `char` to `u8`"
+pub static PRIMITIVE_TYPES: &[&str] = &[
+    "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "u8", "u16", "u32", "u64",
+    "u128",
+];
+
+/// The id of the anchor on the row that mentions of a combinator link to,
+/// from its full path, like `nom-branch-alt`
+fn row_anchor(url: &Url) -> String {
+    let path = format!("{}::{}", url.module, url.name);
+    if url.module.starts_with("nom_") {
+        path.replace("::", "-")
+    } else {
+        format!("nom::{path}").replace("::", "-")
+    }
+}
+
+/// The small badges after a combinator in the first cell for the features of
+/// nom that it needs, like `<sup class="feature">alloc</sup>`, as it's not
+/// there without them
+fn feature_badges(features: &[&str]) -> String {
+    features
+        .iter()
+        .map(|feature| {
+            format!(
+                r#" <sup class="feature" title="Needs nom's {feature} feature">{feature}</sup>"#
+            )
+        })
+        .collect()
+}
+
+/// The badges at the end of a row's first cell for the version of nom that
+/// its combinators came with and whether they're deprecated, with what to
+/// use instead as the tooltip
+fn version_badges(row: &Row) -> String {
+    let mut badges = String::new();
+    if let Some(since) = row.since {
+        badges.push_str(&format!(
+            r#" <sup class="since" title="Added in nom {since}">since {since}</sup>"#
+        ));
+    }
+    if let Some(note) = row.deprecated {
+        let title = note.unwrap_or("Deprecated in nom").replace('"', "&quot;");
+        badges.push_str(&format!(
+            r#" <sup class="deprecated" title="{title}">deprecated</sup>"#
+        ));
+    }
+    badges
+}
+
+/// The id of the anchor that a row's permalink goes to. That's the anchor
+/// that mentions link to if the row has one, and otherwise the one of its
+/// first combinator with a number that no other row has.
+fn permalink(urls: &[Url], anchors: &[bool], used: &mut HashSet<String>) -> String {
+    if let Some((url, _)) = urls.iter().zip(anchors).find(|(_, anchored)| **anchored) {
+        return row_anchor(url);
+    }
+    // Rows that translate a regex have no combinator
+    let base = urls.first().map_or("regex".to_string(), row_anchor);
+    let anchor = (2..)
+        .map(|number| format!("{base}-{number}"))
+        .find(|anchor| !used.contains(anchor))
+        .unwrap();
+    used.insert(anchor.clone());
+    anchor
+}
+
+/// Turns the code spans in `text` that are just the name or the path of a
+/// combinator with a row, like `` `take_until` ``, into links. Mentions of
+/// the combinators in `own` link to the docs instead of to their own row.
+/// Code blocks, headings, and code spans that are already in a link are left
+/// alone.
+fn link_mentions(
+    text: &str,
+    targets: &HashMap<String, MentionTarget>,
+    own: &[Url],
+    level: usize,
+) -> String {
+    let mut linked = String::new();
+    let mut in_code_block = false;
+    for line in text.split_inclusive('\n') {
+        let fence = line.trim_start().starts_with("```");
+        if fence {
+            in_code_block = !in_code_block;
+        }
+        if fence || in_code_block || line.starts_with('#') {
+            linked.push_str(line);
+            continue;
+        }
+        let mut rest = line;
+        let mut brackets = 0;
+        while let Some(index) = rest.find(['`', '[', ']']) {
+            let (before, after) = rest.split_at(index);
+            linked.push_str(before);
+            let ticks = after.len() - after.trim_start_matches('`').len();
+            if ticks == 0 {
+                if after.starts_with('[') {
+                    brackets += 1;
+                } else if brackets > 0 {
+                    brackets -= 1;
+                }
+                linked.push_str(&after[..1]);
+                rest = &after[1..];
+                continue;
+            }
+            let Some(end) = after[ticks..].find(&after[..ticks]) else {
+                linked.push_str(after);
+                rest = "";
+                break;
+            };
+            let code = &after[ticks..ticks + end];
+            let span = &after[..2 * ticks + end];
+            rest = &after[2 * ticks + end..];
+            let href = targets
+                .get(code)
+                .filter(|_| brackets == 0 && !PRIMITIVE_TYPES.contains(&code))
+                .map(|target| {
+                    let own = own.iter().find(|url| {
+                        let path = format!("{}::{}", url.module, url.name);
+                        (url.name == code && !url.is_method()) || path == target.path
+                    });
+                    match own {
+                        Some(url) => url.docsurl.clone(),
+                        None if target.level <= level => format!("#{}", target.anchor),
+                        None => target.docsurl.clone(),
+                    }
+                });
+            match href {
+                Some(href) => linked.push_str(&format!("[{span}]({href})")),
+                None => linked.push_str(span),
+            }
+        }
+        linked.push_str(rest);
+    }
+    linked
+}
+
+/// The names of the combinators with a row that `usage` uses, leaving out
+/// types like `ErrorTree`
+fn used_combinators<'a>(
+    usage: &'a str,
+    targets: &HashMap<String, MentionTarget>,
+) -> BTreeSet<&'a str> {
+    usage
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| word.starts_with(char::is_lowercase))
+        .filter(|word| targets.contains_key(*word) && !PRIMITIVE_TYPES.contains(word))
+        .collect()
+}
+
+/// The combinators that show up in the same usage cells as any of `names` at
+/// least twice, most often first, for the "commonly combined with" line
+fn combined_with<'a>(
+    names: &[&str],
+    together: &'a HashMap<&str, HashMap<&str, usize>>,
+) -> Vec<&'a str> {
+    let mut counts = HashMap::<&str, usize>::new();
+    for name in names {
+        for (other, count) in together.get(name).into_iter().flatten() {
+            if !names.contains(other) {
+                *counts.entry(other).or_default() += count;
+            }
+        }
+    }
+    let mut combined = counts
+        .into_iter()
+        .filter(|(_, count)| *count >= 2)
+        .collect::<Vec<_>>();
+    combined.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    combined.into_iter().take(3).map(|(name, _)| name).collect()
+}
+
+/// Where byte inputs link to
+pub static BYTE_REFERENCE_ANCHOR: &str = "#ascii-and-byte-reference";
+
+/// The names of the ASCII control characters, by their value
+static CONTROL_NAMES: &[&str] = &[
+    "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "HT", "LF", "VT", "FF", "CR",
+    "SO", "SI", "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC",
+    "FS", "GS", "RS", "US",
+];
+
+/// How a byte shows up in the byte reference, as a character if it is a
+/// printable one
+fn byte_name(byte: u8) -> String {
+    match byte {
+        0x00..=0x1f => CONTROL_NAMES[usize::from(byte)].to_string(),
+        0x20 => "SP".to_string(),
+        0x7f => "DEL".to_string(),
+        0x21..=0x7e => markdown_cell_code(&char::from(byte).to_string()),
+        _ => format!("{byte:#04x}"),
+    }
+}
+
+/// The runs of bytes that `class` is true for, with their characters
+fn byte_ranges(class: fn(u8) -> bool) -> String {
+    let mut ranges: Vec<(u8, u8)> = Vec::new();
+    for byte in (0..=u8::MAX).filter(|byte| class(*byte)) {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == byte => *end = byte,
+            _ => ranges.push((byte, byte)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| match start == end {
+            true => format!("{start:#04x} ({})", byte_name(start)),
+            false => format!(
+                "{start:#04x}-{end:#04x} ({}-{})",
+                byte_name(start),
+                byte_name(end)
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+/// A section that lists the tagged rows by their tags, by the first
+/// combinator of each, for readers who know what they want to do but not
+/// what it's called. Sheets without tags don't get one. The combinators are
+/// mentions, so they link to their rows.
+fn tag_index<'r, 'a: 'r>(rows: impl Iterator<Item = &'r Row<'a>>) -> String {
+    let mut tags = BTreeMap::<&str, Vec<String>>::new();
+    for row in rows {
+        let Some(url) = row.combinators.first() else {
+            continue;
+        };
+        let path = format!("`{}::{}`", url.module, url.name);
+        for tag in &row.tags {
+            let combinators = tags.entry(tag).or_default();
+            if !combinators.contains(&path) {
+                combinators.push(path.clone());
+            }
+        }
+    }
+    if tags.is_empty() {
+        return String::new();
+    }
+    let mut text = String::from(
+        "## I want to…\n\n\
+         The combinators by what they're for, going by the tags of their rows.\n\n",
+    );
+    for (tag, combinators) in tags {
+        text.push_str(&format!("- **#{tag}**: {}\n", combinators.join(", ")));
+    }
+    text.push('\n');
+    text
+}
+
+/// One of nom's `is_*` functions, and the parsers that use it
+type ByteClass = (&'static str, &'static str, fn(u8) -> bool);
+
+/// An appendix for the byte parsing sections, with the bytes that nom's
+/// character classes accept, and an ASCII table. The classes come from nom's
+/// own `is_*` functions, so they can't get out of date. The rows are marked
+/// for beginners, so that the HTML never hides them.
+fn byte_reference() -> String {
+    let classes: &[ByteClass] = &[
+        (
+            "is_alphabetic",
+            "`alpha0`, `alpha1`",
+            nom::character::is_alphabetic,
+        ),
+        ("is_digit", "`digit0`, `digit1`", nom::character::is_digit),
+        (
+            "is_hex_digit",
+            "`hex_digit0`, `hex_digit1`",
+            nom::character::is_hex_digit,
+        ),
+        (
+            "is_oct_digit",
+            "`oct_digit0`, `oct_digit1`",
+            nom::character::is_oct_digit,
+        ),
+        (
+            "is_alphanumeric",
+            "`alphanumeric0`, `alphanumeric1`",
+            nom::character::is_alphanumeric,
+        ),
+        ("is_space", "`space0`, `space1`", nom::character::is_space),
+        ("is_newline", "`newline`", nom::character::is_newline),
+    ];
+    let marker = "<!-- level = beginner -->";
+    let mut text = String::from(
+        "## ASCII and byte reference\n\n\
+         The bytes that nom's character classes accept, going by the `is_*` \
+         functions in `nom::character`, and the parsers that use them. Byte \
+         inputs in the tables above link here.\n\n\
+         | function | parsers | bytes |\n|---|---|---|\n",
+    );
+    for (function, parsers, class) in classes {
+        let bytes = byte_ranges(*class);
+        text.push_str(&format!(
+            "| `{function}` | {parsers} | {bytes} {marker} |\n"
+        ));
+    }
+    text.push_str("\n| | 0x0_ | 0x1_ | 0x2_ | 0x3_ | 0x4_ | 0x5_ | 0x6_ | 0x7_ |\n");
+    text.push_str("|---|---|---|---|---|---|---|---|---|\n");
+    for low in 0..16 {
+        let cells = (0..8)
+            .map(|high| byte_name(high * 16 + low))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        text.push_str(&format!("| 0x_{low:x} | {cells} {marker} |\n"));
+    }
+    text.push('\n');
+    text
+}
+
+/// Splits a description into chunks of at most `width` characters, breaking
+/// only on spaces. Spaces inside code spans and links don't count, because
+/// breaking there would leave half a code span or link in each cell. Words
+/// that are longer than `width` on their own get a chunk to themselves. A
+/// `<br>` always starts a new chunk, as each one is a line of its own.
+fn wrap_description(description: &str, width: usize) -> Vec<String> {
+    if description.contains("<br>") {
+        return description
+            .split("<br>")
+            .flat_map(|line| wrap_description(line.trim(), width))
+            .collect();
+    }
+    let mut words = Vec::new();
+    let mut word_start = 0;
+    let mut backticks = 0;
+    let mut brackets = 0;
+    let mut chars = description.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '`' => {
+                let mut count = 1;
+                while chars.next_if(|&(_, c)| c == '`').is_some() {
+                    count += 1;
+                }
+                if backticks == 0 {
+                    backticks = count;
+                } else if backticks == count {
+                    backticks = 0;
+                }
+            }
+            '[' | '(' if backticks == 0 => brackets += 1,
+            ']' | ')' if backticks == 0 && brackets > 0 => brackets -= 1,
+            ' ' if backticks == 0 && brackets == 0 => {
+                words.push(&description[word_start..index]);
+                word_start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    words.push(&description[word_start..]);
+
+    let mut chunks: Vec<String> = Vec::new();
+    for word in words.into_iter().filter(|word| !word.is_empty()) {
+        match chunks.last_mut() {
+            Some(chunk) if chunk.chars().count() + 1 + word.chars().count() <= width => {
+                chunk.push(' ');
+                chunk.push_str(word);
+            }
+            _ => chunks.push(word.to_string()),
+        }
+    }
+    chunks
+}
+
+/// The settings from the environment that apply to all rows
+pub struct Settings {
+    /// How long descriptions can get before being wrapped into
+    /// continuation rows
+    pub wrap_width: Option<usize>,
+    /// The base for numbers in output cells, for rows that don't pick one
+    pub numbers: Option<String>,
+    /// How whitespace is shown, for rows that don't pick how
+    pub whitespace: Option<String>,
+    /// One of `STREAMING`, for rows that don't pick one
+    pub streaming: Option<String>,
+    /// The major version of nom that the examples get built with, which
+    /// rows can be limited to with the `nom` attribute
+    pub nom_major: String,
+    /// Where the docs of nom's items are, ending in a `/`
+    pub nom_docs: String,
+    /// Where the template files can be edited, ending in a `/`
+    pub edit: String,
+}
+
+/// What the rows of a template were parsed from, for finding the line of the
+/// file that each one is on
+pub struct Source<'a> {
+    /// The template with its includes put in
+    pub expanded: &'a Expanded,
+    /// The template without its front matter
+    pub body: &'a str,
+    /// The body with its code blocks taken care of, which is what the rows
+    /// are part of
+    pub document: &'a str,
+}
+
+impl Source<'_> {
+    /// Where the line of a row in the document can be edited. Files outside
+    /// of the repository can't be.
+    fn edit_url(&self, line: &str, edit: &str) -> Option<String> {
+        let offset = (line.as_ptr() as usize).checked_sub(self.document.as_ptr() as usize)?;
+        // Code blocks come out a line longer, so the row is found by how many
+        // of the same lines come before it, instead of by its line number
+        let same_before = self
+            .document
+            .get(..offset)?
+            .lines()
+            .filter(|other| *other == line)
+            .count();
+        let (index, _) = self
+            .body
+            .lines()
+            .enumerate()
+            .filter(|(_, other)| *other == line)
+            .nth(same_before)?;
+        let front_matter = &self.expanded.text[..self.expanded.text.len() - self.body.len()];
+        let (path, number) = self
+            .expanded
+            .location(front_matter.matches('\n').count() + index + 1);
+        let mut segments = Vec::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(segment) => segments.push(segment.to_str()?),
+                Component::CurDir => {}
+                _ => return None,
+            }
+        }
+        Some(format!("{edit}{}#L{number}", segments.join("/")))
+    }
+}
+
+/// A row with everything looked up that it needs for being generated
+pub struct Row<'a> {
+    /// The index of the template that the row is from
+    pub template: usize,
+    /// The heading of the section that the row is in
+    pub section: &'a str,
+    /// The combinators in the row's first cell
+    urls: Vec<Url>,
+    /// The combinators that the row shows, which rows that continue the
+    /// combinator above get from that row
+    pub combinators: Vec<Url>,
+    /// The first cell, with the links to the docs and the anchors that
+    /// mentions link to
+    pub first_cell: String,
+    /// The id of the anchor that the row's permalink goes to
+    pub permalink: String,
+    pub regex: Option<&'a str>,
+    pub usage: Option<&'a str>,
+    pub inputs: Vec<parse::Input<'a>>,
+    /// What the output cell has to come out as, if the template says
+    pub expected: Option<&'a str>,
+    /// The cells of the template's columns that are passed through, by the
+    /// name of their column
+    pub extra: HashMap<&'a str, &'a str>,
+    /// The description as it is in the template
+    pub template_description: &'a str,
+    /// The description with its mentions linked, and the part that fits in
+    /// the first row if descriptions get wrapped
+    pub description: String,
+    /// The rest of the wrapped description, a row's worth each
+    pub continuations: Vec<String>,
+    pub level: &'static str,
+    /// The crate features that the row needs
+    pub requires: Vec<&'a str>,
+    /// The version of nom that the row's combinators came with, like `7.1`
+    pub since: Option<&'a str>,
+    /// Whether nom deprecated the row's combinators, with what to use
+    /// instead if the template says
+    pub deprecated: Option<Option<&'a str>>,
+    /// What the row is for, like `numbers` or `whitespace`
+    pub tags: Vec<&'a str>,
+    /// One of `NUMBERS`, or the default if not set
+    pub numbers: Option<&'a str>,
+    pub visible_whitespace: bool,
+    /// The parts of the usage whose outputs are shown as steps
+    pub traces: Vec<&'a str>,
+    /// Whether the output also shows what `IResult`'s parameters were
+    /// inferred as
+    pub types: bool,
+    /// The imports that the row's code runs with
+    pub imports: syn::File,
+    /// The row's own imports, for its test
+    pub test_uses: Vec<syn::ItemUse>,
+    /// The imports of the row's combinators, with the name that the usage
+    /// calls each by, for its test
+    pub test_combinator_uses: Vec<(String, syn::ItemUse)>,
+    /// Imports of the `streaming` versions of the row's `complete`
+    /// combinators, to run the usage with a second time, if the row shows
+    /// both
+    pub streaming_uses: Vec<syn::ItemUse>,
+    /// What the row's combinators are called in winnow, with their module
+    /// in winnow, for the `winnow` column
+    pub winnow: Vec<Url>,
+    /// The signatures of the combinators in the first cell, for the
+    /// `signature` column, if they're functions of nom
+    pub signatures: Vec<Option<&'static str>>,
+    /// Whether the parser gets applied with `.parse(input)` even if it could
+    /// be called, as nom 8's parsers can't be
+    pub method_calls: bool,
+    /// Where the row's line of the template can be edited
+    pub edit: Option<String>,
+}
+
+/// A table, with its prose linked
+pub struct Section<'a> {
+    pub text: String,
+    pub header: &'a str,
+    /// The names of the columns in the template
+    pub columns: &'a [&'a str],
+    pub rows: Vec<Row<'a>>,
+}
+
+pub struct Template<'a> {
+    pub sections: Vec<Section<'a>>,
+    /// The text after the last table, without the closing of the first
+    /// template
+    pub remainder: String,
+}
+
+pub struct Transformed<'a> {
+    pub templates: Vec<Template<'a>>,
+    /// What goes at the very end of the sheet, the byte reference and the
+    /// first template's closing heading
+    pub closing: String,
+    /// The imports of nom's combinators by their name, leaving out the
+    /// names that several rows import from different places
+    pub uses: HashMap<String, Item>,
+    /// The docs of the combinators that mentions link to the rows of, by
+    /// the anchor on those rows, sorted
+    pub anchor_docs: Vec<(String, String)>,
+}
+
+/// The attributes that make rows only compile with some crate features,
+/// and the ones for the rows that say they need those features. Both are
+/// empty for rows that don't need any.
+pub fn features_cfg(requires: &[&str]) -> (TokenStream, TokenStream) {
+    if requires.is_empty() {
+        (quote! {}, quote! {})
+    } else {
+        let features = requires.iter().map(|feature| quote! { feature = #feature });
+        let features = quote! { all(#(#features),*) };
+        (
+            quote! { #[cfg(#features)] },
+            quote! { #[cfg(not(#features))] },
+        )
+    }
+}
+
+/// The level of a row. Rows that continue the combinator above also get its
+/// level, unless they have one of their own.
+fn level(row: &parse::Row, continuation: bool, last_level: &'static str) -> Result<&'static str> {
+    match row.attributes.get("level") {
+        Some(AttributeValue::String(level)) => LEVELS
+            .iter()
+            .copied()
+            .find(|known| known == level)
+            .ok_or_else(|| format!("Unknown level `{level}`, expected one of {LEVELS:?}").into()),
+        Some(_) => Err(format!("`level` needs to be one of {LEVELS:?}").into()),
+        None if continuation => Ok(last_level),
+        None => Ok("intermediate"),
+    }
+}
+
+/// The major versions of nom that a row is for, like `nom = 7` for
+/// combinators that nom 8 doesn't have anymore, or `None` if it doesn't say
+fn nom_versions<'a>(row: &parse::Row<'a>) -> Result<Option<Vec<&'a str>>> {
+    let versions = match row.attributes.get("nom") {
+        Some(AttributeValue::List(versions)) => versions.clone(),
+        Some(AttributeValue::String(version)) => vec![*version],
+        Some(AttributeValue::Flag) => {
+            return Err("`nom` needs a list of major versions of nom".into())
+        }
+        None => return Ok(None),
+    };
+    if let Some(version) = versions
+        .iter()
+        .find(|version| version.parse::<u64>().is_err())
+    {
+        return Err(format!(
+            "Unknown version of nom `{version}`, expected a major version like `7`"
+        )
+        .into());
+    }
+    Ok(Some(versions))
+}
+
+/// Imports of the `streaming` versions of those of `urls` that are in a
+/// `complete` module, if `urls` has them too. The other combinators aren't
+/// any different in streaming parsers.
+fn streaming_uses(urls: &[Url]) -> Result<Vec<syn::ItemUse>> {
+    let mut uses = Vec::new();
+    for url in urls {
+        let Some(parent) = url.module.strip_suffix("::complete") else {
+            continue;
+        };
+        let streaming_module = format!("{parent}::streaming");
+        if parent.starts_with("bits")
+            || !urls
+                .iter()
+                .any(|other| other.module == streaming_module && other.name == url.name)
+        {
+            continue;
+        }
+        let other_crate = OTHER_CRATES
+            .iter()
+            .any(|(crate_path, _)| parent.split("::").next() == Some(crate_path));
+        let path = match other_crate {
+            true => format!("{streaming_module}::{}", url.name),
+            false => format!("nom::{streaming_module}::{}", url.name),
+        };
+        let path: syn::Path = syn::parse_str(&path)?;
+        uses.push(parse_quote! {
+            #[allow(unused_imports)]
+            use #path;
+        });
+    }
+    Ok(uses)
+}
+
+/// The imports of a row, the ones of its test, and the ones of nom's
+/// combinators that go at the top of the generated code too
+struct Imports {
+    imports: syn::File,
+    test_uses: Vec<syn::ItemUse>,
+    test_combinator_uses: Vec<(String, syn::ItemUse)>,
+    shared: Vec<(String, Item)>,
+}
+
+/// Rows whose parser gets bits import the combinators in the `bits`
+/// modules, rather than the ones of the same name for bytes, and the other
+/// rows the other way around.
+fn imports(
+    template: usize,
+    imports: &str,
+    urls: &[Url],
+    bits: bool,
+    cfg: &TokenStream,
+) -> Result<Imports> {
+    let mut items: syn::File = syn::parse_str(imports)?;
+    let mut test_combinator_uses = Vec::new();
+    let mut shared = Vec::new();
+    let test_uses = items
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Use(item) => Some(item.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if template > 0 {
+        let fixtures = fixtures_ident(template);
+        items.items.push(parse_quote! {
+            #[allow(unused_imports)]
+            use #fixtures::*;
+        });
+    }
+    for url in urls {
+        let Url { module, name, .. } = url;
+        // filter out any modules that end with streaming, and the bits ones
+        // or the ones they have the same names as
+        let bits_version = urls
+            .iter()
+            .any(|other| other.module.starts_with("bits") && other.name == *name);
+        if module.ends_with("streaming") || (module.starts_with("bits") != (bits && bits_version)) {
+            continue;
+        }
+        let other_crate = OTHER_CRATES
+            .iter()
+            .any(|(crate_path, _)| module.split("::").next() == Some(crate_path));
+        let module = if other_crate {
+            module.clone()
+        } else {
+            format!("nom::{module}")
+        };
+        let module: syn::Path = syn::parse_str(&module)?;
+        // Methods come with their trait, which is all there is to import
+        // for them
+        if url.is_method() {
+            test_combinator_uses.push((name.clone(), parse_quote! { use #module; }));
+            items.items.push(parse_quote! {
+                #cfg
+                #[allow(unused_imports)]
+                use #module;
+            });
+            continue;
+        }
+        let name_ident = quote::format_ident!("{name}");
+        test_combinator_uses.push((name.clone(), parse_quote! { use #module::#name_ident; }));
+        let use_statement = Item::Use(parse_quote! {
+            #cfg
+            #[allow(unused_imports)]
+            use #module::#name_ident;
+        });
+        items.items.push(use_statement.clone());
+        // Other crates reuse nom's names, like nom-supreme's `tag`, and so
+        // do the bit parsers, so their imports stay in their own rows.
+        if !other_crate && !url.module.starts_with("bits") {
+            shared.push((name.clone(), use_statement));
+        }
+    }
+    Ok(Imports {
+        imports: items,
+        test_uses,
+        test_combinator_uses,
+        shared,
+    })
+}
+
+/// The features of nom that the item named `name` needs, going by
+/// `NOM_FEATURES`, for any item by that name
+fn gated_name_features(name: &str) -> Vec<&'static str> {
+    NOM_FEATURES
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once(' '))
+        .filter(|(path, _)| path.rsplit("::").next() == Some(name))
+        .flat_map(|(_, features)| features.split(' '))
+        .collect()
+}
+
+/// The signature of a combinator in `NOM_SIGNATURES`, if it's a function of
+/// nom
+pub fn signature(url: &Url) -> Option<&'static str> {
+    let path = format!("{}::{}", url.module, url.name);
+    NOM_SIGNATURES
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .find(|(item, _)| *item == path)
+        .map(|(_, signature)| signature)
+}
+
+/// Checks that a row says it `requires` the features of nom that its
+/// combinators need, and the ones that the functions and types its usage
+/// calls or names need, like `many1(` or `VerboseError<`. Without that it
+/// would break the build without those features instead of saying so in
+/// the output cell. `std` comes with `alloc`.
+fn check_required_features(row: &parse::Row, combinators: &[Url], requires: &[&str]) -> Result<()> {
+    let mut needed = combinators
+        .iter()
+        .filter(|url| !url.is_method())
+        .flat_map(|url| url.features.iter().copied())
+        .collect::<Vec<_>>();
+    let usage = row.usage.as_deref().unwrap_or_default();
+    let mut rest = usage;
+    while let Some(start) = rest.find(|c: char| c.is_alphabetic() || c == '_') {
+        let after = &rest[start..];
+        let end = after
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(after.len());
+        let (name, after) = after.split_at(end);
+        // Only whole names, not the end of e.g. `my_count`
+        let whole = !rest[..start].ends_with(|c: char| c.is_alphanumeric() || c == '_');
+        if whole && after.trim_start().starts_with(['(', '<']) {
+            needed.extend(gated_name_features(name));
+        }
+        rest = after;
+    }
+    for feature in needed {
+        let covered =
+            requires.contains(&feature) || (feature == "alloc" && requires.contains(&"std"));
+        if !covered {
+            return Err(format!(
+                "The row `{usage}` uses something that needs nom's `{feature}` feature, so it \
+                 needs `requires = [\"{feature}\"]`"
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// A row with what it means looked up, but not yet what depends on the
+/// other rows
+fn resolve_row<'a>(
+    template: usize,
+    section: &'a str,
+    row: &'a parse::Row<'a>,
+    last: Option<&Row>,
+    settings: &'a Settings,
+) -> Result<(Row<'a>, Vec<(String, Item)>)> {
+    let urls = row
+        .paths
+        .iter()
+        .map(|path| Url::resolve(path, &settings.nom_docs))
+        .collect::<Vec<_>>();
+    // Rows with a regex don't show any combinator in particular
+    let continuation = urls.is_empty() && row.regex.is_none();
+    let combinators = match last {
+        Some(last) if continuation => last.combinators.clone(),
+        _ if continuation => Vec::new(),
+        _ => urls.clone(),
+    };
+    let level = level(
+        row,
+        continuation,
+        last.map_or("intermediate", |last| last.level),
+    )?;
+    // Rows that need certain crate features only get compiled and run when
+    // those are enabled. Otherwise they say so in the output cell, instead
+    // of breaking the build.
+    let requires = match row.attributes.get("requires") {
+        Some(AttributeValue::List(features)) => features.clone(),
+        Some(AttributeValue::String(feature)) => vec![*feature],
+        Some(AttributeValue::Flag) => return Err("`requires` needs a list of features".into()),
+        None => Vec::new(),
+    };
+    if let Some(feature) = requires
+        .iter()
+        .find(|feature| !KNOWN_FEATURES.contains(feature))
+    {
+        return Err(format!(
+            "Unknown feature `{feature}` in `requires`, expected one of {KNOWN_FEATURES:?}"
+        )
+        .into());
+    }
+    check_required_features(row, &combinators, &requires)?;
+    let numbers = match row.attributes.get("numbers") {
+        Some(AttributeValue::String(numbers)) => Some(*numbers),
+        Some(_) => return Err(format!("`numbers` needs to be one of {NUMBERS:?}").into()),
+        None => settings.numbers.as_deref(),
+    };
+    if let Some(numbers) = numbers.filter(|numbers| !NUMBERS.contains(numbers)) {
+        return Err(format!("Unknown numbers `{numbers}`, expected one of {NUMBERS:?}").into());
+    }
+    let visible_whitespace = visible_whitespace_for(match row.attributes.get("whitespace") {
+        Some(AttributeValue::String(whitespace)) => Some(*whitespace),
+        Some(_) => return Err(format!("`whitespace` needs to be one of {WHITESPACE:?}").into()),
+        None => settings.whitespace.as_deref(),
+    })?;
+    // Parsers that are nested in a combinator chain can be traced, which
+    // shows what they passed on to the next stage as steps.
+    let traces = match row.attributes.get("trace") {
+        Some(AttributeValue::List(traces)) => traces.clone(),
+        Some(AttributeValue::String(trace)) => vec![*trace],
+        Some(AttributeValue::Flag) => return Err("`trace` needs a list of expressions".into()),
+        None => Vec::new(),
+    };
+    // Rows can say which version of nom their combinators came with, and
+    // whether it deprecated them, which the first cell gets badges for
+    let since = match row.attributes.get("since") {
+        Some(AttributeValue::String(since)) => Some(*since),
+        Some(_) => return Err("`since` needs a version of nom, like `since = \"7.1\"`".into()),
+        None => None,
+    };
+    if let Some(since) =
+        since.filter(|since| !since.split('.').all(|part| part.parse::<u64>().is_ok()))
+    {
+        return Err(format!(
+            "Unknown version of nom `{since}` in `since`, expected one like `7.1`"
+        )
+        .into());
+    }
+    let deprecated = match row.attributes.get("deprecated") {
+        Some(AttributeValue::String(note)) => Some(Some(*note)),
+        Some(AttributeValue::Flag) => Some(None),
+        Some(AttributeValue::List(_)) => {
+            return Err("`deprecated` needs to be a flag or a note".into())
+        }
+        None => None,
+    };
+    // Rows can be tagged with what they're for, like `numbers`, which the
+    // HTML can filter by and the "I want to…" section lists them by
+    let tags = match row.attributes.get("tags") {
+        Some(AttributeValue::List(tags)) => tags.clone(),
+        Some(AttributeValue::String(tag)) => vec![*tag],
+        Some(AttributeValue::Flag) => return Err("`tags` needs a list of tags".into()),
+        None => Vec::new(),
+    };
+    if let Some(tag) = tags.iter().find(|tag| {
+        tag.is_empty()
+            || !tag
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    }) {
+        return Err(
+            format!("Tag `{tag}` needs to be lowercase letters, digits, and dashes").into(),
+        );
+    }
+    // Combinators that are in both a `complete` and a `streaming` module
+    // can show how they differ on the same input, which is mostly that the
+    // streaming ones ask for more of it
+    let streaming = match row.attributes.get("streaming") {
+        Some(AttributeValue::String(streaming)) => Some(*streaming),
+        Some(_) => return Err(format!("`streaming` needs to be one of {STREAMING:?}").into()),
+        None => settings.streaming.as_deref(),
+    };
+    if let Some(streaming) = streaming.filter(|streaming| !STREAMING.contains(streaming)) {
+        return Err(
+            format!("Unknown streaming `{streaming}`, expected one of {STREAMING:?}").into(),
+        );
+    }
+    let streaming_uses = match streaming {
+        Some("both") => streaming_uses(&combinators)?,
+        _ => Vec::new(),
+    };
+    // For people moving to winnow, or choosing between the two, rows can
+    // say what their combinators are in winnow, like `token::take_until`
+    let winnow = match row.attributes.get("winnow") {
+        Some(AttributeValue::List(paths)) => paths.clone(),
+        Some(AttributeValue::String(path)) => vec![*path],
+        Some(AttributeValue::Flag) => return Err("`winnow` needs a list of paths in winnow".into()),
+        None => Vec::new(),
+    };
+    let winnow = winnow
+        .iter()
+        .map(|path| {
+            let mut url = Url::resolve(&format!("winnow::{path}"), &settings.nom_docs);
+            url.module = url.module.trim_start_matches("winnow::").to_string();
+            url
+        })
+        .collect();
+    let signatures = urls.iter().map(signature).collect();
+    // Bit parsers get a tuple of the bytes and the bit to start at
+    let bits = row
+        .inputs
+        .iter()
+        .any(|input| matches!(syn::parse_str(&input.code), Ok(syn::Expr::Tuple(_))));
+    let (cfg, _) = features_cfg(&requires);
+    let imports = imports(template, row.imports, &combinators, bits, &cfg)?;
+    // Expected outputs are written for the default formatting, which the
+    // settings for the whole sheet change, unlike the row's own attributes
+    let default_formatting =
+        settings.numbers.is_none() && settings.whitespace.is_none() && settings.streaming.is_none();
+    let row = Row {
+        template,
+        section,
+        urls,
+        combinators,
+        first_cell: String::new(),
+        permalink: String::new(),
+        regex: row.regex.as_deref(),
+        usage: row.usage.as_deref(),
+        inputs: row.inputs.clone(),
+        expected: row.expected.filter(|_| default_formatting),
+        extra: row.extra.clone(),
+        template_description: row.description,
+        description: String::new(),
+        continuations: Vec::new(),
+        level,
+        requires,
+        since,
+        deprecated,
+        tags,
+        numbers,
+        visible_whitespace,
+        traces,
+        types: row.attributes.contains_key("types"),
+        imports: imports.imports,
+        test_uses: imports.test_uses,
+        test_combinator_uses: imports.test_combinator_uses,
+        streaming_uses,
+        winnow,
+        signatures,
+        method_calls: settings
+            .nom_major
+            .parse::<u64>()
+            .is_ok_and(|major| major >= 8),
+        edit: None,
+    };
+    Ok((row, imports.shared))
+}
+
+/// Headings that are in more than one template are most likely copies, and
+/// would end up with the same anchors
+fn check_headings(sheets: &[parse::Sheet], template_paths: &[PathBuf]) -> Result<()> {
+    let mut heading_templates = HashMap::<&str, usize>::new();
+    for (number, sheet) in sheets.iter().enumerate() {
+        let texts = sheet.sections.iter().map(|section| section.text);
+        for heading in texts.chain([sheet.remainder]).flat_map(headings) {
+            match heading_templates.insert(heading, number) {
+                Some(other) if other != number => {
+                    return Err(format!(
+                        "Heading `{heading}` is in both {} and {}",
+                        template_paths[other].display(),
+                        template_paths[number].display()
+                    )
+                    .into())
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Where mentions of each combinator link to, by both its path and its bare
+/// name. `rows` are all rows of all templates, in order.
+fn mention_targets<'r, 'a: 'r>(
+    rows: impl Iterator<Item = &'r Row<'a>>,
+) -> HashMap<String, MentionTarget> {
+    let mut mention_targets = HashMap::<String, MentionTarget>::new();
+    for (row, combinator) in rows.enumerate() {
+        let level = LEVELS
+            .iter()
+            .position(|known| *known == combinator.level)
+            .unwrap();
+        for url in &combinator.urls {
+            let path = format!("{}::{}", url.module, url.name);
+            // Bare names of methods, like `map`, mean the combinators
+            let keys = if url.is_method() {
+                vec![path.clone()]
+            } else {
+                vec![path.clone(), url.name.clone()]
+            };
+            for key in keys {
+                if mention_targets
+                    .get(&key)
+                    .is_some_and(|target| target.level <= level)
+                {
+                    continue;
+                }
+                let target = MentionTarget {
+                    row,
+                    path: path.clone(),
+                    anchor: row_anchor(url),
+                    docsurl: url.docsurl.clone(),
+                    level,
+                };
+                mention_targets.insert(key, target);
+            }
+        }
+    }
+    mention_targets
+}
+
+pub fn transform<'a>(
+    sheets: &'a [parse::Sheet<'a>],
+    sources: &[Source],
+    template_paths: &[PathBuf],
+    settings: &'a Settings,
+) -> Result<Transformed<'a>> {
+    check_headings(sheets, template_paths)?;
+    // The imports of nom's combinators also go at the top of the generated
+    // code, for the tests of rows that use combinators that only other rows
+    // import. They're deduplicated by name, as `character::complete::i8`
+    // and `number::complete::i8` would clash, and names that are imported
+    // from more than one place are left out.
+    let mut uses = HashMap::<String, Item>::new();
+    let mut uses_conflicts = HashSet::<String>::new();
+    let mut templates = Vec::new();
+    let mut closing = "";
+    for (number, sheet) in sheets.iter().enumerate() {
+        let in_path = |e| format!("In {}: {e}", template_paths[number].display());
+        let mut sections = Vec::new();
+        let mut heading = "";
+        for section in &sheet.sections {
+            heading = section.heading.unwrap_or(heading);
+            // Rows only continue the combinator of the row above within a
+            // table
+            let mut rows: Vec<Row> = Vec::new();
+            // Rows for other versions of nom are left out, and so are the
+            // rows that continue their combinator, unless they say otherwise
+            let mut other_version = false;
+            for row in &section.rows {
+                let continuation = row.paths.is_empty() && row.regex.is_none();
+                match nom_versions(row).map_err(in_path)? {
+                    Some(versions) => other_version = !versions.contains(&&*settings.nom_major),
+                    None if continuation => {}
+                    None => other_version = false,
+                }
+                if other_version {
+                    continue;
+                }
+                let edit = sources[number].edit_url(row.line, &settings.edit);
+                let (mut row, shared) =
+                    resolve_row(number, heading, row, rows.last(), settings).map_err(in_path)?;
+                row.edit = edit;
+                for (name, use_statement) in shared {
+                    if let Some(conflict) = uses.insert(name.clone(), use_statement.clone()) {
+                        if conflict != use_statement {
+                            uses_conflicts.insert(name);
+                        }
+                    }
+                }
+                rows.push(row);
+            }
+            sections.push(Section {
+                text: section.text.to_string(),
+                header: section.header,
+                columns: &section.columns,
+                rows,
+            });
+        }
+        // Everything but the first template goes in before the first one's
+        // closing `# ` heading
+        let remainder = if number == 0 {
+            let (remainder, rest) = split_closing(sheet.remainder);
+            closing = rest;
+            remainder
+        } else {
+            sheet.remainder
+        };
+        templates.push(Template {
+            sections,
+            remainder: remainder.to_string(),
+        });
+    }
+    for conflict in uses_conflicts {
+        uses.remove(&conflict);
+    }
+
+    // Mentions of combinators in the prose link to their rows, so those are
+    // needed before any prose gets linked
+    let all_rows = || {
+        templates
+            .iter()
+            .flat_map(|template: &Template| &template.sections)
+            .flat_map(|section| &section.rows)
+    };
+    let mention_targets = mention_targets(all_rows());
+    // How often each pair of combinators is used together in a usage cell
+    let mut together = HashMap::<&str, HashMap<&str, usize>>::new();
+    for usage in all_rows().filter_map(|row| row.usage) {
+        let used = used_combinators(usage, &mention_targets);
+        for name in &used {
+            for other in used.iter().filter(|other| *other != name) {
+                *together.entry(name).or_default().entry(other).or_default() += 1;
+            }
+        }
+    }
+
+    let tag_index = tag_index(all_rows());
+
+    // Rows get an anchor for each of their combinators that mentions link to
+    let mut row_number = 0;
+    let mut used_anchors = mention_targets
+        .values()
+        .map(|target| target.anchor.clone())
+        .collect::<HashSet<_>>();
+    for template in &mut templates {
+        for section in &mut template.sections {
+            // Prose outside of tables is in every sheet
+            section.text = link_mentions(&section.text, &mention_targets, &[], 0);
+            for row in &mut section.rows {
+                let anchors = row
+                    .urls
+                    .iter()
+                    .map(|url| {
+                        let path = format!("{}::{}", url.module, url.name);
+                        mention_targets
+                            .get(&path)
+                            .is_some_and(|target| target.row == row_number)
+                    })
+                    .collect::<Vec<_>>();
+                row_number += 1;
+                row.permalink = permalink(&row.combinators, &anchors, &mut used_anchors);
+                describe(row, &anchors, &mention_targets, &together, settings);
+                row.first_cell = match row.regex {
+                    Some(regex) => markdown_cell_code(regex),
+                    None => row
+                        .urls
+                        .iter()
+                        .zip(&anchors)
+                        .map(|(url, anchored)| {
+                            let Url {
+                                module,
+                                name,
+                                docsurl,
+                                features,
+                                ..
+                            } = url;
+                            let badges = feature_badges(features);
+                            if *anchored {
+                                let anchor = row_anchor(url);
+                                format!(
+                                    r#"<a id="{anchor}"></a>{module}::[{name}]({docsurl}){badges}"#
+                                )
+                            } else {
+                                format!("{module}::[{name}]({docsurl}){badges}")
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join("<br>"),
+                };
+                if !row.first_cell.is_empty() {
+                    row.first_cell.push_str(&version_badges(row));
+                }
+            }
+        }
+        template.remainder = link_mentions(&template.remainder, &mention_targets, &[], 0);
+    }
+    let closing = link_mentions(
+        &format!("{tag_index}{}{closing}", byte_reference()),
+        &mention_targets,
+        &[],
+        0,
+    );
+    let mut anchor_docs = mention_targets
+        .into_values()
+        .map(|target| (target.anchor, target.docsurl))
+        .collect::<Vec<_>>();
+    anchor_docs.sort();
+    anchor_docs.dedup();
+    Ok(Transformed {
+        templates,
+        closing,
+        uses,
+        anchor_docs,
+    })
+}
+
+/// Fills in the description of a row. The rows that mentions link to also
+/// say what their combinators are commonly used with, going by all usages.
+fn describe(
+    row: &mut Row,
+    anchors: &[bool],
+    mention_targets: &HashMap<String, MentionTarget>,
+    together: &HashMap<&str, HashMap<&str, usize>>,
+    settings: &Settings,
+) {
+    let level_index = LEVELS.iter().position(|known| *known == row.level).unwrap();
+    let mut description = escape_cell(row.template_description);
+    if anchors.contains(&true) {
+        let names = row
+            .urls
+            .iter()
+            .filter(|url| !url.is_method())
+            .map(|url| url.name.as_str())
+            .collect::<Vec<_>>();
+        let combined = combined_with(&names, together);
+        if !combined.is_empty() {
+            let combined = combined
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            description.push_str(&format!("<br>Commonly combined with {combined}"));
+        }
+    }
+    let description = link_mentions(&description, mention_targets, &row.combinators, level_index);
+    (row.description, row.continuations) = match settings.wrap_width {
+        Some(width) => {
+            let mut chunks = wrap_description(&description, width).into_iter();
+            (chunks.next().unwrap_or_default(), chunks.collect())
+        }
+        None => (description, Vec::new()),
+    };
+}
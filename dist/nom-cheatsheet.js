@@ -0,0 +1,46 @@
+// Runs the examples of the nom cheatsheet, using `nom-cheatsheet.wasm`.
+//
+//     import { load } from "./nom-cheatsheet.js";
+//     const cheatsheet = await load();
+//     cheatsheet.run(0, "\nhello"); // { result: "'\\n'", remainder: "\"hello\"", output: … }
+//
+// Ids are the indexes into `cheatsheet.labels()`, which describe the rows in
+// the order they appear in the cheatsheet.
+
+export async function load(url = new URL("nom-cheatsheet.wasm", import.meta.url)) {
+    const { instance } = await WebAssembly.instantiateStreaming(fetch(url));
+    return new Cheatsheet(instance.exports);
+}
+
+class Cheatsheet {
+    constructor(exports) {
+        this.exports = exports;
+    }
+
+    // Strings get UTF-8 encoded. Returns `{ result, remainder }` as formatted
+    // by Rust's `Debug`, plus `steps` for rows that show intermediate values,
+    // or `{ error }` if the parser failed. Either way `output` has the
+    // markdown of the output cell that the sheet would show for the input.
+    run(combinatorId, inputBytes) {
+        if (typeof inputBytes === "string") {
+            inputBytes = new TextEncoder().encode(inputBytes);
+        }
+        const { alloc, dealloc, run } = this.exports;
+        const pointer = alloc(inputBytes.length);
+        new Uint8Array(this.exports.memory.buffer, pointer, inputBytes.length).set(inputBytes);
+        try {
+            return this.output(run(combinatorId, pointer, inputBytes.length));
+        } finally {
+            dealloc(pointer, inputBytes.length);
+        }
+    }
+
+    labels() {
+        return this.output(this.exports.labels());
+    }
+
+    output(pointer) {
+        const bytes = new Uint8Array(this.exports.memory.buffer, pointer, this.exports.output_len());
+        return JSON.parse(new TextDecoder().decode(bytes));
+    }
+}
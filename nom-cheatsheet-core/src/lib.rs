@@ -0,0 +1,15 @@
+//! The engine of the cheatsheet, for sheets of other parser crates too: it
+//! parses a template into its prose and its tables of rows with
+//! [`Sheet::parse`], and writes it back with [`Sheet::to_markdown`]. Running
+//! the examples of the rows is up to the sheet, as they need its crate.
+
+mod markdown;
+mod parse;
+
+pub use parse::{
+    escape_cell, headings, parse_code_block, parse_outside_code_blocks, AttributeValue, CodeBlock,
+    Component, Input, Row, Section, Sheet,
+};
+
+pub type Result<T> = core::result::Result<T, Error>;
+pub type Error = Box<dyn std::error::Error>;
@@ -0,0 +1,147 @@
+//! Writes a parsed sheet back as a template. The prose comes out as it went
+//! in, but the rows are normalized: one space around each cell, code spans
+//! with as few backticks as they need, and attributes in alphabetical order
+//! with `level` last, as most rows in the template have them. Parsing the
+//! result gives the same sheet again.
+
+use crate::parse::{escape_cell, AttributeValue, Row, Sheet};
+use nom_cheatsheet_shared::{markdown_cell_code, markdown_cell_code_lines};
+
+/// An attribute value as it's written in the template, bare where it can be
+fn attribute_value(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_alphanumeric() || "_-.".contains(c))
+    {
+        value.to_string()
+    } else {
+        format!("\"{value}\"")
+    }
+}
+
+/// The comment that carries the attributes at the end of a description, or
+/// nothing if there are none
+fn attributes(row: &Row) -> String {
+    let mut keys = row.attributes.keys().copied().collect::<Vec<_>>();
+    keys.sort_by_key(|key| (*key == "level", *key));
+    if keys.is_empty() {
+        return String::new();
+    }
+    let attributes = keys
+        .iter()
+        .map(|key| match &row.attributes[key] {
+            AttributeValue::Flag => key.to_string(),
+            AttributeValue::String(value) => format!("{key} = {}", attribute_value(value)),
+            AttributeValue::List(values) => {
+                let values = values
+                    .iter()
+                    .map(|value| format!("\"{value}\""))
+                    .collect::<Vec<_>>();
+                format!("{key} = [{}]", values.join(", "))
+            }
+        })
+        .collect::<Vec<_>>();
+    format!(" <!-- {} -->", attributes.join(", "))
+}
+
+impl Row<'_> {
+    /// The row as a line of a table with `columns`, with its line ending
+    #[must_use]
+    pub fn to_markdown(&self, columns: &[&str]) -> String {
+        let mut parser = self
+            .regex
+            .as_deref()
+            .map(markdown_cell_code)
+            .unwrap_or_default();
+        parser.push_str(&self.paths.join("<br>"));
+        let usage = self
+            .usage
+            .as_ref()
+            .map(|usage| markdown_cell_code_lines(&format!("{}{usage}", self.imports)))
+            .unwrap_or_default();
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|input| match input.fails {
+                true => format!("Err: {}", markdown_cell_code(&input.code)),
+                false => markdown_cell_code(&input.code),
+            })
+            .collect::<Vec<_>>()
+            .join("<br>");
+        let expected = self.expected.unwrap_or_default();
+        let description = format!("{}{}", escape_cell(self.description), attributes(self));
+        // Rows that continue the combinator above start with `| |`, which
+        // makes them easy to tell apart
+        let mut line = match parser.is_empty() {
+            true => "| |".to_string(),
+            false => format!("| {parser} |"),
+        };
+        for column in columns.iter().skip(1) {
+            let cell = match *column {
+                "usage" => usage.as_str(),
+                "input" => inputs.as_str(),
+                "output" => expected,
+                "description" => description.trim_start(),
+                extra => self.extra.get(extra).copied().unwrap_or_default(),
+            };
+            line.push_str(&format!(" {cell} |"));
+        }
+        line.push('\n');
+        line
+    }
+}
+
+impl Sheet<'_> {
+    /// The sheet as a template
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        for section in &self.sections {
+            markdown.push_str(section.text);
+            markdown.push_str(section.header);
+            for row in &section.rows {
+                markdown.push_str(&row.to_markdown(&section.columns));
+            }
+        }
+        markdown.push_str(self.remainder);
+        markdown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_markdown() {
+        let template = "# Sheet\n\n\
+            | parser | usage | input | output | description |\n\
+            |---|---|---|---|---|\n\
+            | bytes::complete::tag<br>bytes::streaming::tag | `tag(\"a\")` | `\"abc\"`<br>`b\"abc\"` | | One <!-- requires = [\"alloc\"], level=beginner --> |\n\
+            | | `` use nom::character::complete::char; char('`') `` | `\"a\"`<br>Err: `\"b\"` |Result: `'a'`   | Two |\n\
+            | `\\d+` | `digit1` | `\"1\"` |  |  <!-- types, trace = [\"take(5_u8)\"] --> |\n\
+            | `a\\|b` | `alt((tag(\"a\"), tag(\"b\")))` | `\"a\\|\"` |  | Like `a | b` |\n\
+            | | `use nom::bytes::complete::tag; let b = tag(\"b\");` <br> `b` | `\"b\"` |  | One<br>Two |\n\
+            \n| parser | notes | description |\n|:---|---|:---|\n\
+            | combinator::eof |Zero-length|At the end|\n\
+            \nThe end\n";
+        let sheet = Sheet::parse(template).unwrap();
+        let markdown = sheet.to_markdown();
+        assert_eq!(
+            markdown,
+            "# Sheet\n\n\
+            | parser | usage | input | output | description |\n\
+            |---|---|---|---|---|\n\
+            | bytes::complete::tag<br>bytes::streaming::tag | `tag(\"a\")` | `\"abc\"`<br>`b\"abc\"` |  | One <!-- requires = [\"alloc\"], level = beginner --> |\n\
+            | | ``use nom::character::complete::char; char('`')`` | `\"a\"`<br>Err: `\"b\"` | Result: `'a'` | Two |\n\
+            | `\\d+` | `digit1` | `\"1\"` |  | <!-- trace = [\"take(5_u8)\"], types --> |\n\
+            | `a\\|b` | `alt((tag(\"a\"), tag(\"b\")))` | `\"a\\|\"` |  | Like `a \\| b` |\n\
+            | | `use nom::bytes::complete::tag; let b = tag(\"b\");`<br>`b` | `\"b\"` |  | One<br>Two |\n\
+            \n| parser | notes | description |\n|:---|---|:---|\n\
+            | combinator::eof | Zero-length | At the end |\n\
+            \nThe end\n"
+        );
+        assert_eq!(Sheet::parse(&markdown).unwrap().to_markdown(), markdown);
+    }
+}
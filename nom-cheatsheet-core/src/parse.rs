@@ -0,0 +1,626 @@
+//! Parses a template into the prose and the tables that it's made of. Nothing
+//! gets looked up or checked against the other rows here, that's up to what
+//! the sheet gets generated with.
+
+use crate::Result;
+use nom::{
+    branch::alt,
+    bytes::complete::{is_a, is_not, tag, take_until, take_while1},
+    character::complete::{char, line_ending, not_line_ending, space0},
+    combinator::{all_consuming, map, opt, recognize, rest},
+    multi::{many0, many1, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+use std::{borrow::Cow, collections::HashMap};
+
+/// The columns that a table can have besides the first one, which has the
+/// combinators or the regex, whatever it's called. Tables can leave any of
+/// them out, or have them in another order. Columns with other names, like
+/// `notes`, are passed through as they are.
+pub static KNOWN_COLUMNS: &[&str] = &["usage", "input", "output", "description"];
+
+/// Rows can carry extra settings in an HTML comment at the end of the
+/// description cell, which keeps them invisible when the template itself is
+/// rendered. For example: `<!-- types, requires = ["alloc"] -->`
+#[derive(Debug, PartialEq)]
+pub enum AttributeValue<'a> {
+    Flag,
+    String(&'a str),
+    List(Vec<&'a str>),
+}
+
+#[derive(Debug)]
+pub enum Component<'a> {
+    Text(&'a str),
+    CodeBlock(CodeBlock<'a>),
+}
+
+#[derive(Debug)]
+pub struct CodeBlock<'a> {
+    pub language: &'a str,
+    pub code: &'a str,
+}
+
+pub fn parse_outside_code_blocks(input: &str) -> IResult<&str, Component<'_>> {
+    let (input, text) = alt((take_until("```"), rest))(input)?;
+    if text.is_empty() {
+        return Err(nom::Err::Error(nom::error::Error {
+            input,
+            code: nom::error::ErrorKind::Eof,
+        }));
+    }
+    Ok((input, Component::Text(text)))
+}
+
+pub fn parse_code_block(input: &str) -> IResult<&str, Component<'_>> {
+    let (input, _) = tag("```")(input)?;
+    let (input, language) = terminated(not_line_ending, line_ending)(input)?;
+    let (input, code) = take_until("```")(input)?;
+    let (input, _) = tag("```")(input)?;
+    Ok((input, Component::CodeBlock(CodeBlock { language, code })))
+}
+
+fn parse_code_span(input: &str) -> IResult<&str, &str> {
+    let (input, backticks) = is_a("`")(input)?;
+    let (input, code) = take_until(backticks)(input)?;
+    let (input, _) = tag(backticks)(input)?;
+    // Strip a single space from the beginning and the end of the code,
+    // but only if they're both there. If only one is there, leave it.
+    let code = if code.len() >= 2 && code.starts_with(' ') && code.ends_with(' ') {
+        &code[1..code.len() - 1]
+    } else {
+        code
+    };
+    Ok((input, code))
+}
+
+fn parse_quoted(input: &str) -> IResult<&str, &str> {
+    delimited(char('"'), take_until("\""), char('"'))(input)
+}
+
+fn parse_attribute_value(input: &str) -> IResult<&str, AttributeValue<'_>> {
+    alt((
+        map(parse_quoted, AttributeValue::String),
+        map(
+            delimited(
+                pair(char('['), space0),
+                separated_list0(tuple((space0, char(','), space0)), parse_quoted),
+                pair(space0, char(']')),
+            ),
+            AttributeValue::List,
+        ),
+        map(
+            take_while1(|c: char| c.is_alphanumeric() || "_-.".contains(c)),
+            AttributeValue::String,
+        ),
+    ))(input)
+}
+
+fn parse_attribute(input: &str) -> IResult<&str, (&str, AttributeValue<'_>)> {
+    let (input, key) = take_while1(|c: char| c.is_alphanumeric() || c == '_' || c == '-')(input)?;
+    let (input, value) = opt(preceded(
+        tuple((space0, char('='), space0)),
+        parse_attribute_value,
+    ))(input)?;
+    Ok((input, (key, value.unwrap_or(AttributeValue::Flag))))
+}
+
+fn parse_attributes(input: &str) -> IResult<&str, HashMap<&str, AttributeValue<'_>>> {
+    let (input, _) = pair(tag("<!--"), space0)(input)?;
+    let (input, attributes) =
+        separated_list0(tuple((space0, char(','), space0)), parse_attribute)(input)?;
+    let (input, _) = pair(space0, tag("-->"))(input)?;
+    Ok((input, attributes.into_iter().collect()))
+}
+
+/// Splits the attributes comment, if any, off the end of a description
+fn split_attributes(description: &str) -> Result<(&str, HashMap<&str, AttributeValue<'_>>)> {
+    if !description.ends_with("-->") {
+        return Ok((description, HashMap::new()));
+    }
+    let start = description
+        .rfind("<!--")
+        .ok_or_else(|| format!("Unterminated attributes comment in `{description}`"))?;
+    let (_, attributes) = all_consuming(parse_attributes)(&description[start..])
+        .map_err(|e| format!("Invalid attributes in `{description}`: {e}"))?;
+    Ok((description[..start].trim_end(), attributes))
+}
+
+/// An input of a row. Inputs that are marked with `Err: ` in front of them,
+/// like `` Err: `"xyz"` ``, show what the parser fails on, and have to make
+/// it fail.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Input<'a> {
+    pub code: Cow<'a, str>,
+    pub fails: bool,
+}
+
+fn parse_input(input: &str) -> IResult<&str, Input<'_>> {
+    map(
+        pair(opt(tag("Err: ")), parse_code_span),
+        |(marker, code)| Input {
+            code: unescape_pipes(code),
+            fails: marker.is_some(),
+        },
+    )(input)
+}
+
+/// A row of a table, as it is in the template
+#[derive(Debug)]
+pub struct Row<'a> {
+    /// The whole line of the row, which is part of the template, for finding
+    /// where in there it is
+    pub line: &'a str,
+    /// The paths of the combinators in the first cell, like
+    /// `bytes::complete::tag`. Rows that continue the combinator of the row
+    /// above have none.
+    pub paths: Vec<&'a str>,
+    /// Rows that translate a regex have that in the first cell instead
+    pub regex: Option<Cow<'a, str>>,
+    pub imports: &'a str,
+    pub usage: Option<Cow<'a, str>>,
+    pub inputs: Vec<Input<'a>>,
+    /// What the output cell should come out as, if the template says. The
+    /// output cell is usually empty, as it gets filled in with the outputs
+    /// of the inputs.
+    pub expected: Option<&'a str>,
+    pub description: &'a str,
+    /// From the comment at the end of the description cell, which the
+    /// description is without
+    pub attributes: HashMap<&'a str, AttributeValue<'a>>,
+    /// The cells of the columns that aren't in `KNOWN_COLUMNS`, by the name
+    /// of their column
+    pub extra: HashMap<&'a str, &'a str>,
+}
+
+/// The text of a cell, up to the pipe that ends it. Pipes that are escaped
+/// with a backslash, or that are in code spans, are part of it. Backticks
+/// that don't start a code span are just backticks.
+fn parse_cell(input: &str) -> IResult<&str, &str> {
+    recognize(many0(alt((
+        tag("\\|"),
+        recognize(parse_code_span),
+        is_not("\\|`"),
+        tag("\\"),
+        is_a("`"),
+    ))))(input)
+}
+
+/// Splits a table row into its cells, without the padding around them
+fn parse_cells(line: &str) -> IResult<&str, Vec<&str>> {
+    all_consuming(delimited(
+        pair(space0, char('|')),
+        many0(map(terminated(parse_cell, char('|')), str::trim)),
+        space0,
+    ))(line)
+}
+
+/// Code in a table cell has its pipes escaped, like `\|`, which they aren't
+/// in the code itself
+fn unescape_pipes(code: &str) -> Cow<'_, str> {
+    match code.contains("\\|") {
+        true => Cow::Owned(code.replace("\\|", "|")),
+        false => Cow::Borrowed(code),
+    }
+}
+
+/// `text` for a table cell, with the pipes that would end it escaped. The
+/// template can leave them unescaped in code spans, but GitHub can't.
+#[must_use]
+pub fn escape_cell(text: &str) -> String {
+    let Ok((_, tokens)) = all_consuming(many0(alt((
+        map(recognize(parse_code_span), |span: &str| {
+            Cow::Owned(span.replace("\\|", "|").replace('|', "\\|"))
+        }),
+        map(
+            alt((
+                tag("\\|"),
+                map(tag("|"), |_| "\\|"),
+                is_not("\\|`"),
+                tag("\\"),
+                is_a("`"),
+            )),
+            Cow::Borrowed,
+        ),
+    ))))(text) else {
+        unreachable!("Every text is a sequence of tokens")
+    };
+    tokens.concat()
+}
+
+/// Splits the line under the column names of a table into its cells, like
+/// `---`, with colons for the alignment, like `:---:`
+fn parse_separator(line: &str) -> IResult<&str, Vec<&str>> {
+    all_consuming(delimited(
+        pair(space0, char('|')),
+        many1(terminated(
+            delimited(
+                space0,
+                recognize(tuple((opt(char(':')), is_a("-"), opt(char(':'))))),
+                space0,
+            ),
+            char('|'),
+        )),
+        space0,
+    ))(line)
+}
+
+/// Finds the first table in `input`, a line of column names with a separator
+/// line of as many cells under it. Returns where the table starts, where its
+/// rows start, and the names of its columns.
+fn find_table(input: &str) -> Option<(usize, usize, Vec<&str>)> {
+    let mut lines = input
+        .split_inclusive('\n')
+        .scan(0, |start, line| {
+            let line_start = *start;
+            *start += line.len();
+            Some((line_start, line))
+        })
+        .peekable();
+    while let Some((start, line)) = lines.next() {
+        let &(separator_start, separator) = lines.peek()?;
+        let Ok((_, columns)) = parse_cells(line.trim_end()) else {
+            continue;
+        };
+        let Ok((_, separators)) = parse_separator(separator.trim_end()) else {
+            continue;
+        };
+        if columns.len() == separators.len() && separator.ends_with('\n') {
+            return Some((start, separator_start + separator.len(), columns));
+        }
+    }
+    None
+}
+
+/// The cell of `cells` in the column called `name`, or an empty one if the
+/// table doesn't have that column
+fn cell<'a>(cells: &[&'a str], columns: &[&str], name: &str) -> &'a str {
+    columns
+        .iter()
+        .position(|column| *column == name)
+        .map_or("", |index| cells[index])
+}
+
+// This parses a single table row, with the cells in the order of `columns`
+fn parse_row<'a>(input: &'a str, columns: &[&'a str]) -> IResult<&'a str, Row<'a>> {
+    let (input, line) = terminated(not_line_ending, line_ending)(input)?;
+    let (_, cells) = parse_cells(line)?;
+    if cells.len() != columns.len() {
+        return Err(nom::Err::Error(nom::error::Error {
+            input: line,
+            code: nom::error::ErrorKind::Count,
+        }));
+    }
+    let first = cells[0];
+    let [usage, inputs, expected, description] =
+        ["usage", "input", "output", "description"].map(|name| cell(&cells, columns, name));
+    let extra = columns
+        .iter()
+        .zip(&cells)
+        .skip(1)
+        .filter(|(column, _)| !KNOWN_COLUMNS.contains(column))
+        .map(|(column, cell)| (*column, *cell))
+        .collect();
+    let (_, (regex, paths)) = all_consuming(pair(opt(parse_code_span), rest))(first)?;
+    let (_, usage) = all_consuming(opt(separated_list1(
+        delimited(space0, tag("<br>"), space0),
+        parse_code_span,
+    )))(usage)?;
+    let (_, example_inputs) = all_consuming(separated_list0(tag("<br>"), parse_input))(inputs)?;
+
+    let expected = Some(expected).filter(|expected| !expected.is_empty());
+    let paths = paths
+        .trim_start()
+        .split("<br>")
+        .filter(|path| !path.is_empty())
+        .collect();
+    // Usages can be several lines, one code span each, which get joined with
+    // line endings. Imports are only at the start of the first one.
+    let (usage, imports) = match usage.as_deref() {
+        Some([first, more @ ..]) => {
+            let (first, imports) = parse_imports_short(first)?;
+            let usage = match more {
+                [] => unescape_pipes(first),
+                _ => Cow::Owned(
+                    [first]
+                        .iter()
+                        .chain(more)
+                        .map(|line| unescape_pipes(line))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ),
+            };
+            (Some(usage), imports)
+        }
+        _ => (None, ""),
+    };
+    Ok((
+        input,
+        Row {
+            line,
+            paths,
+            regex: regex.map(unescape_pipes),
+            imports,
+            usage,
+            inputs: example_inputs,
+            expected,
+            description,
+            attributes: HashMap::new(),
+            extra,
+        },
+    ))
+}
+
+/// The texts of the markdown headings in `text`
+pub fn headings(text: &str) -> impl DoubleEndedIterator<Item = &str> {
+    text.lines().filter_map(|line| {
+        let title = line.trim_start_matches('#');
+        (title.len() < line.len() && title.starts_with(' ')).then(|| title.trim())
+    })
+}
+
+/// The text of the last markdown heading in `text`, if there is one
+fn last_heading(text: &str) -> Option<&str> {
+    headings(text).next_back()
+}
+
+/// The outputs that prose can be limited to, by putting it between
+/// `<!-- only = html -->` and `<!-- end only -->` lines
+static ONLY_OUTPUTS: &[&str] = &["markdown", "html", "print"];
+
+/// Checks that the `only` blocks of a template are for known outputs and
+/// are closed again. Taking them out happens when the sheet is written, for
+/// each output. Tables can't be in them, as their rows would still be in the
+/// API and the other outputs.
+fn check_only_blocks(document: &str) -> Result<()> {
+    let mut in_code_block = false;
+    let mut open: Option<usize> = None;
+    for (index, line) in document.lines().enumerate() {
+        let line_number = index + 1;
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block {
+            continue;
+        }
+        if let Some(output) = line
+            .strip_prefix("<!-- only = ")
+            .and_then(|rest| rest.strip_suffix(" -->"))
+        {
+            if !ONLY_OUTPUTS.contains(&output) {
+                return Err(format!(
+                    "Unknown output `{output}` at line {line_number}, expected one of \
+                     {ONLY_OUTPUTS:?}"
+                )
+                .into());
+            }
+            if open.is_some() {
+                return Err(format!("`only` blocks can't be nested, at line {line_number}").into());
+            }
+            open = Some(line_number);
+        } else if line == "<!-- end only -->" {
+            if open.take().is_none() {
+                return Err(format!("`end only` without an `only` at line {line_number}").into());
+            }
+        } else if line.starts_with('|') && open.is_some() {
+            return Err(format!("Tables can't be in `only` blocks, at line {line_number}").into());
+        }
+    }
+    match open {
+        Some(line_number) => Err(format!("Unterminated `only` block at line {line_number}").into()),
+        None => Ok(()),
+    }
+}
+
+fn parse_imports_short(input: &str) -> IResult<&str, &str> {
+    recognize(many0(tuple((
+        tag("use "),
+        take_until(";"),
+        tag(";"),
+        space0,
+    ))))(input)
+}
+
+/// A table, with the prose in front of it
+#[derive(Debug)]
+pub struct Section<'a> {
+    /// Everything since the start of the template or the end of the
+    /// previous table
+    pub text: &'a str,
+    /// The column names and the separator line
+    pub header: &'a str,
+    /// The names of the columns, which are the first one, and then any of
+    /// `KNOWN_COLUMNS` or others
+    pub columns: Vec<&'a str>,
+    /// The last heading in the text. Tables without one are under the same
+    /// heading as the table before them.
+    pub heading: Option<&'a str>,
+    pub rows: Vec<Row<'a>>,
+}
+
+// This parses a single table, along with the text before it
+fn parse_section(input: &str) -> IResult<&str, Section<'_>> {
+    let Some((header_start, rows_start, columns)) = find_table(input) else {
+        return Err(nom::Err::Error(nom::error::Error {
+            input,
+            code: nom::error::ErrorKind::TakeUntil,
+        }));
+    };
+    let header = &input[header_start..rows_start];
+    let (remainder, rows) = many1(|input| parse_row(input, &columns))(&input[rows_start..])?;
+    Ok((
+        remainder,
+        Section {
+            text: &input[..header_start],
+            header,
+            heading: last_heading(&input[..rows_start]),
+            columns,
+            rows,
+        },
+    ))
+}
+
+/// A template, once its code blocks have been taken care of
+#[derive(Debug)]
+pub struct Sheet<'a> {
+    pub sections: Vec<Section<'a>>,
+    /// The text after the last table
+    pub remainder: &'a str,
+}
+
+impl<'a> Sheet<'a> {
+    /// Parses a template, which needs at least one table
+    pub fn parse(document: &'a str) -> Result<Sheet<'a>> {
+        check_only_blocks(document)?;
+        let (remainder, mut sections) =
+            many1(parse_section)(document).map_err(|e| format!("Can't parse the tables: {e}"))?;
+        for row in sections.iter_mut().flat_map(|section| &mut section.rows) {
+            (row.description, row.attributes) = split_attributes(row.description)?;
+        }
+        Ok(Sheet {
+            sections,
+            remainder,
+        })
+    }
+
+    /// Checks that the rows only have the attributes in `known`, which are
+    /// the ones that whatever generates the sheet looks at
+    pub fn check_attributes(&self, known: &[&str]) -> Result<()> {
+        let rows = self.sections.iter().flat_map(|section| &section.rows);
+        for row in rows {
+            if let Some(key) = row.attributes.keys().find(|key| !known.contains(key)) {
+                return Err(format!("Unknown attribute `{key}` in `{}`", row.line).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let template = "# Sheet\n\n## Basic\n\n\
+            | parser | usage | input | output | description |\n\
+            |---|---|---|---|---|\n\
+            | bytes::complete::tag<br>bytes::streaming::tag | `use nom::bytes::complete::tag; tag(\"a\")` | `\"abc\"` |  | One <!-- types, level = beginner --> |\n\
+            | | `tag(\"b\")` | `\"abc\"`<br>Err: `b\"xyz\"` | Error: `Tag`<br>`\\|` | Two |\n\
+            | | `verify(alpha1, \\|s: &str\\| s.len() > 1)` | `\"a\\|b\"` |  | Like `a || b`, or a \\| b |\n\
+            | | `use nom::character::complete::digit1; let digits = digit1;`<br>`preceded(tag(\"#\"), digits)` | `\"#1\"` |  | Three<br>lines<br>long |\n\
+            \nText\n\n\
+            | parser | usage | input | output | description |\n\
+            |---|---|---|---|---|\n\
+            | `\\d+` | `digit1` | `\"1\"` |  |  |\n\
+            \nShort\n\n\
+            | parser | description | usage | notes |\n\
+            | :--- |:---: | --- | --- |\n\
+            | combinator::eof | At the end | `eof` | Also on `&[u8]` |\n\
+            \nThe end\n";
+        let sheet = Sheet::parse(template).unwrap();
+        assert_eq!(sheet.remainder, "\nThe end\n");
+        let [basic, text, short] = sheet.sections.as_slice() else {
+            panic!("Expected three sections: {:?}", sheet.sections);
+        };
+        assert_eq!(basic.text, "# Sheet\n\n## Basic\n\n");
+        assert_eq!(
+            basic.header,
+            "| parser | usage | input | output | description |\n|---|---|---|---|---|\n"
+        );
+        assert_eq!(basic.heading, Some("Basic"));
+        assert_eq!(text.heading, None);
+        assert_eq!(
+            basic.columns,
+            ["parser", "usage", "input", "output", "description"]
+        );
+        assert_eq!(short.text, "\nShort\n\n");
+        assert_eq!(short.columns, ["parser", "description", "usage", "notes"]);
+        assert_eq!(short.rows[0].usage.as_deref(), Some("eof"));
+        assert_eq!(short.rows[0].description, "At the end");
+        assert!(short.rows[0].inputs.is_empty());
+        assert_eq!(short.rows[0].extra["notes"], "Also on `&[u8]`");
+        assert_eq!(
+            short.rows[0].line,
+            "| combinator::eof | At the end | `eof` | Also on `&[u8]` |"
+        );
+        assert!(basic.rows[0].extra.is_empty());
+
+        let [tag, continuation, pipes, lines] = basic.rows.as_slice() else {
+            panic!("Expected four rows: {:?}", basic.rows);
+        };
+        assert_eq!(tag.paths, ["bytes::complete::tag", "bytes::streaming::tag"]);
+        assert_eq!(tag.imports, "use nom::bytes::complete::tag; ");
+        assert_eq!(tag.usage.as_deref(), Some("tag(\"a\")"));
+        assert_eq!(tag.description, "One");
+        assert_eq!(tag.attributes["types"], AttributeValue::Flag);
+        assert_eq!(tag.attributes["level"], AttributeValue::String("beginner"));
+        assert_eq!(tag.expected, None);
+        assert!(continuation.paths.is_empty());
+        assert_eq!(continuation.expected, Some("Error: `Tag`<br>`\\|`"));
+        assert_eq!(continuation.description, "Two");
+        assert_eq!(
+            continuation.inputs,
+            [
+                Input {
+                    code: "\"abc\"".into(),
+                    fails: false
+                },
+                Input {
+                    code: "b\"xyz\"".into(),
+                    fails: true
+                }
+            ]
+        );
+        assert_eq!(
+            pipes.usage.as_deref(),
+            Some("verify(alpha1, |s: &str| s.len() > 1)")
+        );
+        assert_eq!(pipes.inputs[0].code, "\"a|b\"");
+        assert_eq!(pipes.description, "Like `a || b`, or a \\| b");
+        assert_eq!(lines.imports, "use nom::character::complete::digit1; ");
+        assert_eq!(
+            lines.usage.as_deref(),
+            Some("let digits = digit1;\npreceded(tag(\"#\"), digits)")
+        );
+        assert_eq!(lines.description, "Three<br>lines<br>long");
+        assert_eq!(text.rows[0].regex.as_deref(), Some("\\d+"));
+        assert_eq!(text.rows[0].description, "");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let table = "| parser | usage | description |\n|---|---|---|\n";
+        let error = |template: &str| Sheet::parse(template).unwrap_err().to_string();
+        assert_eq!(
+            error(&format!(
+                "Text\n<!-- only = pdf -->\n<!-- end only -->\n{table}"
+            )),
+            "Unknown output `pdf` at line 2, expected one of [\"markdown\", \"html\", \"print\"]"
+        );
+        assert_eq!(
+            error(&format!(
+                "<!-- only = html -->\n{table}| eof | `eof` | End |\n"
+            )),
+            "Tables can't be in `only` blocks, at line 2"
+        );
+        assert_eq!(
+            error(&format!("{table}\n<!-- only = html -->\n")),
+            "Unterminated `only` block at line 4"
+        );
+        assert!(error(&format!(
+            "{table}| eof | `eof` | End <!-- level = [ --> |\n"
+        ))
+        .starts_with("Invalid attributes in `End <!-- level = [ -->`"));
+
+        let sheet =
+            Sheet::parse("| parser | description |\n|---|---|\n| eof | End <!-- colour --> |\n")
+                .unwrap();
+        assert_eq!(sheet.check_attributes(&["colour"]).ok(), Some(()));
+        assert_eq!(
+            sheet.check_attributes(&["level"]).unwrap_err().to_string(),
+            "Unknown attribute `colour` in `| eof | End <!-- colour --> |`"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! The first template can start with front matter for the sheet as a whole,
+//! like its title, either in TOML between `+++` lines or in YAML between
+//! `---` lines. Only plain `key = "value"` or `key: value` lines are read,
+//! which is all that the settings need.
+
+use nom::{
+    branch::alt,
+    bytes::complete::{take_until, take_while1},
+    character::complete::{char, not_line_ending, space0},
+    combinator::{all_consuming, map},
+    sequence::{delimited, separated_pair, tuple},
+    IResult, Parser,
+};
+use std::io::{Error, Result};
+
+/// The settings of a sheet that can be in the front matter of its template
+#[derive(Debug, Default, PartialEq)]
+pub struct FrontMatter<'a> {
+    /// The title of the HTML pages, instead of `Nom Cheatsheet`
+    pub title: Option<&'a str>,
+    /// The version of nom that the prose is written for, like `7.1`
+    pub nom: Option<&'a str>,
+    /// Where the docs of nom's items are, instead of
+    /// `https://docs.rs/nom/latest/nom/`
+    pub docs: Option<&'a str>,
+    /// The color scheme of the HTML, `light` or `dark`, or `auto` for the
+    /// one that the reader's system prefers
+    pub theme: Option<&'a str>,
+    /// Where the template files can be edited, instead of this repository
+    /// on GitHub. The HTML links each row to its line in there.
+    pub edit: Option<&'a str>,
+    /// The columns of the tables and their order, separated by commas, like
+    /// `parser,output,description`, instead of the ones of the template
+    pub columns: Option<&'a str>,
+}
+
+fn parse_value(input: &str) -> IResult<&str, &str> {
+    alt((
+        delimited(char('"'), take_until("\""), char('"')),
+        delimited(char('\''), take_until("'"), char('\'')),
+        map(not_line_ending, str::trim),
+    ))
+    .parse(input)
+}
+
+fn parse_entry(line: &str, separator: char) -> IResult<&str, (&str, &str)> {
+    all_consuming(separated_pair(
+        take_while1(|c: char| c.is_alphanumeric() || c == '_' || c == '-'),
+        tuple((space0, char(separator), space0)),
+        map(parse_value, str::trim),
+    ))
+    .parse(line.trim_end())
+}
+
+/// Splits the front matter off the start of `template`, and returns it with
+/// the rest of the template. Templates without any get the defaults.
+pub fn split_front_matter(template: &str) -> Result<(FrontMatter<'_>, &str)> {
+    let mut lines = template.split_inclusive('\n');
+    let Some(first_line) = lines.next() else {
+        return Ok((FrontMatter::default(), template));
+    };
+    let fence = first_line.trim_end();
+    let separator = match fence {
+        "+++" => '=',
+        "---" => ':',
+        _ => return Ok((FrontMatter::default(), template)),
+    };
+    let mut front_matter = FrontMatter::default();
+    let mut end = first_line.len();
+    for line in lines {
+        end += line.len();
+        if line.trim_end() == fence {
+            return Ok((front_matter, &template[end..]));
+        }
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let (_, (key, value)) = parse_entry(line, separator).map_err(|_| {
+            Error::other(format!("Can't read `{}` in the front matter", line.trim()))
+        })?;
+        let field = match key {
+            "title" => &mut front_matter.title,
+            "nom" => &mut front_matter.nom,
+            "docs" => &mut front_matter.docs,
+            "theme" => &mut front_matter.theme,
+            "edit" => &mut front_matter.edit,
+            "columns" => &mut front_matter.columns,
+            _ => {
+                return Err(Error::other(format!(
+                    "Unknown key `{key}` in the front matter, expected one of \
+                     `title`, `nom`, `docs`, `theme`, `edit`, or `columns`"
+                )))
+            }
+        };
+        *field = Some(value);
+    }
+    Err(Error::other(format!(
+        "The front matter isn't closed with a `{fence}` line"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_front_matter() {
+        let template = "+++\ntitle = \"Parsing\"\n# For the footer\nnom = '7.1'\n\n\
+                        columns = \"parser, output\"\n+++\n# Sheet\n";
+        let (front_matter, rest) = split_front_matter(template).unwrap();
+        assert_eq!(
+            front_matter,
+            FrontMatter {
+                title: Some("Parsing"),
+                nom: Some("7.1"),
+                columns: Some("parser, output"),
+                ..FrontMatter::default()
+            }
+        );
+        assert_eq!(rest, "# Sheet\n");
+
+        let template =
+            "---\r\ntheme: dark\r\ndocs: https://docs.rs/nom/7.1.3/nom/\r\nedit: https://example.com/\r\n---\r\n# Sheet";
+        let (front_matter, rest) = split_front_matter(template).unwrap();
+        assert_eq!(front_matter.theme, Some("dark"));
+        assert_eq!(front_matter.docs, Some("https://docs.rs/nom/7.1.3/nom/"));
+        assert_eq!(front_matter.edit, Some("https://example.com/"));
+        assert_eq!(rest, "# Sheet");
+
+        let (front_matter, rest) = split_front_matter("# Sheet\n---\n").unwrap();
+        assert_eq!(front_matter, FrontMatter::default());
+        assert_eq!(rest, "# Sheet\n---\n");
+
+        for (template, error) in [
+            ("+++\ncolor = \"red\"\n+++\n", "Unknown key `color`"),
+            ("+++\ntitle: Parsing\n+++\n", "Can't read `title: Parsing`"),
+            ("---\ntitle: Parsing\n", "isn't closed with a `---` line"),
+        ] {
+            let message = split_front_matter(template).unwrap_err().to_string();
+            assert!(message.contains(error), "{message}");
+        }
+    }
+}
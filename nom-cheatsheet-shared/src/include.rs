@@ -0,0 +1,144 @@
+//! Templates can be split into several files, like one per module, with
+//! `<!-- include: bytes.md -->` lines where the other files go. The path is
+//! relative to the file that the line is in, and included files can include
+//! others in turn.
+
+use std::{
+    fs,
+    io::{Error, Result},
+    path::{Path, PathBuf},
+};
+
+/// A template with its includes put in
+#[derive(Debug)]
+pub struct Expanded {
+    pub text: String,
+    /// The files that went into the text, starting with the template
+    pub files: Vec<PathBuf>,
+    /// Where each line of the text comes from, by the index of its file in
+    /// `files` and its line number in there
+    lines: Vec<(usize, usize)>,
+}
+
+impl Expanded {
+    /// The file and the line number in there of a line of the text, by its
+    /// line number
+    #[must_use]
+    pub fn location(&self, line: usize) -> (&Path, usize) {
+        match self.lines.get(line.wrapping_sub(1)) {
+            Some(&(file, line)) => (&self.files[file], line),
+            None => (&self.files[0], line),
+        }
+    }
+}
+
+/// The path of the file that `line` includes, if it's an include line
+fn include_path(line: &str) -> Option<&str> {
+    let path = line
+        .trim()
+        .strip_prefix("<!--")?
+        .strip_suffix("-->")?
+        .trim()
+        .strip_prefix("include:")?
+        .trim();
+    (!path.is_empty()).then_some(path)
+}
+
+/// Puts the file at `path` into `expanded`, with its includes. `including`
+/// are the files that include it, to catch files that include themselves.
+fn expand(path: &Path, including: &mut Vec<PathBuf>, expanded: &mut Expanded) -> Result<()> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| Error::new(e.kind(), format!("Can't read {}: {e}", path.display())))?;
+    let canonical = fs::canonicalize(path)?;
+    if including.contains(&canonical) {
+        return Err(Error::other(format!("{} includes itself", path.display())));
+    }
+    including.push(canonical);
+    let file = expanded.files.len();
+    expanded.files.push(path.to_path_buf());
+
+    let mut in_code_block = false;
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        match include_path(line).filter(|_| !in_code_block) {
+            Some(included) => {
+                let included = path.parent().unwrap_or(Path::new("")).join(included);
+                expand(&included, including, expanded)?;
+            }
+            None => {
+                expanded.text.push_str(line);
+                // What comes after an included file starts on a line of its own
+                if !line.ends_with('\n') && including.len() > 1 {
+                    expanded.text.push('\n');
+                }
+                expanded.lines.push((file, index + 1));
+            }
+        }
+    }
+    including.pop();
+    Ok(())
+}
+
+/// Reads the template at `path`, with the files that it includes in place
+/// of their include lines
+pub fn expand_includes(path: &Path) -> Result<Expanded> {
+    let mut expanded = Expanded {
+        text: String::new(),
+        files: Vec::new(),
+        lines: Vec::new(),
+    };
+    expand(path, &mut Vec::new(), &mut expanded)?;
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_expand_includes() {
+        let dir = env::temp_dir().join(format!("nom-cheatsheet-include-{}", std::process::id()));
+        fs::create_dir_all(dir.join("modules")).unwrap();
+        fs::write(
+            dir.join("template.md"),
+            "# Sheet\n\n<!-- include: modules/bytes.md -->\n```\n<!-- include: x.md -->\n```\nEnd",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("modules/bytes.md"),
+            "## Bytes\n<!-- include: more.md -->",
+        )
+        .unwrap();
+        fs::write(dir.join("modules/more.md"), "More").unwrap();
+        let expanded = expand_includes(&dir.join("template.md")).unwrap();
+        assert_eq!(
+            expanded.text,
+            "# Sheet\n\n## Bytes\nMore\n```\n<!-- include: x.md -->\n```\nEnd"
+        );
+        assert_eq!(
+            expanded.files,
+            [
+                dir.join("template.md"),
+                dir.join("modules/bytes.md"),
+                dir.join("modules/more.md")
+            ]
+        );
+        assert_eq!(
+            expanded.location(3),
+            (dir.join("modules/bytes.md").as_path(), 1)
+        );
+        assert_eq!(
+            expanded.location(4),
+            (dir.join("modules/more.md").as_path(), 1)
+        );
+        assert_eq!(expanded.location(5), (dir.join("template.md").as_path(), 4));
+
+        fs::write(dir.join("modules/more.md"), "<!-- include: bytes.md -->\n").unwrap();
+        let error = expand_includes(&dir.join("template.md")).unwrap_err();
+        assert!(error.to_string().ends_with("bytes.md includes itself"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
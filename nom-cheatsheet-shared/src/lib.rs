@@ -1,3 +1,15 @@
+use nom::{character::complete::digit1, combinator::map, AsBytes, IResult, Parser};
+use nom_locate::LocatedSpan;
+use std::cell::RefCell;
+
+mod front_matter;
+mod include;
+mod output;
+
+pub use front_matter::{split_front_matter, FrontMatter};
+pub use include::{expand_includes, Expanded};
+pub use output::{format_iresult, FormatOutput, Length, Location, Numbers, Whitespace};
+
 #[must_use]
 pub fn markdown_format_code(input: &str) -> String {
     // Find longest sequence of backticks
@@ -31,6 +43,282 @@ pub fn markdown_format_code(input: &str) -> String {
     format!("{backticks}{spacing}{input}{spacing}{backticks}")
 }
 
+/// Like [`markdown_format_code`], for a table cell. Pipes would end the cell
+/// even in a code span, so they're escaped, and the backslashes don't show.
+#[must_use]
+pub fn markdown_cell_code(input: &str) -> String {
+    markdown_format_code(input).replace('|', "\\|")
+}
+
+/// Like [`markdown_cell_code`], with a code span for each line and `<br>` in
+/// between, as table cells can't have line endings
+#[must_use]
+pub fn markdown_cell_code_lines(input: &str) -> String {
+    input
+        .split('\n')
+        .map(markdown_cell_code)
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+/// Makes the whitespace in the string and character literals of `code`
+/// visible, which is otherwise hard to tell apart in inputs like `"\r\n  "`.
+/// Line endings and tabs become arrows, and spaces at the end of a line dots.
+/// The result is for reading only, it's not valid Rust anymore.
+#[must_use]
+pub fn visible_whitespace(code: &str) -> String {
+    let mut visible = String::new();
+    let mut chars = code.chars();
+    while let Some(quote) = chars.next() {
+        visible.push(quote);
+        if quote != '"' && quote != '\'' {
+            continue;
+        }
+        // Characters and escapes, like `a` and `\n`
+        let mut tokens = Vec::new();
+        let mut closed = false;
+        while let Some(c) = chars.next() {
+            if c == quote {
+                closed = true;
+                break;
+            }
+            let mut token = c.to_string();
+            if c == '\\' {
+                token.extend(chars.next());
+            }
+            tokens.push(token);
+        }
+        // Going backwards, to know which spaces are at the end of a line
+        let mut trailing = true;
+        let mut rendered = Vec::new();
+        for token in tokens.iter().rev() {
+            rendered.push(match token.as_str() {
+                " " if trailing => "·",
+                "\\n" => "↵",
+                "\\r" => "␍",
+                "\\t" => "→",
+                token => token,
+            });
+            trailing = matches!(token.as_str(), "\\n" | "\\r")
+                || (trailing && matches!(token.as_str(), " " | "\\t"));
+        }
+        visible.extend(rendered.into_iter().rev());
+        if closed {
+            visible.push(quote);
+        }
+    }
+    visible
+}
+
+/// Where a subslice of a parser's input starts, which is how errors point at
+/// what went wrong
+pub trait SubsliceOffset {
+    /**
+    Returns the index of the first character of the subslice in the original slice.
+
+    # Example
+    ```
+    use nom_cheatsheet_shared::SubsliceOffset;
+
+    let string = "a\nb\nc";
+    let lines: Vec<&str> = string.lines().collect();
+    assert_eq!(string.subslice_offset_bytes(lines[0]), Some(0));
+    assert_eq!(string.subslice_offset_bytes(lines[1]), Some(2));
+    assert_eq!(string.subslice_offset_bytes(lines[2]), Some(4));
+    assert_eq!(string.subslice_offset_bytes("other"), None);
+    assert_eq!(string.subslice_offset_bytes("a"), None);
+    ```
+    */
+    fn subslice_offset_bytes(&self, subslice: &Self) -> Option<usize>;
+
+    /// Where `subslice` starts, for the output cell
+    fn describe_offset(&self, subslice: &Self) -> String {
+        format!(
+            "Byte offset: {}",
+            self.subslice_offset_bytes(subslice).unwrap()
+        )
+    }
+}
+
+impl SubsliceOffset for str {
+    fn subslice_offset_bytes(&self, subslice: &str) -> Option<usize> {
+        let self_ptr = self.as_ptr() as usize;
+        let self_end = self_ptr.checked_add(self.len())?;
+        let subslice_ptr = subslice.as_ptr() as usize;
+        let subslice_end = subslice_ptr.checked_add(subslice.len())?;
+        if subslice_ptr < self_ptr || subslice_end > self_end {
+            return None;
+        }
+        if subslice_ptr < self_ptr || subslice_ptr > self_ptr.checked_add(self.len())? {
+            return None;
+        }
+        // This is safe because we've already checked that subslice_ptr is never
+        // smaller than self_ptr.
+        Some(subslice_ptr - self_ptr)
+    }
+}
+
+impl SubsliceOffset for &str {
+    fn subslice_offset_bytes(&self, subslice: &Self) -> Option<usize> {
+        (*self).subslice_offset_bytes(*subslice)
+    }
+}
+
+impl SubsliceOffset for [u8] {
+    fn subslice_offset_bytes(&self, subslice: &Self) -> Option<usize> {
+        let self_ptr = self.as_ptr() as usize;
+        let self_end = self_ptr.checked_add(self.len())?;
+        let subslice_ptr = subslice.as_ptr() as usize;
+        let subslice_end = subslice_ptr.checked_add(subslice.len())?;
+        if subslice_ptr < self_ptr || subslice_end > self_end {
+            return None;
+        }
+        // This is safe because we've already checked that subslice_ptr is never
+        // smaller than self_ptr.
+        Some(subslice_ptr - self_ptr)
+    }
+}
+
+impl SubsliceOffset for &[u8] {
+    fn subslice_offset_bytes(&self, subslice: &Self) -> Option<usize> {
+        (*self).subslice_offset_bytes(*subslice)
+    }
+}
+
+/// The input of bit parsers, the bytes and the bit in the first one to go on
+/// from
+impl SubsliceOffset for (&[u8], usize) {
+    fn subslice_offset_bytes(&self, subslice: &Self) -> Option<usize> {
+        self.0.subslice_offset_bytes(subslice.0)
+    }
+
+    fn describe_offset(&self, subslice: &Self) -> String {
+        let bytes = self.subslice_offset_bytes(subslice).unwrap();
+        format!("Bit offset: {}", bytes * 8 + subslice.1 - self.1)
+    }
+}
+
+/// nom_locate's spans already know where they are, so errors can say which
+/// line and column they're at
+impl<T: AsBytes, X> SubsliceOffset for LocatedSpan<T, X> {
+    fn subslice_offset_bytes(&self, subslice: &Self) -> Option<usize> {
+        subslice
+            .location_offset()
+            .checked_sub(self.location_offset())
+    }
+
+    fn describe_offset(&self, subslice: &Self) -> String {
+        format!(
+            "Line: {}, column: {}",
+            subslice.location_line(),
+            subslice.get_utf8_column()
+        )
+    }
+}
+
+/// How the error of a failed parser shows up in an output cell, after the
+/// `Error` or `Failure` line. With an implementation of this, the error types
+/// that fixtures define can be used in rows like nom's own.
+pub trait FormatParseError<I> {
+    fn format_parse_error(&self, input: &I) -> String;
+}
+
+impl<I: SubsliceOffset> FormatParseError<I> for nom::error::Error<I> {
+    fn format_parse_error(&self, input: &I) -> String {
+        let offset = input.describe_offset(&self.input);
+        format!("{offset}<br>Code: {:?}", self.code)
+    }
+}
+
+/// Each error on the way up gets a line, from where the parser failed to the
+/// outermost `context`
+#[cfg(all(feature = "alloc", not(feature = "nom8")))]
+impl<I: SubsliceOffset> FormatParseError<I> for nom::error::VerboseError<I> {
+    fn format_parse_error(&self, input: &I) -> String {
+        use nom::error::VerboseErrorKind;
+        let lines = self
+            .errors
+            .iter()
+            .map(|(location, kind)| {
+                let offset = input.subslice_offset_bytes(location).unwrap();
+                match kind {
+                    VerboseErrorKind::Context(context) => {
+                        format!("In {context} at byte offset {offset}")
+                    }
+                    VerboseErrorKind::Char(c) => format!("Expected {c:?} at byte offset {offset}"),
+                    VerboseErrorKind::Nom(kind) => format!("{kind:?} at byte offset {offset}"),
+                }
+            })
+            .collect::<Vec<_>>();
+        lines.join("<br>")
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "nom8")))]
+impl<I: SubsliceOffset> FormatParseError<I> for nom_supreme::error::ErrorTree<I> {
+    fn format_parse_error(&self, input: &I) -> String {
+        use nom_supreme::error::GenericErrorTree;
+        let offset = |location: &I| input.subslice_offset_bytes(location).unwrap();
+        match self {
+            GenericErrorTree::Base { location, kind } => {
+                format!("{kind} at byte offset {}", offset(location))
+            }
+            GenericErrorTree::Stack { base, contexts } => {
+                let mut lines = vec![base.format_parse_error(input)];
+                for (location, context) in contexts {
+                    lines.push(format!("{context} at byte offset {}", offset(location)));
+                }
+                lines.join("<br>")
+            }
+            GenericErrorTree::Alt(alternatives) => {
+                let mut lines = vec!["One of:".to_string()];
+                for alternative in alternatives {
+                    lines.push(format!("- {}", alternative.format_parse_error(input)));
+                }
+                lines.join("<br>")
+            }
+        }
+    }
+}
+
+// The helpers below are used by the examples in the cheatsheet, both when
+// generating it and in the WebAssembly module.
+
+pub fn number(input: &str) -> IResult<&str, usize> {
+    map(digit1, |s: &str| s.parse().unwrap()).parse(input)
+}
+
+// Just to make the example compile
+pub fn my_alpha1(input: &str) -> IResult<&str, &str> {
+    nom::character::complete::alpha1(input)
+}
+
+/// Formats a value that was bound halfway through a multi-statement example
+#[must_use]
+pub fn format_step<T: std::fmt::Debug>(name: &str, value: &T) -> String {
+    format!("{name}: {}", markdown_format_code(&format!("{value:?}")))
+}
+
+/// Wraps a parser inside of a combinator chain, so that its output gets
+/// recorded as a step every time it succeeds. This is what makes the
+/// intermediate values of `map_parser`, `flat_map` and `and_then` visible.
+/// The parser is a closure, as nom 8's combinators aren't functions anymore.
+pub fn trace<'a, I: 'a, O: std::fmt::Debug + 'a, E: 'a>(
+    label: &'a str,
+    steps: &'a RefCell<Vec<String>>,
+    mut parser: impl FnMut(I) -> IResult<I, O, E> + 'a,
+) -> impl FnMut(I) -> IResult<I, O, E> + 'a {
+    move |input| {
+        let result = parser(input);
+        if let Ok((_, output)) = &result {
+            steps
+                .borrow_mut()
+                .push(format_step(&markdown_format_code(label), output));
+        }
+        result
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,4 +334,43 @@ mod tests {
         assert_eq!(markdown_format_code("`"), "`` ` ``");
         assert_eq!(markdown_format_code("``"), "``` `` ```");
     }
+
+    #[test]
+    fn test_markdown_cell_code() {
+        assert_eq!(markdown_cell_code("a || b"), "`a \\|\\| b`");
+        assert_eq!(markdown_cell_code("\\|"), "`\\\\|`");
+        assert_eq!(
+            markdown_cell_code_lines("let a = tag(\"a\");\na"),
+            "`let a = tag(\"a\");`<br>`a`"
+        );
+    }
+
+    #[test]
+    fn test_subslice_offset() {
+        let string = "a\nb\nc";
+        let lines: Vec<&str> = string.lines().collect();
+        assert_eq!(string.subslice_offset_bytes(lines[0]), Some(0));
+        assert_eq!(string.subslice_offset_bytes(lines[1]), Some(2));
+        assert_eq!(string.subslice_offset_bytes(lines[2]), Some(4));
+        assert_eq!(string.subslice_offset_bytes("other"), None);
+        assert_eq!(string.subslice_offset_bytes("a"), None);
+
+        let string = "foobar";
+        let str1 = &string[0..3];
+        let str2 = &string[3..];
+        let str3 = &string[3..3];
+        let str4 = &string[2..3];
+        assert_eq!(str1.subslice_offset_bytes(str2), None);
+        assert_eq!(str1.subslice_offset_bytes(str3), Some(3));
+        assert_eq!(str1.subslice_offset_bytes(str4), Some(2));
+    }
+
+    #[test]
+    fn test_visible_whitespace() {
+        assert_eq!(visible_whitespace(r#""\r\nhello""#), r#""␍↵hello""#);
+        assert_eq!(visible_whitespace(r#"" \thi  \n  ""#), r#"" →hi··↵··""#);
+        assert_eq!(visible_whitespace(r#"b"a \"b\\n ""#), r#"b"a \"b\\n·""#);
+        assert_eq!(visible_whitespace("[' ', 'a']"), "['·', 'a']");
+        assert_eq!(visible_whitespace("&[0x20]"), "&[0x20]");
+    }
 }
@@ -0,0 +1,336 @@
+//! How the outcome of running a row's parser gets written in its output
+//! cell. This is shared with the WebAssembly module, so that the output that
+//! the HTML shows for an edited input looks like the one in the sheet.
+
+use crate::{markdown_format_code, visible_whitespace, FormatParseError, SubsliceOffset};
+use nom::IResult;
+use nom_locate::LocatedSpan;
+
+pub trait Length {
+    fn length(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.length() == 0
+    }
+}
+
+impl Length for str {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl Length for &str {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl Length for [u8] {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl Length for &[u8] {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+/// How many bits are left
+impl Length for (&[u8], usize) {
+    fn length(&self) -> usize {
+        self.0.len() * 8 - self.1
+    }
+}
+
+/// What's left of a span
+impl<T: nom::AsBytes, X> Length for LocatedSpan<T, X> {
+    fn length(&self) -> usize {
+        self.fragment().as_bytes().len()
+    }
+}
+
+/// Where a remainder is in the whole input, for the input types that keep
+/// track of that
+pub trait Location {
+    /// The `Debug` output of the part of the input that's left, and the line
+    /// and column it starts at
+    fn location(&self) -> Option<(String, u32, usize)> {
+        None
+    }
+}
+
+impl Location for &str {}
+
+impl Location for &[u8] {}
+
+impl Location for (&[u8], usize) {}
+
+impl<T: std::fmt::Debug + nom::AsBytes, X> Location for LocatedSpan<T, X> {
+    fn location(&self) -> Option<(String, u32, usize)> {
+        Some((
+            format!("{:?}", self.fragment()),
+            self.location_line(),
+            self.get_utf8_column(),
+        ))
+    }
+}
+
+/// How the numbers in output cells are written. By default values are in
+/// decimal, and bytes in remainders in hex. The others apply to both.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Numbers {
+    Default,
+    Decimal,
+    Hex,
+    Binary,
+}
+
+/// Whether the whitespace in remainders is shown with symbols, like `↵` for
+/// `\n`, which makes the ones of `line_ending` and friends readable
+#[derive(Clone, Copy, PartialEq)]
+pub enum Whitespace {
+    Plain,
+    Visible,
+}
+
+/// Writes the integers in `debug` in the base that `numbers` asks for.
+/// Numbers inside string and character literals stay as they are, and so do
+/// floats and the digits in names like `u8`.
+fn rewrite_numbers(debug: &str, numbers: Numbers) -> String {
+    if matches!(numbers, Numbers::Default | Numbers::Decimal) {
+        return debug.to_string();
+    }
+    let mut rewritten = String::new();
+    let mut chars = debug.char_indices().peekable();
+    let mut previous = ' ';
+    while let Some((start, c)) = chars.next() {
+        if c == '"' || c == '\'' {
+            rewritten.push(c);
+            while let Some((_, inner)) = chars.next() {
+                rewritten.push(inner);
+                if inner == '\\' {
+                    rewritten.extend(chars.next().map(|(_, escaped)| escaped));
+                } else if inner == c {
+                    break;
+                }
+            }
+        } else if c.is_ascii_digit() && !(previous.is_alphanumeric() || "_.".contains(previous)) {
+            let mut end = start + 1;
+            while let Some((index, _)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                end = index + 1;
+            }
+            let digits = &debug[start..end];
+            let float = chars.peek().is_some_and(|(_, c)| ".eE".contains(*c));
+            match digits.parse::<u128>() {
+                Ok(number) if !float && numbers == Numbers::Hex => {
+                    rewritten.push_str(&format!("{number:#04x}"));
+                }
+                Ok(number) if !float => rewritten.push_str(&format!("{number:#010b}")),
+                _ => rewritten.push_str(digits),
+            }
+        } else {
+            rewritten.push(c);
+        }
+        previous = debug[..chars.peek().map_or(debug.len(), |(index, _)| *index)]
+            .chars()
+            .next_back()
+            .unwrap_or(' ');
+    }
+    rewritten
+}
+
+fn format_remainder<I>(remainder: &I, numbers: Numbers, whitespace: Whitespace) -> String
+where
+    I: std::fmt::Debug + Location,
+{
+    let numbers = match numbers {
+        Numbers::Default => Numbers::Hex,
+        numbers => numbers,
+    };
+    // Spans show the fragment that's left, with where it is
+    let (remainder, location) = match remainder.location() {
+        Some((fragment, line, column)) => (fragment, format!(" at line {line}, column {column}")),
+        None => (format!("{remainder:?}"), String::new()),
+    };
+    // Bit remainders are a tuple of the bytes that are left and the bit to go
+    // on from in the first one
+    if let Some((bytes, bit)) = remainder
+        .strip_prefix("([")
+        .and_then(|remainder| remainder.strip_suffix(')'))
+        .and_then(|remainder| remainder.rsplit_once("], "))
+    {
+        let bytes = rewrite_numbers(&format!("&[{bytes}]"), numbers);
+        return format!("{} from bit {bit}", markdown_format_code(&bytes));
+    }
+    let mut remainder = rewrite_numbers(&remainder, numbers);
+    if whitespace == Whitespace::Visible {
+        remainder = visible_whitespace(&remainder);
+    }
+    // Byte remainders are slices
+    let remainder = match remainder.starts_with('[') {
+        true => markdown_format_code(&format!("&{remainder}")),
+        false => markdown_format_code(&remainder),
+    };
+    format!("{remainder}{location}")
+}
+
+/// Formats a value from a parser's output
+fn format_value(value: &impl std::fmt::Debug, numbers: Numbers) -> String {
+    markdown_format_code(&rewrite_numbers(&format!("{value:?}"), numbers))
+}
+
+/// How the outcome of running a row's parser shows up in its output cell
+pub trait FormatOutput<I> {
+    fn format_output(&self, input: &I, numbers: Numbers, whitespace: Whitespace) -> String;
+}
+
+impl<I, O, E> FormatOutput<I> for IResult<I, O, E>
+where
+    I: std::fmt::Debug + SubsliceOffset + Length + Location,
+    O: std::fmt::Debug,
+    E: FormatParseError<I>,
+{
+    fn format_output(&self, input: &I, numbers: Numbers, whitespace: Whitespace) -> String {
+        match self {
+            Ok((remainder, value)) => {
+                let value = format_value(value, numbers);
+                if remainder.is_empty() {
+                    format!("Result: {value}<br>No remainder")
+                } else {
+                    let remainder = format_remainder(remainder, numbers, whitespace);
+                    format!("Result: {value}<br>Remainder: {remainder}")
+                }
+            }
+            Err(nom::Err::Incomplete(needed)) => match needed {
+                nom::Needed::Size(size) => format!("Incomplete<br>Needed: {size} items"),
+                nom::Needed::Unknown => "Incomplete<br>Needed: unknown".to_string(),
+            },
+            Err(nom::Err::Error(error)) => format!("Error<br>{}", error.format_parse_error(input)),
+            Err(nom::Err::Failure(error)) => {
+                format!("Failure<br>{}", error.format_parse_error(input))
+            }
+        }
+    }
+}
+
+/// What nom-supreme's `final_parser` returns. It has already turned the
+/// error locations into lines and columns, and there's never a remainder.
+#[cfg(all(feature = "std", not(feature = "nom8")))]
+impl<I, O: std::fmt::Debug> FormatOutput<I>
+    for std::result::Result<O, nom_supreme::error::ErrorTree<nom_supreme::final_parser::Location>>
+{
+    fn format_output(&self, _input: &I, numbers: Numbers, _whitespace: Whitespace) -> String {
+        match self {
+            Ok(value) => format!("Result: {}", format_value(value, numbers)),
+            Err(error) => format!("Error<br>{}", error.to_string().replace('\n', "<br>")),
+        }
+    }
+}
+
+/// The output cell for running a row's parser on `input`
+#[must_use]
+pub fn format_iresult<I>(
+    input: &I,
+    result: &impl FormatOutput<I>,
+    numbers: Numbers,
+    whitespace: Whitespace,
+) -> String {
+    result.format_output(input, numbers, whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_remainder() {
+        let input = "a 1";
+        assert_eq!(
+            format_remainder(&input, Numbers::Default, Whitespace::Plain),
+            "`\"a 1\"`"
+        );
+        let input = &[0_u8, 1, 2, 255][..];
+        assert_eq!(
+            format_remainder(&input, Numbers::Default, Whitespace::Plain),
+            "`&[0x00, 0x01, 0x02, 0xff]`"
+        );
+        assert_eq!(
+            format_remainder(&input, Numbers::Decimal, Whitespace::Plain),
+            "`&[0, 1, 2, 255]`"
+        );
+        assert_eq!(
+            format_remainder(&input, Numbers::Binary, Whitespace::Plain),
+            "`&[0b00000000, 0b00000001, 0b00000010, 0b11111111]`"
+        );
+        let input = "\tb \n";
+        assert_eq!(
+            format_remainder(&input, Numbers::Default, Whitespace::Visible),
+            "`\"→b·↵\"`"
+        );
+        let input = (&[0b1010_0000_u8, 0x0f][..], 4);
+        assert_eq!(
+            format_remainder(&input, Numbers::Default, Whitespace::Plain),
+            "`&[0xa0, 0x0f]` from bit 4"
+        );
+        assert_eq!(input.length(), 12);
+        assert_eq!(input.describe_offset(&(&input.0[1..], 2)), "Bit offset: 6");
+        let input = LocatedSpan::new("a = 1\nb = 2");
+        let (remainder, _) =
+            nom::bytes::complete::take::<_, _, nom::error::Error<_>>(8_usize)(input).unwrap();
+        assert_eq!(
+            format_remainder(&remainder, Numbers::Default, Whitespace::Plain),
+            "`\"= 2\"` at line 2, column 3"
+        );
+        assert_eq!(remainder.length(), 3);
+        assert_eq!(input.describe_offset(&remainder), "Line: 2, column: 3");
+    }
+
+    #[test]
+    fn test_rewrite_numbers() {
+        let debug = r#"[(42, "7\"8", 'x', 1.5, u8)]"#;
+        assert_eq!(rewrite_numbers(debug, Numbers::Default), debug);
+        assert_eq!(
+            rewrite_numbers(debug, Numbers::Hex),
+            r#"[(0x2a, "7\"8", 'x', 1.5, u8)]"#
+        );
+        assert_eq!(rewrite_numbers("-3", Numbers::Binary), "-0b00000011");
+        assert_eq!(rewrite_numbers("1e10", Numbers::Hex), "1e10");
+    }
+
+    #[cfg(all(feature = "std", not(feature = "nom8")))]
+    #[test]
+    fn test_format_error_tree() {
+        use nom::{branch::alt, character::complete::digit1};
+        use nom_supreme::{error::ErrorTree, tag::complete::tag, ParserExt};
+
+        let input = "ab12";
+        let result: IResult<&str, &str, ErrorTree<&str>> =
+            alt((tag("abc"), digit1.context("number")))(input);
+        assert_eq!(
+            format_iresult(&input, &result, Numbers::Default, Whitespace::Plain),
+            "Error<br>One of:<br>- expected \"abc\" at byte offset 0<br>\
+             - expected an ascii digit at byte offset 0<br>in section \"number\" at byte offset 0"
+        );
+    }
+
+    #[cfg(all(feature = "alloc", not(feature = "nom8")))]
+    #[test]
+    fn test_format_verbose_error() {
+        use nom::{
+            character::complete::{alpha1, char},
+            error::{context, VerboseError},
+            sequence::preceded,
+        };
+
+        let input = "x=1";
+        let result: IResult<&str, &str, VerboseError<&str>> =
+            context("assignment", preceded(char('x'), context("value", alpha1)))(input);
+        assert_eq!(
+            format_iresult(&input, &result, Numbers::Default, Whitespace::Plain),
+            "Error<br>Alpha at byte offset 1<br>In value at byte offset 1<br>\
+             In assignment at byte offset 0"
+        );
+    }
+}
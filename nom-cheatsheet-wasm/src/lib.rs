@@ -0,0 +1,148 @@
+//! All the example parsers of the cheatsheet in one WebAssembly module.
+//!
+//! The exports are deliberately low level, so that no bindings generator is
+//! needed. `nom-cheatsheet.js` wraps them in a friendlier API.
+
+use nom::IResult;
+use nom_cheatsheet_shared::{format_iresult, FormatOutput, Numbers, Whitespace};
+use serde_json::{json, Value};
+use std::{cell::RefCell, fmt::Debug, str};
+
+// The path gets passed in by `cargo run`, because the rows are generated by
+// the main crate's build script.
+mod rows {
+    include!(env!("NOM_CHEATSHEET_WASM_ROWS"));
+}
+
+/// Inputs are always passed in as bytes, but rows with text inputs need a
+/// `&str`. This turns the bytes into the same type as the row's own input.
+trait Decode {
+    fn decode(bytes: &[u8]) -> Option<&Self>;
+}
+
+impl Decode for str {
+    fn decode(bytes: &[u8]) -> Option<&Self> {
+        str::from_utf8(bytes).ok()
+    }
+}
+
+impl Decode for [u8] {
+    fn decode(bytes: &[u8]) -> Option<&Self> {
+        Some(bytes)
+    }
+}
+
+fn decode_like<'a, T: Decode + ?Sized>(bytes: &'a [u8], _example: &&T) -> Option<&'a T> {
+    T::decode(bytes)
+}
+
+fn invalid_input() -> Value {
+    json!({ "error": "This example needs text input, but the input isn't valid UTF-8" })
+}
+
+/// The outcome of running a row's parser, as JSON
+trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+impl<I: Debug, O: Debug, E: Debug> ToJson for IResult<I, O, E> {
+    fn to_json(&self) -> Value {
+        match self {
+            Ok((remainder, result)) => json!({
+                "result": format!("{result:?}"),
+                "remainder": format!("{remainder:?}"),
+            }),
+            Err(error) => json!({ "error": format!("{error:?}") }),
+        }
+    }
+}
+
+/// What nom-supreme's `final_parser` returns, which never has a remainder
+#[cfg(all(feature = "std", not(feature = "nom8")))]
+impl<O: Debug> ToJson
+    for Result<O, nom_supreme::error::ErrorTree<nom_supreme::final_parser::Location>>
+{
+    fn to_json(&self) -> Value {
+        match self {
+            Ok(result) => json!({ "result": format!("{result:?}") }),
+            Err(error) => json!({ "error": error.to_string() }),
+        }
+    }
+}
+
+/// The JSON of the outcome, with `output` being the output cell that the
+/// sheet would have for it
+fn run_result<I, T: ToJson + FormatOutput<I>>(
+    input: &I,
+    output: &T,
+    numbers: Numbers,
+    whitespace: Whitespace,
+    steps: Option<Vec<String>>,
+) -> Value {
+    let mut value = output.to_json();
+    value["output"] = format_iresult(input, output, numbers, whitespace).into();
+    if let Some(steps) = steps {
+        value["steps"] = steps.into();
+    }
+    value
+}
+
+thread_local! {
+    /// The JSON of the last call, which stays around until the next one so
+    /// that JavaScript can read it out of memory.
+    static OUTPUT: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+fn set_output(value: &Value) -> *const u8 {
+    OUTPUT.with_borrow_mut(|output| {
+        *output = value.to_string();
+        output.as_ptr()
+    })
+}
+
+/// Reserves `len` bytes for JavaScript to write an input into
+#[no_mangle]
+pub extern "C" fn alloc(len: usize) -> *mut u8 {
+    let mut buffer = Vec::<u8>::with_capacity(len);
+    let pointer = buffer.as_mut_ptr();
+    std::mem::forget(buffer);
+    pointer
+}
+
+/// Frees memory that was reserved with [`alloc`]
+///
+/// # Safety
+///
+/// `pointer` and `len` need to come from a single earlier call to [`alloc`].
+#[no_mangle]
+pub unsafe extern "C" fn dealloc(pointer: *mut u8, len: usize) {
+    drop(Vec::from_raw_parts(pointer, 0, len));
+}
+
+/// Runs the parser of the row with id `id` on the input, and returns a
+/// pointer to the JSON of the outcome. Its length is in [`output_len`].
+///
+/// # Safety
+///
+/// `pointer` needs to point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn run(id: usize, pointer: *const u8, len: usize) -> *const u8 {
+    let input = std::slice::from_raw_parts(pointer, len);
+    let value = rows::run_row(id, input)
+        .unwrap_or_else(|| json!({ "error": format!("No row with id {id}") }));
+    set_output(&value)
+}
+
+/// Returns a pointer to the JSON array of row labels, indexed by id. Its
+/// length is in [`output_len`].
+#[no_mangle]
+pub extern "C" fn labels() -> *const u8 {
+    set_output(&json!(rows::ROW_LABELS))
+}
+
+/// The length of the JSON that the last call to [`run`] or [`labels`]
+/// returned
+#[no_mangle]
+pub extern "C" fn output_len() -> usize {
+    OUTPUT.with_borrow(String::len)
+}
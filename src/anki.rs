@@ -0,0 +1,147 @@
+//! Writes the rows as flashcards that Anki can import, in the text format of
+//! its importer: a field per column, split by tabs, with the settings in `#`
+//! lines at the top. Each card has the paths of the combinators on the front,
+//! with their signatures, and their usage, inputs, and outputs on the back.
+//!
+//! Anki matches notes by their first field when importing a file again, so
+//! rows with the same combinators go on the same card, or all but the last
+//! would be lost.
+
+use crate::{
+    hover::full_path,
+    llms::plain,
+    sheet::{Link, Row},
+};
+use std::{fs, io::Result, path::Path};
+
+static DECK: &str = "Nom Cheatsheet";
+
+/// Text as a field of a card, which is HTML. Tabs would start the next field
+/// and newlines the next card.
+fn field(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' => escaped.push_str("&#9;"),
+            '\n' => escaped.push_str("<br>"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// What goes on the back of the card for a row
+fn back(row: &Row) -> String {
+    let mut back = format!("<code>{}</code>", field(row.usage));
+    for input in &row.inputs {
+        back.push_str(&format!("<br>Input: <code>{}</code>", field(input)));
+    }
+    back.push_str(&format!("<br>{}", field(&plain(&row.output))));
+    let description = plain(row.description);
+    if !description.is_empty() {
+        back.push_str(&format!("<br><br>{}", field(&description)));
+    }
+    back
+}
+
+/// What goes on the front of the card for a combinator: its path, and its
+/// signature if it's a function of nom
+fn front(link: &Link) -> String {
+    match link.signature {
+        Some(signature) => format!(
+            "{}<br><code>{}</code>",
+            field(&full_path(link)),
+            field(signature)
+        ),
+        None => field(&full_path(link)),
+    }
+}
+
+/// The front, back, and tags of the cards, in the order of the sheet. Rows
+/// without combinators don't have anything to ask about.
+fn cards(rows: &[Row]) -> Vec<(String, String, String)> {
+    let mut cards = Vec::<(String, String, String)>::new();
+    for row in rows.iter().filter(|row| !row.combinators.is_empty()) {
+        let front = row
+            .combinators
+            .iter()
+            .map(front)
+            .collect::<Vec<_>>()
+            .join("<br>");
+        let mut tags = vec![row.level.name().to_string()];
+        tags.extend(row.tags.iter().map(|tag| tag.to_string()));
+        match cards.iter_mut().find(|(other, _, _)| *other == front) {
+            Some((_, other_back, other_tags)) => {
+                other_back.push_str("<hr>");
+                other_back.push_str(&back(row));
+                for tag in tags {
+                    if !other_tags.split(' ').any(|other| other == tag) {
+                        other_tags.push(' ');
+                        other_tags.push_str(&tag);
+                    }
+                }
+            }
+            None => cards.push((front, back(row), tags.join(" "))),
+        }
+    }
+    cards
+}
+
+/// Writes the cards for the rows to `path`
+pub fn write_anki(path: &Path, rows: &[Row]) -> Result<()> {
+    let mut text =
+        format!("#separator:tab\n#html:true\n#notetype:Basic\n#deck:{DECK}\n#tags column:3\n");
+    for (front, back, tags) in cards(rows) {
+        text.push_str(&format!("{front}\t{back}\t{tags}\n"));
+    }
+    fs::write(path, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cards() {
+        let row = |usage, output: &str, tags| Row {
+            combinators: vec![Link {
+                module: "bytes::complete",
+                name: "tag",
+                url: "https://docs.rs/nom/latest/nom/bytes/complete/fn.tag.html".into(),
+                summary: None,
+                signature: Some("fn tag(tag)"),
+            }],
+            usage,
+            inputs: vec!["\"hello\tworld\""],
+            output: output.to_string(),
+            description: "Matches [the tag](#x)",
+            tags,
+            ..Row::for_test("Basic elements")
+        };
+        let rows = [
+            row(
+                "tag(\"hello\")",
+                "Result: `\"hello\"`<br>Remainder: `\"\\tworld\"`",
+                vec!["text"],
+            ),
+            row("tag(\"world\")", "Error", vec!["text", "errors"]),
+        ];
+        assert_eq!(
+            cards(&rows),
+            [(
+                "nom::bytes::complete::tag<br><code>fn tag(tag)</code>".to_string(),
+                "<code>tag(&quot;hello&quot;)</code><br>Input: \
+                 <code>&quot;hello&#9;world&quot;</code><br>Result: \
+                 `&quot;hello&quot;`<br>Remainder: `&quot;\\tworld&quot;`<br><br>Matches the \
+                 tag<hr><code>tag(&quot;world&quot;)</code><br>Input: \
+                 <code>&quot;hello&#9;world&quot;</code><br>Error<br><br>Matches the tag"
+                    .to_string(),
+                "beginner text errors".to_string(),
+            )]
+        );
+    }
+}
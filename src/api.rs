@@ -0,0 +1,130 @@
+use crate::{
+    cache::Cache,
+    generated::generate,
+    server::{self, Response},
+    sheet::{Level, Link, Row},
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Result,
+    process::ExitCode,
+};
+
+/// A combinator along with the ids of the rows that show it
+#[derive(Serialize)]
+struct Combinator<'a> {
+    name: &'a str,
+    links: Vec<&'a Link>,
+    rows: Vec<usize>,
+}
+
+fn combinators(rows: &[Row]) -> Vec<Combinator<'_>> {
+    let mut combinators = BTreeMap::<&str, Combinator>::new();
+    for row in rows {
+        for link in &row.combinators {
+            let combinator = combinators.entry(link.name).or_insert_with(|| Combinator {
+                name: link.name,
+                links: Vec::new(),
+                rows: Vec::new(),
+            });
+            if !combinator.links.iter().any(|known| known.url == link.url) {
+                combinator.links.push(link);
+            }
+            if !combinator.rows.contains(&row.id) {
+                combinator.rows.push(row.id);
+            }
+        }
+    }
+    combinators.into_values().collect()
+}
+
+fn route(rows: &[Row], path: &str, parameters: &HashMap<String, String>) -> Response {
+    match path.trim_end_matches('/') {
+        "/api/combinators" => Response::json(&combinators(rows)),
+        "/api/rows" => Response::json(&rows),
+        "/api/search" => match parameters.get("q") {
+            Some(query) => Response::json(
+                &rows
+                    .iter()
+                    .filter(|row| row.matches(query))
+                    .collect::<Vec<_>>(),
+            ),
+            None => Response::error(400, "Search needs a `q` parameter"),
+        },
+        path => match path
+            .strip_prefix("/api/rows/")
+            .and_then(|id| id.parse::<usize>().ok())
+            .and_then(|id| rows.iter().find(|row| row.id == id))
+        {
+            Some(row) => Response::json(row),
+            None => Response::error(404, "Not found"),
+        },
+    }
+}
+
+/// Serves the cheatsheet as JSON over HTTP, so that other tools can look
+/// things up in it without bundling a copy.
+pub fn serve(address: &str) -> Result<ExitCode> {
+    // Cached outputs don't say how long the examples took to run
+    let sheet = generate(Level::Advanced, false, &mut Cache::disabled())?;
+    println!(
+        "Serving {} rows on http://{address}/api/rows",
+        sheet.rows.len()
+    );
+    server::serve(address, |path, parameters| {
+        route(&sheet.rows, path, parameters)
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: usize, name: &'static str, usage: &'static str) -> Row {
+        Row {
+            id,
+            combinators: vec![Link {
+                module: "bytes::complete",
+                name,
+                url: "https://docs.rs/".into(),
+                summary: None,
+                signature: None,
+            }],
+            usage,
+            inputs: vec![r#""abc""#],
+            output: "Result: `\"a\"`".to_string(),
+            ..Row::for_test("Basic elements")
+        }
+    }
+
+    #[test]
+    fn test_route() {
+        let rows = [
+            row(0, "tag", r#"tag("a")"#),
+            row(3, "tag", r#"tag("b")"#),
+            row(4, "take", "take(1_u8)"),
+        ];
+        let no_parameters = HashMap::new();
+
+        let response = route(&rows, "/api/combinators", &no_parameters);
+        let combinators: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(combinators[0]["name"], "tag");
+        assert_eq!(combinators[0]["rows"], serde_json::json!([0, 3]));
+        assert_eq!(combinators[0]["links"].as_array().unwrap().len(), 1);
+
+        let response = route(&rows, "/api/rows/3", &no_parameters);
+        assert_eq!(response.status, 200);
+        let row: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(row["usage"], r#"tag("b")"#);
+        assert_eq!(route(&rows, "/api/rows/1", &no_parameters).status, 404);
+
+        let parameters = HashMap::from([("q".to_string(), "TAKE".to_string())]);
+        let response = route(&rows, "/api/search", &parameters);
+        let found: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(found.as_array().unwrap().len(), 1);
+        assert_eq!(found[0]["id"], 4);
+        assert_eq!(route(&rows, "/api/search", &no_parameters).status, 400);
+    }
+}
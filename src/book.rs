@@ -0,0 +1,112 @@
+//! Writes the sheet as an mdBook book, in `book/`, with a chapter per
+//! section. `mdbook build` turns it into a site with mdBook's own search and
+//! navigation, instead of the HTML that's written here.
+
+use crate::{
+    sheet::{self, Section},
+    site::{ids, link_across_pages},
+};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Result,
+    path::{Path, PathBuf},
+};
+
+const BOOK_TOML: &str = r#"[book]
+title = "Nom Cheatsheet"
+src = "src"
+"#;
+
+/// The file that the chapter of a section gets written to
+fn file_name(section: &Section) -> String {
+    format!("{}.md", section.anchor)
+}
+
+/// The chapters in order, with the introduction first, as mdBook makes a
+/// `README.md` the index of the book
+fn summary(sections: &[Section]) -> String {
+    let mut summary = "# Summary\n\n[Nom cheatsheet](README.md)\n\n".to_string();
+    for section in sections {
+        summary.push_str(&format!(
+            "- [{}]({})\n",
+            section.title.replace('`', ""),
+            file_name(section)
+        ));
+    }
+    summary
+}
+
+/// Makes every heading outside of code blocks one level higher, so that the
+/// chapters start with a level 1 heading, which is what mdBook expects
+fn promote_headings(markdown: &str) -> String {
+    let mut promoted = String::new();
+    let mut in_code = false;
+    for line in markdown.split_inclusive('\n') {
+        if line.starts_with("```") {
+            in_code = !in_code;
+        }
+        match line.strip_prefix("##") {
+            Some(rest) if !in_code && rest.starts_with(['#', ' ']) => {
+                promoted.push('#');
+                promoted.push_str(rest);
+            }
+            _ => promoted.push_str(line),
+        }
+    }
+    promoted
+}
+
+/// Writes the book for `markdown`, which is the sheet as the markdown files
+/// have it, to `dist/book/`, and returns the path of the book
+pub fn write_book(dist: &Path, markdown: &str) -> Result<PathBuf> {
+    let dir = dist.join("book");
+    let src = dir.join("src");
+    fs::create_dir_all(&src)?;
+    fs::write(dir.join("book.toml"), BOOK_TOML)?;
+    let (intro, sections) = sheet::sections(markdown);
+    fs::write(src.join("SUMMARY.md"), summary(&sections))?;
+
+    let file_names = sections.iter().map(file_name).collect::<Vec<_>>();
+    let mut chapter_of_id = HashMap::new();
+    for (section, file_name) in sections.iter().zip(&file_names) {
+        let anchors = section.anchors.iter().map(String::as_str);
+        for id in anchors.chain(ids(&section.markdown)) {
+            chapter_of_id.entry(id).or_insert(file_name.as_str());
+        }
+    }
+    fs::write(
+        src.join("README.md"),
+        link_across_pages(&intro, "README.md", &chapter_of_id),
+    )?;
+    for (section, file_name) in sections.iter().zip(&file_names) {
+        let markdown = link_across_pages(&section.markdown, file_name, &chapter_of_id);
+        fs::write(src.join(file_name), promote_headings(&markdown))?;
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let (_, sections) = sheet::sections("## Basic `tag`\n\n### Numbers\n\n## Choice\n");
+        assert_eq!(
+            summary(&sections),
+            "# Summary\n\n[Nom cheatsheet](README.md)\n\n\
+             - [Basic tag](basic-tag.md)\n- [Choice](choice.md)\n"
+        );
+    }
+
+    #[test]
+    fn test_promote_headings() {
+        assert_eq!(
+            promote_headings(
+                "## Numbers\n\n```rust\n## not a heading\n```\n\n### Text\n#[derive]\n"
+            ),
+            "# Numbers\n\n```rust\n## not a heading\n```\n\n## Text\n#[derive]\n"
+        );
+    }
+}
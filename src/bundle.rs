@@ -0,0 +1,154 @@
+//! Packs everything that a run generated into a single archive, for handing
+//! the cheatsheet to offline environments. The archives are deterministic:
+//! the files are sorted by name, and their timestamps, owners, and
+//! permissions don't depend on the machine that made them.
+
+use crate::{html::Artifact, manifest, versions::NOM_VERSION};
+use clap::ValueEnum;
+use flate2::{write::GzEncoder, Compression};
+use std::{
+    fs::{self, File},
+    io::{self, Result, Write},
+    path::{Path, PathBuf},
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, DateTime, ZipWriter};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    #[value(name = "tar.gz")]
+    TarGz,
+    Zip,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::TarGz => "tar.gz",
+            Format::Zip => "zip",
+        }
+    }
+}
+
+/// The files to bundle, by the name they get in the archive, with the ones
+/// in directories like `site/` under that name
+fn entries(artifacts: &[Artifact]) -> Result<Vec<(String, PathBuf)>> {
+    let mut entries = Vec::new();
+    for artifact in artifacts {
+        entries.extend(manifest::artifact_files(&artifact.path)?);
+    }
+    entries.sort();
+    entries.dedup_by(|a, b| a.0 == b.0);
+    Ok(entries)
+}
+
+pub(crate) fn write_tar_gz(
+    archive: &mut impl Write,
+    directory: &str,
+    entries: &[(String, PathBuf)],
+    mtime: u64,
+) -> Result<()> {
+    // `GzEncoder::new` leaves the timestamp in the gzip header at zero
+    let mut builder = tar::Builder::new(GzEncoder::new(archive, Compression::default()));
+    for (name, path) in entries {
+        let contents = fs::read(path)?;
+        let mut header = tar::Header::new_ustar();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        header.set_uid(0);
+        header.set_gid(0);
+        builder.append_data(
+            &mut header,
+            format!("{directory}/{name}"),
+            contents.as_slice(),
+        )?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+fn write_zip(
+    archive: &mut (impl Write + io::Seek),
+    directory: &str,
+    entries: &[(String, PathBuf)],
+    mtime: u64,
+) -> Result<()> {
+    // Zip timestamps can't go back further than 1980
+    let [year, month, day, hour, minute, second] = manifest::civil_time(mtime);
+    let time = u16::try_from(year)
+        .ok()
+        .and_then(|year| {
+            let part = |value: u64| u8::try_from(value).unwrap();
+            DateTime::from_date_and_time(
+                year,
+                part(month),
+                part(day),
+                part(hour),
+                part(minute),
+                part(second),
+            )
+            .ok()
+        })
+        .unwrap_or_default();
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(time)
+        .unix_permissions(0o644);
+    let mut writer = ZipWriter::new(archive);
+    for (name, path) in entries {
+        writer.start_file(format!("{directory}/{name}"), options)?;
+        writer.write_all(&fs::read(path)?)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Writes an archive with all the artifacts to `dist`, and returns its path.
+/// Everything in there is in a directory named after the nom version, so
+/// unpacking several of them doesn't mix them up.
+pub fn bundle(dist: &Path, artifacts: &[Artifact], format: Format) -> Result<PathBuf> {
+    let directory = format!("nom-cheatsheet-{NOM_VERSION}");
+    let path = dist.join(format!("{directory}.{}", format.extension()));
+    let entries = entries(artifacts)?;
+    let mtime = manifest::build_time();
+    let mut archive = File::create(&path)?;
+    match format {
+        Format::TarGz => write_tar_gz(&mut archive, &directory, &entries, mtime)?,
+        Format::Zip => write_zip(&mut archive, &directory, &entries, mtime)?,
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_deterministic() {
+        let dir =
+            std::env::temp_dir().join(format!("nom-cheatsheet-bundle-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::create_dir_all(dir.join("site")).unwrap();
+        for name in ["b.md", "a.html", "site/index.html"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+        let artifacts = ["b.md", "a.html", "site"].map(|name| Artifact {
+            label: "Test",
+            path: dir.join(name),
+        });
+        let entries = entries(&artifacts).unwrap();
+        assert_eq!(entries[0].0, "a.html");
+        assert_eq!(entries[2].0, "site/index.html");
+
+        type Writer = fn(&mut Cursor<Vec<u8>>, &str, &[(String, PathBuf)], u64) -> Result<()>;
+        for write in [write_tar_gz as Writer, write_zip] {
+            let mut first = Cursor::new(Vec::new());
+            write(&mut first, "sheet", &entries, 1_000_000_000).unwrap();
+            let mut second = Cursor::new(Vec::new());
+            write(&mut second, "sheet", &entries, 1_000_000_000).unwrap();
+            assert_eq!(first.into_inner(), second.into_inner());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+//! Keeps the outputs of the examples between runs, so regenerating the sheet
+//! after only the prose changed, or on a CI re-run, doesn't run them again.
+//! A row's output is only reused for the same row code, nom version, and
+//! compiler, and for the same code formatting the outputs.
+//!
+//! Only running the examples is skipped, not compiling them. The build script
+//! could put the cached output cells in as literals, but the binary would
+//! then give stale outputs with `--no-cache`, `--timings` and `validate`, and
+//! the build would have to rerun whenever a run of the binary writes to the
+//! cache.
+
+use crate::versions::NOM_VERSION;
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use std::{env, fs, path::PathBuf};
+
+/// The code that formats the outputs, which changes them without changing
+/// the rows
+static FORMATTING_CODE: &[&str] = &[
+    include_str!("main.rs"),
+    include_str!("../nom-cheatsheet-shared/src/lib.rs"),
+];
+
+pub struct Cache {
+    /// `None` when disabled
+    dir: Option<PathBuf>,
+    /// A hash of everything besides the row that the outputs depend on
+    environment: String,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl Cache {
+    /// The cache in `target/example-cache`, or in `NOM_CHEATSHEET_CACHE_DIR`
+    /// if that's set
+    pub fn new() -> Self {
+        let dir = env::var_os("NOM_CHEATSHEET_CACHE_DIR").map_or_else(
+            || PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/example-cache"),
+            PathBuf::from,
+        );
+        let mut hasher = Sha256::new();
+        for part in [NOM_VERSION, env!("NOM_CHEATSHEET_RUSTC_VERSION")]
+            .into_iter()
+            .chain(FORMATTING_CODE.iter().copied())
+        {
+            hasher.update(part);
+            hasher.update([0]);
+        }
+        Self {
+            dir: Some(dir),
+            environment: format!("{:x}", hasher.finalize()),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// A cache that never has anything, for when the examples need to run,
+    /// like for timing them
+    pub fn disabled() -> Self {
+        Self {
+            dir: None,
+            environment: String::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn path(&self, row_hash: &str) -> Option<PathBuf> {
+        let key = Sha256::digest(format!("{row_hash} {}", self.environment));
+        Some(self.dir.as_ref()?.join(format!("{key:x}.json")))
+    }
+
+    /// The cached output of the row with this hash, if there is one
+    pub fn get<T: DeserializeOwned>(&mut self, row_hash: &str) -> Option<T> {
+        let path = self.path(row_hash)?;
+        let output = fs::read(path)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok());
+        match output {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        output
+    }
+
+    /// Stores the output of a row. The cache is only there to save time, so
+    /// failing to write to it isn't an error.
+    pub fn insert(&self, row_hash: &str, output: &impl Serialize) {
+        if let Some(path) = self.path(row_hash) {
+            let _ = fs::create_dir_all(path.parent().unwrap());
+            let _ = fs::write(path, serde_json::to_vec(output).unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache() {
+        let dir = env::temp_dir().join(format!("nom-cheatsheet-cache-{}", std::process::id()));
+        let mut cache = Cache {
+            dir: Some(dir.clone()),
+            ..Cache::new()
+        };
+        assert_eq!(cache.get::<String>("row"), None);
+        cache.insert("row", &("Result: `1`", ["Ok((\"\", 1))"]));
+        assert_eq!(
+            cache.get("row"),
+            Some(("Result: `1`".to_string(), vec!["Ok((\"\", 1))".to_string()]))
+        );
+        assert_eq!(cache.get::<String>("other row"), None);
+        assert_eq!((cache.hits, cache.misses), (1, 2));
+
+        let mut disabled = Cache::disabled();
+        disabled.insert("row", &"Result: `1`");
+        assert_eq!(disabled.get::<String>("row"), None);
+        assert_eq!(disabled.misses, 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,45 @@
+// Copies things to the clipboard with a click. The "Copy test" button of a
+// row has the test in its `data-test` attribute. The code of usage cells,
+// which follows a `<!-- usage -->` comment, gets a button of its own here.
+// Usages of several lines have a code element for each, with `<br>`s.
+(() => {
+    const comments = document.createTreeWalker(
+        document.querySelector("article"),
+        NodeFilter.SHOW_COMMENT,
+    );
+    const usages = [];
+    while (comments.nextNode()) {
+        const code = comments.currentNode.nextSibling;
+        if (comments.currentNode.data.trim() === "usage" && code?.nodeName === "CODE") {
+            usages.push(code);
+        }
+    }
+    for (const code of usages) {
+        const lines = [code];
+        let next = code.nextSibling;
+        while (next?.nodeName === "BR" && next.nextSibling?.nodeName === "CODE") {
+            lines.push(next.nextSibling);
+            next = next.nextSibling.nextSibling;
+        }
+        const button = document.createElement("button");
+        button.className = "copy-usage";
+        button.title = "Copy the code";
+        button.textContent = "Copy";
+        button.dataset.text = lines.map((line) => line.textContent).join("\n");
+        lines[lines.length - 1].after(button);
+    }
+
+    const buttons = document.querySelectorAll("button.copy-test, button.copy-usage");
+    for (const button of buttons) {
+        button.addEventListener("click", async () => {
+            const label = button.textContent;
+            try {
+                await navigator.clipboard.writeText(button.dataset.test ?? button.dataset.text);
+                button.textContent = "Copied";
+            } catch {
+                button.textContent = "Couldn't copy";
+            }
+            setTimeout(() => (button.textContent = label), 1500);
+        });
+    }
+})();
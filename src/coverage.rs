@@ -0,0 +1,130 @@
+//! Reports which of nom's public functions the template doesn't have a row
+//! for yet, by module, going by nom's rustdoc JSON. Combinators that are in
+//! several modules, like the `complete` and `streaming` versions of `tag`,
+//! each count on their own.
+
+use crate::{
+    links::rustdoc_pages,
+    seed::{build_rustdoc_json, covered_paths},
+    versions::NOM_VERSION,
+};
+use nom_cheatsheet_shared::expand_includes;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+    fs,
+    io::Result,
+    path::Path,
+    process::ExitCode,
+};
+
+/// The report as markdown, with a section per module that has functions
+/// missing from the template, and how many that module has covered
+fn report(pages: &HashMap<String, String>, covered: &HashSet<String>) -> String {
+    let mut modules = BTreeMap::<&str, (usize, Vec<&str>)>::new();
+    for (path, page) in pages {
+        if !page
+            .rsplit('/')
+            .next()
+            .is_some_and(|file| file.starts_with("fn."))
+        {
+            continue;
+        }
+        let module = path.rsplit_once("::").map_or("", |(module, _)| module);
+        let (count, missing) = modules.entry(module).or_default();
+        *count += 1;
+        if !covered.contains(path) {
+            missing.push(path);
+        }
+    }
+    let total = modules.values().map(|(count, _)| count).sum::<usize>();
+    let missing = modules
+        .values()
+        .map(|(_, missing)| missing.len())
+        .sum::<usize>();
+    let mut report = format!("# Coverage of nom {NOM_VERSION}\n\n");
+    writeln!(
+        report,
+        "The template has rows for {} of nom's {total} public functions.",
+        total - missing
+    )
+    .unwrap();
+    for (module, (count, mut missing)) in modules {
+        if missing.is_empty() {
+            continue;
+        }
+        missing.sort_unstable();
+        let module = match module {
+            "" => "nom",
+            module => module,
+        };
+        writeln!(
+            report,
+            "\n## {module}\n\n{} of {count} covered, missing:\n",
+            count - missing.len()
+        )
+        .unwrap();
+        for path in missing {
+            writeln!(report, "- `{path}`").unwrap();
+        }
+    }
+    report
+}
+
+/// Prints the coverage report, or writes it to `output`. The rustdoc JSON
+/// gets built with a nightly toolchain if it isn't given.
+pub fn coverage(
+    rustdoc_json: Option<&Path>,
+    template: &Path,
+    output: Option<&Path>,
+) -> Result<ExitCode> {
+    let rustdoc_json = match rustdoc_json {
+        Some(path) => path.to_path_buf(),
+        None => build_rustdoc_json()?,
+    };
+    let rustdoc: Value = serde_json::from_str(&fs::read_to_string(rustdoc_json)?)?;
+    let covered = covered_paths(&expand_includes(template)?.text);
+    let report = report(&rustdoc_pages(&rustdoc), &covered);
+    match output {
+        Some(output) => {
+            fs::write(output, &report)?;
+            println!("Wrote the coverage report to {}", output.display());
+        }
+        None => print!("{report}"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let pages = HashMap::from(
+            [
+                ("bytes::complete::tag", "bytes/complete/fn.tag.html"),
+                ("bytes::complete::take", "bytes/complete/fn.take.html"),
+                ("bytes::streaming::tag", "bytes/streaming/fn.tag.html"),
+                ("branch::alt", "branch/fn.alt.html"),
+                ("error_position", "macro.error_position.html"),
+                ("Parser::map", "trait.Parser.html#method.map"),
+                ("Parser", "trait.Parser.html"),
+            ]
+            .map(|(path, page)| (path.to_string(), page.to_string())),
+        );
+        let covered = HashSet::from(["bytes::complete::tag", "branch::alt"].map(str::to_string));
+        assert_eq!(
+            report(&pages, &covered),
+            format!(
+                "# Coverage of nom {NOM_VERSION}\n\n\
+                 The template has rows for 2 of nom's 4 public functions.\n\
+                 \n## bytes::complete\n\n1 of 2 covered, missing:\n\n\
+                 - `bytes::complete::take`\n\
+                 \n## bytes::streaming\n\n0 of 1 covered, missing:\n\n\
+                 - `bytes::streaming::tag`\n"
+            )
+        );
+    }
+}
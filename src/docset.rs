@@ -0,0 +1,178 @@
+//! Packages the HTML as a docset for Dash and Zeal, with a search index that
+//! has an entry for every combinator, leading to the section with its rows.
+//! The docset is written as `nom-cheatsheet.docset`, which they can open as
+//! it is, and packed into `nom-cheatsheet.tgz`, which is what docset feeds
+//! point to.
+
+use crate::{
+    bundle, manifest,
+    sheet::Row,
+    sqlite::{self, Value},
+};
+use std::{
+    fs::{self, File},
+    io::Result,
+    path::{Path, PathBuf},
+};
+
+static NAME: &str = "nom-cheatsheet";
+
+static INFO_PLIST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>CFBundleIdentifier</key>
+    <string>nom-cheatsheet</string>
+    <key>CFBundleName</key>
+    <string>nom cheatsheet</string>
+    <key>DocSetPlatformFamily</key>
+    <string>nom</string>
+    <key>isDashDocset</key>
+    <true/>
+    <key>dashIndexFilePath</key>
+    <string>nom-cheatsheet.html</string>
+</dict>
+</plist>
+"#;
+
+/// The kind of entry that Dash shows for the item that the docs are about
+fn entry_type(url: &str) -> &'static str {
+    if url.contains("#method.") {
+        return "Method";
+    }
+    let file = url.rsplit('/').next().unwrap_or_default();
+    match file.split('.').next().unwrap_or_default() {
+        "macro" => "Macro",
+        "trait" => "Trait",
+        "struct" => "Struct",
+        "enum" => "Enum",
+        "type" => "Type",
+        "constant" => "Constant",
+        _ => "Function",
+    }
+}
+
+/// The name, type, and path of every entry in the search index, which goes
+/// to the row that shows the combinator. Combinators that are in several rows
+/// only get one, for the first of them.
+fn entries(rows: &[Row]) -> Vec<(&'static str, &'static str, String)> {
+    let mut entries = Vec::new();
+    for row in rows {
+        for link in &row.combinators {
+            let entry_type = entry_type(&link.url);
+            if !entries
+                .iter()
+                .any(|&(name, known_type, _)| name == link.name && known_type == entry_type)
+            {
+                entries.push((link.name, entry_type, format!("{NAME}.html#{}", row.anchor)));
+            }
+        }
+    }
+    entries
+}
+
+/// Where `write_docset` puts the archive of the docset in `dist`, which the
+/// HTML that goes into it already links to
+pub fn archive_path(dist: &Path) -> PathBuf {
+    dist.join(format!("{NAME}.tgz"))
+}
+
+/// Writes the docset for the HTML at `html_path` to `dist`, and returns the
+/// path of its archive
+pub fn write_docset(dist: &Path, html_path: &Path, rows: &[Row]) -> Result<PathBuf> {
+    let docset = dist.join(format!("{NAME}.docset"));
+    let resources = docset.join("Contents/Resources");
+    fs::create_dir_all(resources.join("Documents"))?;
+    fs::write(docset.join("Contents/Info.plist"), INFO_PLIST)?;
+    fs::copy(html_path, resources.join(format!("Documents/{NAME}.html")))?;
+    let rows = entries(rows)
+        .into_iter()
+        .map(|(name, kind, path)| {
+            vec![
+                Value::Null,
+                Value::Text(name.to_string()),
+                Value::Text(kind.to_string()),
+                Value::Text(path),
+            ]
+        })
+        .collect::<Vec<_>>();
+    fs::write(
+        resources.join("docSet.dsidx"),
+        sqlite::database(
+            "searchIndex",
+            "CREATE TABLE searchIndex(id INTEGER PRIMARY KEY, name TEXT, type TEXT, path TEXT)",
+            &rows,
+        ),
+    )?;
+
+    let files = [
+        "Contents/Info.plist".to_string(),
+        "Contents/Resources/docSet.dsidx".to_string(),
+        format!("Contents/Resources/Documents/{NAME}.html"),
+    ]
+    .map(|file| {
+        let path = docset.join(&file);
+        (file, path)
+    });
+    let archive_path = archive_path(dist);
+    bundle::write_tar_gz(
+        &mut File::create(&archive_path)?,
+        &format!("{NAME}.docset"),
+        &files,
+        manifest::build_time(),
+    )?;
+    Ok(archive_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet::Link;
+
+    #[test]
+    fn test_entries() {
+        let row = |anchor, url: &'static str| Row {
+            combinators: vec![
+                Link {
+                    module: "bytes::complete",
+                    name: "tag",
+                    url: url.into(),
+                    summary: None,
+                    signature: None,
+                },
+                Link {
+                    module: "bytes::streaming",
+                    name: "tag",
+                    url: url.into(),
+                    summary: None,
+                    signature: None,
+                },
+            ],
+            usage: "tag(\"a\")",
+            inputs: vec!["\"abc\""],
+            output: "Result: `\"a\"`".to_string(),
+            anchor,
+            ..Row::for_test("Basic elements")
+        };
+        assert_eq!(
+            entries(&[
+                row(
+                    "tag",
+                    "https://docs.rs/nom/latest/nom/bytes/complete/fn.tag.html"
+                ),
+                row(
+                    "tag-2",
+                    "https://docs.rs/nom/latest/nom/bytes/complete/fn.tag.html"
+                ),
+                row(
+                    "map",
+                    "https://docs.rs/nom/latest/nom/trait.Parser.html#method.map"
+                ),
+            ]),
+            [
+                ("tag", "Function", "nom-cheatsheet.html#tag".to_string()),
+                ("tag", "Method", "nom-cheatsheet.html#map".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+// Makes the inputs of the rows editable, and runs the row's parser on what's
+// typed with the WebAssembly module, so the output cell shows what it would
+// be for that input. This only happens when the module was built, which the
+// "JavaScript bindings" link in the footer says. Rows carry their id in an
+// HTML comment like `<!-- row = 12 -->`.
+(() => {
+    const bindings = document.querySelector('footer a[href$="nom-cheatsheet.js"]');
+    if (!bindings) {
+        return;
+    }
+    // Loaded on the first edit, as most readers never make one
+    let cheatsheet;
+    const load = () => (cheatsheet ??= import(bindings.href).then((module) => module.load()));
+
+    // The characters that escapes in Rust strings stand for
+    const escapes = { n: "\n", r: "\r", t: "\t", "\\": "\\", 0: "\0", "'": "'", '"': '"' };
+    // And the symbols that rows with visible whitespace show instead
+    const symbols = { "·": " ", "↵": "\n", "␍": "\r", "→": "\t" };
+
+    // Turns the body of a string literal into its bytes. Byte strings can
+    // have any byte in a `\x` escape, text only ASCII ones.
+    const stringBytes = (body, bytes) => {
+        const out = [];
+        const encoder = new TextEncoder();
+        // By code point, so that characters outside of the BMP stay whole
+        const chars = [...body];
+        for (let index = 0; index < chars.length; index++) {
+            const c = chars[index];
+            if (c !== "\\") {
+                out.push(...encoder.encode(symbols[c] ?? c));
+                continue;
+            }
+            const next = chars[++index];
+            if (next in escapes) {
+                out.push(escapes[next].charCodeAt(0));
+            } else if (next === "x") {
+                const code = parseInt(chars.slice(index + 1, index + 3).join(""), 16);
+                if (!(code <= (bytes ? 0xff : 0x7f))) {
+                    throw new Error("that `\\x` escape isn't valid here");
+                }
+                out.push(code);
+                index += 2;
+            } else if (next === "u" && !bytes) {
+                const end = chars.indexOf("}", index);
+                const code = parseInt(chars.slice(index + 2, end).join(""), 16);
+                out.push(...encoder.encode(String.fromCodePoint(code)));
+                index = end;
+            } else if (next === "\n") {
+                while (/\s/.test(chars[index + 1])) {
+                    index++;
+                }
+            } else {
+                throw new Error(`\`\\${next}\` isn't an escape`);
+            }
+        }
+        return out;
+    };
+
+    // A number in an array of bytes, like `0x0f`, `0b1010_0000u8`, or `b'a'`
+    const byte = (literal) => {
+        const char = literal.match(/^b'(.*)'$/s);
+        if (char) {
+            return stringBytes(char[1], true)[0];
+        }
+        const digits = literal.replace(/_/g, "").replace(/u8$/, "");
+        const number = /^(0x[\da-f]+|0o[0-7]+|0b[01]+|\d+)$/i.test(digits) ? Number(digits) : NaN;
+        if (!(number >= 0 && number <= 0xff)) {
+            throw new Error(`\`${literal}\` isn't a byte`);
+        }
+        return number;
+    };
+
+    // The bytes of an input like the ones in the sheet: a string, a byte
+    // string, an array of bytes, a span of either, or the bytes of a bit
+    // parser, which the module always starts at the first bit of
+    const inputBytes = (code) => {
+        code = code.trim();
+        const span = code.match(/^LocatedSpan::new\((.*)\)$/s);
+        if (span) {
+            return inputBytes(span[1]);
+        }
+        const bits = code.match(/^\((.*),\s*0\s*\)$/s);
+        if (bits) {
+            return inputBytes(bits[1]);
+        }
+        code = code.replace(/^&/, "").replace(/\[\.\.\]$/, "");
+        const raw = code.match(/^(b?)r(#*)"(.*)"\2$/s);
+        if (raw) {
+            return [...new TextEncoder().encode(raw[3])];
+        }
+        const string = code.match(/^(b?)"(.*)"$/s);
+        if (string) {
+            return stringBytes(string[2], string[1] === "b");
+        }
+        const array = code.match(/^\[(.*)\]$/s);
+        if (array) {
+            return array[1]
+                .split(",")
+                .map((item) => item.trim())
+                .filter(Boolean)
+                .map(byte);
+        }
+        throw new Error("this isn't a string, a byte string, or an array of bytes");
+    };
+
+    // Output cells are markdown, with code spans, and `<br>`s between lines
+    const renderCell = (markdown) => {
+        const cell = document.createDocumentFragment();
+        const parts = markdown.split(/(`+)([\s\S]*?)\1/);
+        for (let index = 0; index < parts.length; index += 3) {
+            parts[index].split("<br>").forEach((line, number) => {
+                if (number > 0) {
+                    cell.append(document.createElement("br"));
+                }
+                cell.append(line);
+            });
+            if (index + 2 < parts.length) {
+                const code = document.createElement("code");
+                code.textContent = parts[index + 2].replace(/^ (.*) $/s, "$1");
+                cell.append(code);
+            }
+        }
+        return cell;
+    };
+
+    const columns = (table) =>
+        [...table.querySelectorAll("thead th")].map((th) => th.textContent.trim());
+
+    const comments = document.createTreeWalker(
+        document.querySelector("article"),
+        NodeFilter.SHOW_COMMENT,
+    );
+    const rows = [];
+    while (comments.nextNode()) {
+        const match = comments.currentNode.data.match(/row = (\d+)/);
+        const row = comments.currentNode.parentElement.closest("tr");
+        if (match && row) {
+            rows.push([Number(match[1]), row]);
+        }
+    }
+    for (const [id, row] of rows) {
+        const names = columns(row.closest("table"));
+        const input = row.cells[names.indexOf("input")];
+        const output = row.cells[names.indexOf("output")];
+        // Rows with several inputs show several outputs, which can't be
+        // told apart
+        const codes = input?.querySelectorAll("code");
+        if (!output || codes.length !== 1) {
+            continue;
+        }
+        const code = codes[0];
+        const original = code.textContent;
+        try {
+            inputBytes(original);
+        } catch {
+            continue;
+        }
+        const originalOutput = [...output.childNodes];
+        code.contentEditable = "plaintext-only";
+        code.spellcheck = false;
+        code.classList.add("editable-input");
+        code.title = "Edit the input to run the parser on it";
+        // The ones that link to the byte reference shouldn't go there
+        code.closest("a")?.addEventListener("click", (event) => event.preventDefault());
+        code.addEventListener("keydown", (event) => {
+            if (event.key === "Escape") {
+                code.textContent = original;
+                code.dispatchEvent(new Event("input"));
+                code.blur();
+            }
+        });
+        code.addEventListener("input", async () => {
+            const text = code.textContent;
+            if (text === original) {
+                output.replaceChildren(...originalOutput);
+                output.classList.remove("edited");
+                return;
+            }
+            let markdown;
+            try {
+                const outcome = (await load()).run(id, new Uint8Array(inputBytes(text)));
+                const steps = (outcome.steps ?? []).map((step, index) => `${index + 1}. ${step}`);
+                markdown = [...steps, outcome.output ?? outcome.error].join("<br>");
+            } catch (error) {
+                markdown = `Can't run this input: ${error.message}`;
+            }
+            // Typing on while the module loaded
+            if (code.textContent === text) {
+                output.replaceChildren(renderCell(markdown));
+                output.classList.add("edited");
+            }
+        });
+    }
+})();
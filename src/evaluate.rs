@@ -0,0 +1,116 @@
+//! Runs the examples of the rows before the sheet gets written. Examples are
+//! independent of each other, so the ones that aren't in the cache run on
+//! all cores, and the sheet is then written from their outputs in order.
+
+use crate::cache::Cache;
+use rayon::prelude::*;
+use std::{
+    io::Result,
+    time::{Duration, Instant},
+};
+
+/// The output cell of a row, and the `Debug` output of the result for each
+/// of its inputs
+pub type Output = (String, Vec<String>);
+
+/// The output of a row, and how long it took to get
+pub type Evaluated = (String, Vec<String>, Duration);
+
+/// Runs the examples of the rows that are `wanted`, with `output` running
+/// the example of the row with that index, and `None` for the rows that
+/// aren't compiled in. The outputs are by index, and are only `None` for
+/// the rows that didn't run. If several examples fail, the error is the one
+/// of the first row, as it would be if they ran one after the other, which
+/// they do without `parallel`.
+pub fn evaluate_rows(
+    hashes: &[&str],
+    wanted: impl Fn(usize) -> bool,
+    output: fn(usize) -> Option<Result<Output>>,
+    parallel: bool,
+    cache: &mut Cache,
+) -> Result<Vec<Option<Evaluated>>> {
+    let mut outputs = vec![None; hashes.len()];
+    let mut missing = Vec::new();
+    for (index, hash) in hashes.iter().enumerate() {
+        if !wanted(index) {
+            continue;
+        }
+        let start = Instant::now();
+        match cache.get::<Output>(hash) {
+            Some((cell, debugs)) => outputs[index] = Some((cell, debugs, start.elapsed())),
+            None => missing.push(index),
+        }
+    }
+
+    let run = |index: &usize| {
+        let start = Instant::now();
+        let result = output(*index)
+            .map(|result| result.map(|(cell, debugs)| (cell, debugs, start.elapsed())));
+        (*index, result)
+    };
+    let results = if parallel {
+        missing.par_iter().map(run).collect::<Vec<_>>()
+    } else {
+        missing.iter().map(run).collect::<Vec<_>>()
+    };
+    for (index, result) in results {
+        let Some(result) = result else {
+            continue;
+        };
+        let (cell, debugs, elapsed) = result?;
+        cache.insert(hashes[index], &(&cell, &debugs));
+        outputs[index] = Some((cell, debugs, elapsed));
+    }
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(index: usize) -> Option<Result<Output>> {
+        match index {
+            0 | 2 => Some(Ok((format!("Row {index}"), vec![format!("Ok({index})")]))),
+            3 => Some(Err(std::io::Error::other("Row 3 failed"))),
+            4 => Some(Err(std::io::Error::other("Row 4 failed"))),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_rows() {
+        for parallel in [false, true] {
+            let outputs = evaluate_rows(
+                &["a", "b", "c", "d", "e"],
+                |index| index < 3,
+                output,
+                parallel,
+                &mut Cache::disabled(),
+            )
+            .unwrap();
+            let outputs = outputs
+                .into_iter()
+                .map(|output| output.map(|(cell, debugs, _)| (cell, debugs)))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                outputs,
+                [
+                    Some(("Row 0".to_string(), vec!["Ok(0)".to_string()])),
+                    None,
+                    Some(("Row 2".to_string(), vec!["Ok(2)".to_string()])),
+                    None,
+                    None,
+                ]
+            );
+            let error = evaluate_rows(
+                &["a", "b", "c", "d", "e"],
+                |_| true,
+                output,
+                parallel,
+                &mut Cache::disabled(),
+            )
+            .unwrap_err();
+            assert_eq!(error.to_string(), "Row 3 failed");
+        }
+    }
+}
@@ -1,5 +1,5 @@
 // String literals in formats are inefficient, but this saves us from having to
-// escape braces in a couple of places in build.rs
+// escape braces in a couple of places in the build script
 #![allow(clippy::write_literal)]
 
 include!(concat!(env!("OUT_DIR"), "/generated.rs"));
@@ -0,0 +1,134 @@
+//! Writes the rows as JSON for editor extensions, to show next to what
+//! rust-analyzer says when hovering over a combinator. Everything is keyed by
+//! the full path of the combinator, like `nom::bytes::complete::tag`, which
+//! is what an extension gets from rust-analyzer.
+//!
+//! The shape of the file is versioned with `schema_version`. Adding fields
+//! keeps the version, anything that could break a reader bumps it.
+
+use crate::{
+    llms::plain,
+    sheet::{Link, Row},
+    versions::NOM_VERSION,
+};
+use serde::Serialize;
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Result, Write},
+    path::Path,
+};
+
+const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Bundle {
+    schema_version: u32,
+    nom_version: &'static str,
+    combinators: BTreeMap<String, Entry>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Entry {
+    docs_url: Cow<'static, str>,
+    /// Markdown, with links that only work within the sheet left out
+    description: String,
+    /// Every row that shows the combinator, in the order of the sheet
+    examples: Vec<Example>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Example {
+    usage: &'static str,
+    inputs: Vec<&'static str>,
+    /// What running the example gave, as plain text
+    output: String,
+}
+
+/// The path that the combinator has in Rust. Nom's own are written without
+/// the crate in the sheet.
+pub(crate) fn full_path(link: &Link) -> String {
+    if link.module.starts_with("nom_") {
+        format!("{}::{}", link.module, link.name)
+    } else {
+        format!("nom::{}::{}", link.module, link.name)
+    }
+}
+
+fn bundle(rows: &[Row]) -> Bundle {
+    let mut combinators = BTreeMap::<String, Entry>::new();
+    for row in rows {
+        for link in &row.combinators {
+            let entry = combinators.entry(full_path(link)).or_insert_with(|| Entry {
+                docs_url: link.url.clone(),
+                description: String::new(),
+                examples: Vec::new(),
+            });
+            // Rows that continue a combinator often have no description
+            if entry.description.is_empty() {
+                entry.description = plain(row.description);
+            }
+            entry.examples.push(Example {
+                usage: row.usage,
+                inputs: row.inputs.clone(),
+                output: plain(&row.output),
+            });
+        }
+    }
+    Bundle {
+        schema_version: SCHEMA_VERSION,
+        nom_version: NOM_VERSION,
+        combinators,
+    }
+}
+
+/// Writes the hover documentation for all combinators to `path`
+pub fn write_hover(path: &Path, rows: &[Row]) -> Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut file, &bundle(rows))?;
+    writeln!(file)?;
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle() {
+        let row = |module, description| Row {
+            combinators: vec![Link {
+                module,
+                name: "tag",
+                url: "https://docs.rs/nom/latest/nom/bytes/complete/fn.tag.html".into(),
+                summary: None,
+                signature: None,
+            }],
+            usage: "tag(\"a\")",
+            inputs: vec!["\"abc\""],
+            output: "Result: `\"a\"`<br>Remainder: `\"bc\"`".to_string(),
+            description,
+            ..Row::for_test("Basic elements")
+        };
+        let bundle = bundle(&[
+            row("bytes::complete", ""),
+            row("bytes::complete", "Matches [`tag`](#x)"),
+            row("nom_supreme::tag::complete", "Remembers it"),
+        ]);
+        assert_eq!(
+            bundle.combinators.keys().collect::<Vec<_>>(),
+            [
+                "nom::bytes::complete::tag",
+                "nom_supreme::tag::complete::tag"
+            ]
+        );
+        let entry = &bundle.combinators["nom::bytes::complete::tag"];
+        assert_eq!(entry.description, "Matches `tag`");
+        assert_eq!(entry.examples.len(), 2);
+        assert_eq!(
+            entry.examples[0].output,
+            "Result: `\"a\"`\nRemainder: `\"bc\"`"
+        );
+    }
+}
@@ -0,0 +1,648 @@
+use crate::{hover::full_path, llms::plain, sheet::Row};
+use comrak::{
+    markdown_to_html_with_plugins, plugins::syntect::SyntectAdapterBuilder, Options, Plugins,
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{BufWriter, Result, Write},
+    path::{Path, PathBuf},
+};
+use syntect::{
+    highlighting::ThemeSet,
+    html::{css_for_theme_with_class_style, ClassStyle},
+};
+
+/// The title of the pages, which the template's front matter can change
+pub(crate) static TITLE: &str = env!("NOM_CHEATSHEET_TITLE");
+
+/// The color scheme of the pages, `auto` for the one that the reader's
+/// system prefers, or `light` or `dark` from the template's front matter
+static THEME: &str = env!("NOM_CHEATSHEET_THEME");
+
+/// The version of nom that the template says it's written for, if it does
+static WRITTEN_FOR: &str = env!("NOM_CHEATSHEET_WRITTEN_FOR");
+
+/// A file that was written alongside the HTML during this run, and that
+/// readers might want to download instead.
+pub struct Artifact {
+    pub label: &'static str,
+    pub path: PathBuf,
+}
+
+/// Links to the artifacts, which start with `base` for pages that aren't
+/// next to them, and says what the sheet was generated from
+pub(crate) fn write_footer(
+    html_file: &mut impl Write,
+    html_path: &Path,
+    base: &str,
+    artifacts: &[Artifact],
+    provenance: &str,
+) -> Result<()> {
+    let links = artifacts
+        .iter()
+        .filter(|artifact| artifact.path != html_path)
+        .map(|Artifact { label, path }| {
+            // All artifacts are siblings of the HTML file, so link to them
+            // relatively. That way the links keep working when the whole
+            // directory gets copied somewhere else.
+            let file_name = path.file_name().unwrap().to_string_lossy();
+            format!(r#"<a href="{base}{file_name}">{label}</a>"#)
+        })
+        .collect::<Vec<_>>();
+    writeln!(html_file, r#"<footer class="downloads">"#)?;
+    if !links.is_empty() {
+        writeln!(html_file, "<p>Also available as: {}</p>", links.join(" · "))?;
+    }
+    writeln!(html_file, r#"<p class="provenance">{provenance}</p>"#)?;
+    writeln!(html_file, "</footer>")
+}
+
+/// What the footer says the sheet was generated from, with `summary` being
+/// the build manifest's, and the version of nom that the prose is for
+pub(crate) fn footer_provenance(summary: &str) -> String {
+    if WRITTEN_FOR.is_empty() {
+        return summary.to_string();
+    }
+    format!("Written for nom {WRITTEN_FOR}, made with {summary}")
+}
+
+/// Makes `css`, which has rules for both color schemes, always use the one
+/// for `theme` unless that's `auto`
+fn css_for_theme(css: &str, theme: &str) -> String {
+    let (shown, hidden) = match theme {
+        "light" => ("light", "dark"),
+        "dark" => ("dark", "light"),
+        _ => return css.to_string(),
+    };
+    css.replace(
+        &format!("@media (prefers-color-scheme: {shown})"),
+        "@media all",
+    )
+    .replace(
+        &format!("@media (prefers-color-scheme: {hidden})"),
+        "@media not all",
+    )
+}
+
+/// What the search box in the HTML looks for in a row, and the tags that
+/// the tag bar filters by
+#[derive(Serialize)]
+struct SearchEntry {
+    row: usize,
+    combinators: Vec<String>,
+    description: String,
+    tags: Vec<&'static str>,
+}
+
+/// The rows that `search.js` filters, as JSON that can go in a `<script>`
+fn search_index(rows: &[Row]) -> String {
+    let entries = rows
+        .iter()
+        .map(|row| SearchEntry {
+            row: row.id,
+            combinators: row.combinators.iter().map(full_path).collect(),
+            description: plain(row.description),
+            tags: row.tags.clone(),
+        })
+        .collect::<Vec<_>>();
+    // `</script>` in a description would end the script early
+    serde_json::to_string(&entries)
+        .unwrap()
+        .replace("</", "<\\/")
+}
+
+/// Lets readers pick how advanced the rows they see can be. Advanced rows
+/// are hidden until asked for, so newcomers aren't overwhelmed.
+pub(crate) fn write_level_selector(html_file: &mut impl Write) -> Result<()> {
+    writeln!(html_file, r#"<nav class="levels">"#)?;
+    writeln!(
+        html_file,
+        r#"<input type="search" id="search" placeholder="Filter rows" aria-label="Filter rows">"#
+    )?;
+    writeln!(html_file, r#"<label for="level">Show rows for</label>"#)?;
+    writeln!(html_file, r#"<select id="level">"#)?;
+    writeln!(html_file, r#"<option value="0">beginners</option>"#)?;
+    writeln!(
+        html_file,
+        r#"<option value="1">intermediate users</option>"#
+    )?;
+    writeln!(html_file, r#"<option value="2">advanced users</option>"#)?;
+    writeln!(html_file, "</select>")?;
+    writeln!(
+        html_file,
+        "· Press <kbd>Ctrl</kbd>+<kbd>K</kbd> to jump to a combinator"
+    )?;
+    writeln!(html_file, "</nav>")
+}
+
+/// Buttons for the tags of `rows`, which `tags.js` shows only the rows with
+/// the picked one for. Sheets without tags don't get any.
+pub(crate) fn write_tag_bar(html_file: &mut impl Write, rows: &[Row]) -> Result<()> {
+    let tags = rows
+        .iter()
+        .flat_map(|row| &row.tags)
+        .collect::<BTreeSet<_>>();
+    if tags.is_empty() {
+        return Ok(());
+    }
+    writeln!(html_file, r#"<nav class="tags" aria-label="Tags">"#)?;
+    for tag in tags {
+        let tag = escape_html(tag);
+        writeln!(
+            html_file,
+            r#"<button type="button" data-tag="{tag}" aria-pressed="false">#{tag}</button>"#
+        )?;
+    }
+    writeln!(html_file, "</nav>")
+}
+
+/// The command palette that `palette.js` fills in and opens
+fn write_palette(html_file: &mut impl Write) -> Result<()> {
+    writeln!(html_file, r#"<dialog id="palette">"#)?;
+    writeln!(
+        html_file,
+        r#"<input type="search" placeholder="Jump to a section or combinator" aria-label="Jump to">"#
+    )?;
+    writeln!(html_file, r#"<ul role="listbox"></ul>"#)?;
+    writeln!(html_file, "</dialog>")
+}
+
+/// Text for HTML, also for in an attribute's quotes
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A title as text for HTML, without the backticks of code
+pub(crate) fn title_html(title: &str) -> String {
+    escape_html(&title.replace('`', ""))
+}
+
+/// Writes everything up to the start of the page's body
+pub(crate) fn write_page_start(html_file: &mut impl Write, title: &str) -> Result<()> {
+    let themeset = ThemeSet::load_defaults();
+    let dark_theme = &themeset.themes["Solarized (dark)"];
+    let css_dark = css_for_theme_with_class_style(dark_theme, ClassStyle::Spaced).unwrap();
+    let light_theme = &themeset.themes["Solarized (light)"];
+    let css_light = css_for_theme_with_class_style(light_theme, ClassStyle::Spaced).unwrap();
+
+    html_file.write_all(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <title>"#
+            .as_bytes(),
+    )?;
+    html_file.write_all(title.as_bytes())?;
+    html_file.write_all(
+        r#"</title>
+    <style>
+"#
+        .as_bytes(),
+    )?;
+    let css = format!(
+        "{}@media (prefers-color-scheme: dark) {{{css_dark}}}
+@media (prefers-color-scheme: light) {{{css_light}}}",
+        include_str!("github-markdown.css")
+    );
+    html_file.write_all(css_for_theme(&css, THEME).as_bytes())?;
+    html_file.write_all(
+        r#"
+
+.markdown-body {
+    margin: 0 auto;
+    padding: 45px;
+}
+
+@media (max-width: 767px) {
+    .markdown-body {
+        padding: 15px;
+    }
+}
+
+.downloads {
+    margin-top: 2em;
+    font-size: 0.9em;
+    text-align: center;
+}
+
+.provenance {
+    opacity: 0.7;
+}
+
+.timing {
+    opacity: 0.6;
+}
+
+.feature,
+.since,
+.deprecated {
+    padding: 0 0.3em;
+    border: 1px solid currentColor;
+    border-radius: 0.6em;
+    font-size: 0.7em;
+    opacity: 0.7;
+}
+
+.deprecated {
+    color: #d29922;
+}
+
+.print-only {
+    display: none;
+}
+
+@media print {
+    .print-only {
+        display: block;
+    }
+
+    .screen-only,
+    .copy-test,
+    .playground,
+    .edit,
+    .row-code,
+    .copy-usage,
+    .permalink {
+        display: none;
+    }
+}
+
+.permalink {
+    margin-right: 0.3em;
+    text-decoration: none;
+    opacity: 0;
+}
+
+tr:hover .permalink,
+.permalink:focus {
+    opacity: 0.6;
+}
+
+tr:has(a:target) {
+    outline: 2px solid #1f6feb;
+}
+
+.copy-usage {
+    margin-left: 0.3em;
+    font-size: 0.7em;
+    opacity: 0;
+    cursor: pointer;
+}
+
+td:hover .copy-usage,
+.copy-usage:focus {
+    opacity: 0.7;
+}
+
+.copy-test {
+    margin-left: 0.5em;
+    font-size: 0.8em;
+    opacity: 0.7;
+    cursor: pointer;
+}
+
+.playground,
+.edit {
+    margin-left: 0.5em;
+    font-size: 0.8em;
+    opacity: 0.7;
+}
+
+.row-code summary {
+    font-size: 0.8em;
+    opacity: 0.7;
+    cursor: pointer;
+}
+
+.row-code pre {
+    margin: 0.3em 0 0;
+    overflow-x: auto;
+}
+
+.editable-input {
+    cursor: text;
+}
+
+.editable-input:hover,
+.editable-input:focus {
+    outline: 1px dashed #1f6feb;
+}
+
+td.edited {
+    background: rgba(31, 111, 235, 0.1);
+}
+
+.levels {
+    text-align: right;
+    font-size: 0.9em;
+}
+
+#search {
+    float: left;
+}
+
+.search-miss,
+.tag-miss {
+    display: none;
+}
+
+.tags {
+    margin-top: 0.5em;
+    font-size: 0.9em;
+}
+
+.tags button {
+    margin: 0 0.3em 0.3em 0;
+    border: 1px solid rgba(127, 127, 127, 0.4);
+    border-radius: 1em;
+    background: none;
+    color: inherit;
+    cursor: pointer;
+}
+
+.tags button[aria-pressed="true"] {
+    background: rgba(31, 111, 235, 0.25);
+}
+
+#palette {
+    width: min(40em, 90vw);
+    padding: 0.5em;
+    border-radius: 6px;
+}
+
+#palette input {
+    width: 100%;
+    font-size: 1.1em;
+    box-sizing: border-box;
+}
+
+#palette ul {
+    max-height: 60vh;
+    overflow-y: auto;
+    margin: 0.5em 0 0;
+    padding: 0;
+    list-style: none;
+}
+
+#palette li {
+    padding: 0.2em 0.5em;
+    cursor: pointer;
+}
+
+#palette li[aria-selected="true"] {
+    background: rgba(127, 127, 127, 0.25);
+}
+
+#palette li[data-kind="section"]::after {
+    content: " (section)";
+    opacity: 0.6;
+}
+
+.sidebar ul {
+    padding-left: 1em;
+    list-style: none;
+}
+
+.sidebar [aria-current="page"] {
+    font-weight: 600;
+}
+
+@media (min-width: 1012px) {
+    body:has(> .sidebar) {
+        padding-left: calc(16em + 45px);
+    }
+
+    .sidebar {
+        position: fixed;
+        top: 45px;
+        left: 15px;
+        width: 15em;
+        max-height: calc(100vh - 90px);
+        overflow-y: auto;
+    }
+}
+
+@media print {
+    .sidebar {
+        display: none;
+    }
+}
+    </style>
+</head>
+<body class="markdown-body">
+"#
+        .as_bytes(),
+    )
+}
+
+/// Turns markdown into HTML, with the code highlighted by classes that the
+/// CSS of the page has colors for
+pub(crate) fn render(markdown: &str) -> String {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.header_ids = Some(String::new());
+    options.render.unsafe_ = true;
+    let mut plugins = Plugins::default();
+    let syntect = SyntectAdapterBuilder::new().css().build();
+    plugins.render.codefence_syntax_highlighter = Some(&syntect);
+    markdown_to_html_with_plugins(markdown, &options, &plugins)
+}
+
+/// Writes the command palette and the scripts that make the rows of the
+/// page interactive, which need the level selector, and ends the page
+pub(crate) fn write_page_end(html_file: &mut impl Write, rows: &[Row]) -> Result<()> {
+    write_palette(html_file)?;
+    writeln!(
+        html_file,
+        r#"<script type="application/json" id="search-index">{}</script>"#,
+        search_index(rows)
+    )?;
+    for script in [
+        include_str!("levels.js"),
+        include_str!("palette.js"),
+        include_str!("copy.js"),
+        include_str!("search.js"),
+        include_str!("tags.js"),
+        include_str!("edit.js"),
+    ] {
+        writeln!(html_file, "<script>\n{script}</script>")?;
+    }
+    html_file.write_all(
+        "</body>
+</html>
+"
+        .as_bytes(),
+    )
+}
+
+pub fn write_html(
+    html_path: &Path,
+    markdown: &str,
+    rows: &[Row],
+    artifacts: &[Artifact],
+    provenance: &str,
+) -> Result<()> {
+    let mut html_file = BufWriter::new(File::create(html_path)?);
+    write_page_start(&mut html_file, &title_html(TITLE))?;
+    html_file.write_all("<article>\n".as_bytes())?;
+    write_level_selector(&mut html_file)?;
+    write_tag_bar(&mut html_file, rows)?;
+    html_file.write_all(render(markdown).as_bytes())?;
+    html_file.write_all("</article>\n".as_bytes())?;
+    write_footer(
+        &mut html_file,
+        html_path,
+        "",
+        artifacts,
+        &footer_provenance(provenance),
+    )?;
+    write_page_end(&mut html_file, rows)?;
+    html_file.flush()
+}
+
+/// Links to the outputs in a directory of the landing page, by their label
+fn page_links(dir: &str, pages: &[(&str, &str)]) -> String {
+    pages
+        .iter()
+        .map(|(label, name)| format!(r#"<a href="{dir}/{name}">{label}</a>"#))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Writes a landing page that links to the cheatsheets for every version of
+/// nom, newest first, which is how the versions are expected to be sorted.
+/// Each version comes with the label and file name of the outputs that its
+/// directory has.
+pub fn write_index(index_path: &Path, versions: &[(String, Vec<(&str, &str)>)]) -> Result<()> {
+    let mut html_file = BufWriter::new(File::create(index_path)?);
+    let title = title_html(TITLE);
+    write_page_start(&mut html_file, &title)?;
+    html_file.write_all("<article>\n".as_bytes())?;
+    writeln!(html_file, "<h1>{title}</h1>")?;
+    writeln!(
+        html_file,
+        "<p>Pick the version of nom that you're using:</p>"
+    )?;
+    writeln!(html_file, "<ul>")?;
+    if let Some((newest, pages)) = versions.first() {
+        writeln!(
+            html_file,
+            "<li>Latest ({newest}): {}</li>",
+            page_links("latest", pages)
+        )?;
+    }
+    for (version, pages) in versions {
+        writeln!(
+            html_file,
+            "<li>nom {version}: {}</li>",
+            page_links(version, pages)
+        )?;
+    }
+    writeln!(html_file, "</ul>")?;
+    html_file.write_all(
+        "</article>
+</body>
+</html>
+"
+        .as_bytes(),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_footer() {
+        let html_path = Path::new("dist/nom-cheatsheet.html");
+        let artifacts = [
+            Artifact {
+                label: "Markdown",
+                path: PathBuf::from("dist/nom-cheatsheet.md"),
+            },
+            Artifact {
+                label: "HTML",
+                path: html_path.to_path_buf(),
+            },
+        ];
+        let mut footer = Vec::new();
+        write_footer(&mut footer, html_path, "", &artifacts, "nom 7.1.3").unwrap();
+        let footer = String::from_utf8(footer).unwrap();
+        assert!(footer.contains(r#"<a href="nom-cheatsheet.md">Markdown</a>"#));
+        assert!(!footer.contains("nom-cheatsheet.html"));
+        assert!(footer.contains("nom 7.1.3"));
+
+        let mut footer = Vec::new();
+        write_footer(&mut footer, html_path, "", &artifacts[1..], "nom 7.1.3").unwrap();
+        let footer = String::from_utf8(footer).unwrap();
+        assert!(!footer.contains("Also available as"));
+
+        let mut footer = Vec::new();
+        let page_path = Path::new("dist/site/index.html");
+        write_footer(&mut footer, page_path, "../", &artifacts, "nom 7.1.3").unwrap();
+        let footer = String::from_utf8(footer).unwrap();
+        assert!(footer.contains(r#"<a href="../nom-cheatsheet.html">HTML</a>"#));
+    }
+
+    #[test]
+    fn test_css_for_theme() {
+        let css =
+            "@media (prefers-color-scheme: dark) {a{}}\n@media (prefers-color-scheme: light) {b{}}";
+        assert_eq!(css_for_theme(css, "auto"), css);
+        assert_eq!(
+            css_for_theme(css, "dark"),
+            "@media all {a{}}\n@media not all {b{}}"
+        );
+        assert_eq!(
+            css_for_theme(css, "light"),
+            "@media not all {a{}}\n@media all {b{}}"
+        );
+    }
+
+    #[test]
+    fn test_search_index() {
+        let row = Row {
+            id: 3,
+            combinators: vec![crate::sheet::Link {
+                module: "branch",
+                name: "alt",
+                url: "https://docs.rs/nom/latest/nom/branch/fn.alt.html".into(),
+                summary: None,
+                signature: None,
+            }],
+            usage: "alt((tag(\"a\"), tag(\"b\")))",
+            inputs: vec!["\"a\""],
+            description: "Tries [each](#x) parser, even `</script>`",
+            tags: vec!["choice"],
+            ..Row::for_test("Choice combinators")
+        };
+        assert_eq!(
+            search_index(std::slice::from_ref(&row)),
+            r#"[{"row":3,"combinators":["nom::branch::alt"],"description":"Tries each parser, even `<\/script>`","tags":["choice"]}]"#
+        );
+
+        let mut bar = Vec::new();
+        write_tag_bar(&mut bar, &[row.clone(), row]).unwrap();
+        let bar = String::from_utf8(bar).unwrap();
+        assert_eq!(bar.matches("<button").count(), 1);
+        assert!(bar.contains(r#"data-tag="choice""#));
+        let row = Row {
+            tags: vec!["a\"<b"],
+            ..Row::for_test("Choice combinators")
+        };
+        let mut bar = Vec::new();
+        write_tag_bar(&mut bar, &[row]).unwrap();
+        let bar = String::from_utf8(bar).unwrap();
+        assert!(bar.contains(r#"data-tag="a&quot;&lt;b" aria-pressed="false">#a&quot;&lt;b<"#));
+        let mut bar = Vec::new();
+        write_tag_bar(&mut bar, &[]).unwrap();
+        assert!(bar.is_empty());
+    }
+}
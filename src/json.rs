@@ -0,0 +1,162 @@
+//! Writes the rows of the cheatsheet as JSON, for tools that build on its
+//! data. Unlike the JSON API, nothing in there changes from run to run, so
+//! the file can be checked in and diffed. Paths are written in full, like
+//! `nom::bytes::complete::tag`.
+//!
+//! The shape of the file is versioned with `schema_version`. Adding fields
+//! keeps the version, anything that could break a reader bumps it.
+
+use crate::{hover::full_path, llms::plain, sheet::Row, versions::NOM_VERSION};
+use serde::Serialize;
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{BufWriter, Result, Write},
+    path::Path,
+};
+
+const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Document {
+    schema_version: u32,
+    nom_version: &'static str,
+    /// In the order of the sheet
+    rows: Vec<Entry>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Entry {
+    section: &'static str,
+    combinators: Vec<Combinator>,
+    usage: &'static str,
+    inputs: Vec<&'static str>,
+    /// What running the example gave, as plain text
+    output: String,
+    /// Markdown, with links that only work within the sheet left out
+    description: String,
+    /// A `#[test]` function that checks what the example gives, for rows
+    /// that have one
+    test: Option<String>,
+    /// A program that prints what the example gives, for the rows that can
+    /// run on the Rust Playground
+    program: Option<&'static str>,
+    /// The equivalents in winnow, with paths like `winnow::token::literal`
+    winnow: Vec<Combinator>,
+    /// What the row is for, like `numbers`
+    tags: Vec<&'static str>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Combinator {
+    path: String,
+    docs_url: Cow<'static, str>,
+}
+
+fn document(rows: &[Row]) -> Document {
+    Document {
+        schema_version: SCHEMA_VERSION,
+        nom_version: NOM_VERSION,
+        rows: rows
+            .iter()
+            .map(|row| Entry {
+                section: row.section,
+                combinators: row
+                    .combinators
+                    .iter()
+                    .map(|link| Combinator {
+                        path: full_path(link),
+                        docs_url: link.url.clone(),
+                    })
+                    .collect(),
+                usage: row.usage,
+                inputs: row.inputs.clone(),
+                output: plain(&row.output),
+                description: plain(row.description),
+                test: row.test.clone(),
+                program: row.program,
+                winnow: row
+                    .winnow
+                    .iter()
+                    .map(|link| Combinator {
+                        path: format!("winnow::{}::{}", link.module, link.name),
+                        docs_url: link.url.clone(),
+                    })
+                    .collect(),
+                tags: row.tags.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Writes the rows to `path` as JSON
+pub fn write_json(path: &Path, rows: &[Row]) -> Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut file, &document(rows))?;
+    writeln!(file)?;
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet::{Level, Link};
+
+    #[test]
+    fn test_document() {
+        let row = Row {
+            id: 0,
+            section: "Basic elements",
+            combinators: vec![Link {
+                module: "character::complete",
+                name: "alpha1",
+                url: "https://docs.rs/nom/latest/nom/character/complete/fn.alpha1.html".into(),
+                summary: None,
+                signature: None,
+            }],
+            usage: "alpha1",
+            inputs: vec!["\"abc123\""],
+            output: "Result: `\"abc\"`<br>Remainder: `\"123\"`".to_string(),
+            description: "Matches [`alpha`](#x) characters",
+            anchor: "",
+            level: Level::Beginner,
+            elapsed_ns: 1234,
+            test: Some("#[test]\nfn alpha1() {}\n".to_string()),
+            program: Some("fn main() {}\n"),
+            code: "",
+            edit: None,
+            winnow: vec![Link {
+                module: "ascii",
+                name: "alpha1",
+                url: "https://docs.rs/winnow/latest/winnow/ascii/fn.alpha1.html".into(),
+                summary: None,
+                signature: None,
+            }],
+            tags: vec!["letters"],
+            offset: 0,
+        };
+        let document = document(&[row]);
+        assert_eq!(
+            document.rows,
+            [Entry {
+                section: "Basic elements",
+                combinators: vec![Combinator {
+                    path: "nom::character::complete::alpha1".to_string(),
+                    docs_url: "https://docs.rs/nom/latest/nom/character/complete/fn.alpha1.html"
+                        .into(),
+                }],
+                usage: "alpha1",
+                inputs: vec!["\"abc123\""],
+                output: "Result: `\"abc\"`\nRemainder: `\"123\"`".to_string(),
+                description: "Matches `alpha` characters".to_string(),
+                test: Some("#[test]\nfn alpha1() {}\n".to_string()),
+                program: Some("fn main() {}\n"),
+                winnow: vec![Combinator {
+                    path: "winnow::ascii::alpha1".to_string(),
+                    docs_url: "https://docs.rs/winnow/latest/winnow/ascii/fn.alpha1.html".into(),
+                }],
+                tags: vec!["letters"],
+            }]
+        );
+    }
+}
@@ -0,0 +1,30 @@
+// Hides the rows that are above the level picked in the level selector.
+// Rows carry their level in an HTML comment like `<!-- level = advanced -->`,
+// and rows without one are for intermediate users.
+(() => {
+    const levels = ["beginner", "intermediate", "advanced"];
+    const select = document.getElementById("level");
+    const rows = new Map();
+    const comments = document.createTreeWalker(
+        document.querySelector("article"),
+        NodeFilter.SHOW_COMMENT,
+    );
+    while (comments.nextNode()) {
+        const match = comments.currentNode.data.match(/level = (\w+)/);
+        const row = comments.currentNode.parentElement.closest("tr");
+        if (match && row) {
+            rows.set(row, levels.indexOf(match[1]));
+        }
+    }
+    const show = (level) => {
+        for (const row of document.querySelectorAll("article tbody tr")) {
+            row.hidden = (rows.get(row) ?? 1) > level;
+        }
+    };
+    select.value = localStorage.getItem("nom-cheatsheet-level") ?? "1";
+    show(Number(select.value));
+    select.addEventListener("change", () => {
+        localStorage.setItem("nom-cheatsheet-level", select.value);
+        show(Number(select.value));
+    });
+})();
@@ -0,0 +1,649 @@
+//! Checks that the links to docs.rs in the sheet go to pages that exist.
+//! The build script works out a combinator's page from its path, and the
+//! kind of item from the case of its name, which can get it wrong. The links
+//! either get fetched, which needs curl, or get checked against nom's
+//! rustdoc JSON without going online. That JSON is also where the build
+//! script's list of the kinds of nom's items comes from.
+
+use crate::{llms::plain, seed::build_rustdoc_json, sheet::NOM_DOCS, versions::NOM_VERSION};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::Write,
+    fs,
+    io::{self, Result},
+    path::Path,
+    process::{Command, ExitCode},
+};
+
+/// The kinds of nom's items and the signatures of its functions, as
+/// `cargo run -- update-items` last wrote them
+static NOM_ITEMS: &str = include_str!("../build/nom-items.txt");
+static NOM_SIGNATURES: &str = include_str!("../build/nom-signatures.txt");
+
+/// The first part of the page names of rustdoc, like `fn.tag.html`, by the
+/// kind of item in rustdoc JSON
+static PAGE_KINDS: &[(&str, &str)] = &[
+    ("function", "fn"),
+    ("enum", "enum"),
+    ("struct", "struct"),
+    ("trait", "trait"),
+    ("type_alias", "type"),
+    ("macro", "macro"),
+    ("constant", "constant"),
+    ("static", "static"),
+];
+
+/// The docs.rs links in some markdown, each once
+fn docs_links(markdown: &str) -> BTreeSet<&str> {
+    markdown
+        .split("](")
+        .skip(1)
+        .filter_map(|rest| rest.split(')').next())
+        .filter(|url| url.starts_with("https://docs.rs/"))
+        .collect()
+}
+
+/// The item in rustdoc JSON's index with `id`, which newer versions of
+/// rustdoc write as a number
+fn lookup<'a>(index: &'a Map<String, Value>, id: &Value) -> Option<&'a Value> {
+    match id {
+        Value::String(id) => index.get(id),
+        id => index.get(&id.to_string()),
+    }
+}
+
+/// Adds the public items in the module with `id` to `items`, with their path
+/// and their page under `modules`, which is where the module's items show up
+/// in the docs. Private modules don't get pages, so what they `pub use` shows
+/// up in the module that does that instead, and the same goes for private
+/// items.
+fn module_items<'a>(
+    index: &'a Map<String, Value>,
+    id: &Value,
+    modules: &[&str],
+    items: &mut Vec<(String, String, &'a Value)>,
+) {
+    let Some(ids) =
+        lookup(index, id).and_then(|module| module["inner"]["module"]["items"].as_array())
+    else {
+        return;
+    };
+    for id in ids {
+        let Some(item) = lookup(index, id) else {
+            continue;
+        };
+        if item["visibility"] != "public" {
+            continue;
+        }
+        let (item, name) = match item["inner"].get("use") {
+            Some(reexport) => match lookup(index, &reexport["id"]) {
+                // The items of a private module that's glob imported are
+                // where the import is
+                Some(target) if reexport["is_glob"] == true && target["visibility"] != "public" => {
+                    module_items(index, &target["id"], modules, items);
+                    continue;
+                }
+                Some(target) if target["visibility"] != "public" => {
+                    (target, reexport["name"].as_str())
+                }
+                _ => continue,
+            },
+            None => (item, item["name"].as_str()),
+        };
+        let Some(name) = name else {
+            continue;
+        };
+        if item["inner"].get("module").is_some() {
+            module_items(index, &item["id"], &[modules, &[name]].concat(), items);
+            continue;
+        }
+        let Some((inner, kind)) = PAGE_KINDS
+            .iter()
+            .find(|(inner, _)| item["inner"].get(*inner).is_some())
+        else {
+            continue;
+        };
+        let mut page = String::new();
+        for module in modules {
+            page.push_str(module);
+            page.push('/');
+        }
+        page.push_str(&format!("{kind}.{name}.html"));
+        let path = [modules, &[name]].concat().join("::");
+        let methods = match *inner {
+            "trait" => item["inner"]["trait"]["items"].as_array(),
+            _ => None,
+        };
+        for method in methods.into_iter().flatten() {
+            let Some(method) = lookup(index, method) else {
+                continue;
+            };
+            let Some(name) = method["name"].as_str() else {
+                continue;
+            };
+            items.push((
+                format!("{path}::{name}"),
+                format!("{page}#method.{name}"),
+                method,
+            ));
+        }
+        items.push((path, page, item));
+    }
+}
+
+/// The public items in nom's rustdoc JSON, with the path and the page that
+/// they're documented at, and the methods of traits on their trait's page
+fn rustdoc_items(rustdoc: &Value) -> Vec<(String, String, &Value)> {
+    let Some(index) = rustdoc["index"].as_object() else {
+        return Vec::new();
+    };
+    let mut items = Vec::new();
+    module_items(index, &rustdoc["root"], &[], &mut items);
+    items
+}
+
+/// The pages of nom's docs that exist, relative to `NOM_DOCS` and with the
+/// methods of traits as anchors, by the path of the item they're for
+pub(crate) fn rustdoc_pages(rustdoc: &Value) -> HashMap<String, String> {
+    rustdoc_items(rustdoc)
+        .into_iter()
+        .map(|(path, page, _)| (path, page))
+        .collect()
+}
+
+/// A type that has nothing generic in it, like `usize` or `&[u8]`, the way
+/// it's written in Rust
+fn concrete_type(ty: &Value) -> Option<String> {
+    if let Some(primitive) = ty["primitive"].as_str() {
+        return Some(primitive.to_string());
+    }
+    if let Some(path) = ty.get("resolved_path") {
+        let generic = match &path["args"]["angle_bracketed"]["args"] {
+            Value::Array(args) => !args.is_empty(),
+            _ => !path["args"].is_null(),
+        };
+        let name = path["path"].as_str().or(path["name"].as_str())?;
+        return (!generic).then(|| name.rsplit("::").next().unwrap().to_string());
+    }
+    if let Some(reference) = ty.get("borrowed_ref") {
+        let mutability = if reference["is_mutable"] == true {
+            "mut "
+        } else {
+            ""
+        };
+        return Some(format!(
+            "&{mutability}{}",
+            concrete_type(&reference["type"])?
+        ));
+    }
+    if let Some(element) = ty.get("slice") {
+        return Some(format!("[{}]", concrete_type(element)?));
+    }
+    if let Some(elements) = ty["tuple"].as_array() {
+        let elements = elements
+            .iter()
+            .map(concrete_type)
+            .collect::<Option<Vec<_>>>()?;
+        return Some(format!("({})", elements.join(", ")));
+    }
+    None
+}
+
+/// The signature of a function in rustdoc JSON as the sheet shows it, like
+/// `fn separated_pair(first, sep, second)`. Parameters only get their type
+/// if it has nothing generic in it, as the bounds of generic ones are what
+/// the docs are for, and what the function returns is mostly a parser.
+fn signature(name: &str, function: &Value) -> Option<String> {
+    // Older versions of rustdoc JSON call the signature `decl`
+    let inputs = function["sig"]["inputs"]
+        .as_array()
+        .or(function["decl"]["inputs"].as_array())?;
+    let parameters = inputs
+        .iter()
+        .map(|input| {
+            let name = input[0].as_str().unwrap_or("_");
+            let ty = &input[1];
+            match (name, concrete_type(ty)) {
+                ("self", _) => match &ty["borrowed_ref"] {
+                    Value::Null => "self".to_string(),
+                    reference if reference["is_mutable"] == true => "&mut self".to_string(),
+                    _ => "&self".to_string(),
+                },
+                (name, Some(ty)) => format!("{name}: {ty}"),
+                (name, None) => name.to_string(),
+            }
+        })
+        .collect::<Vec<_>>();
+    Some(format!("fn {name}({})", parameters.join(", ")))
+}
+
+/// The signatures of nom's functions as the build script reads them, a line
+/// like `bytes::complete::tag fn tag(tag)` per function, sorted by path,
+/// after a line with the version of nom
+fn signatures(rustdoc: &Value) -> String {
+    let signatures = rustdoc_items(rustdoc)
+        .into_iter()
+        .filter_map(|(path, _, item)| {
+            let name = path.rsplit("::").next()?;
+            let signature = signature(name, item["inner"].get("function")?)?;
+            Some((path, signature))
+        })
+        .collect::<BTreeMap<_, _>>();
+    let mut text = format!("# nom {NOM_VERSION}\n");
+    for (path, signature) in signatures {
+        writeln!(text, "{path} {signature}").unwrap();
+    }
+    text
+}
+
+/// The first paragraph of an item's docs as plain text on one line, for a
+/// tooltip. Intra-doc links like `[context]` lose their brackets, as other
+/// links lose their targets.
+fn summary(docs: &str) -> Option<String> {
+    let plain = plain(docs.split("\n\n").next()?);
+    let mut summary = String::new();
+    let mut rest = plain.as_str();
+    while let Some(start) = rest.find('[') {
+        summary.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let link = after.find(']').map(|end| &after[..end]).filter(|text| {
+            !text.is_empty()
+                && !summary.ends_with('&')
+                && text
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || "_:`()".contains(c))
+        });
+        match link {
+            Some(text) => {
+                summary.push_str(text);
+                rest = &after[text.len() + 1..];
+            }
+            None => {
+                summary.push('[');
+                rest = after;
+            }
+        }
+    }
+    summary.push_str(rest);
+    let summary = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!summary.is_empty()).then_some(summary)
+}
+
+/// The summaries of the docs of nom's items as the build script reads them, a
+/// line like `bytes::complete::tag Recognizes a pattern.` per item that has
+/// docs, sorted by path, after a line with the version of nom
+fn summaries(rustdoc: &Value) -> String {
+    let summaries = rustdoc_items(rustdoc)
+        .into_iter()
+        .filter_map(|(path, _, item)| Some((path, summary(item["docs"].as_str()?)?)))
+        .collect::<BTreeMap<_, _>>();
+    let mut text = format!("# nom {NOM_VERSION}\n");
+    for (path, summary) in summaries {
+        writeln!(text, "{path} {summary}").unwrap();
+    }
+    text
+}
+
+/// The features of nom that an item in rustdoc JSON is only there with, like
+/// `alloc` for `#[cfg(feature = "alloc")]`. Newer versions of rustdoc write
+/// that as `CfgTrace([NameValue { name: "feature", value: Some("alloc"), ...`
+/// instead of the way it's written in the code.
+fn item_features(item: &Value) -> Vec<String> {
+    let mut features = Vec::new();
+    for attr in item["attrs"].as_array().into_iter().flatten() {
+        let Some(attr) = attr.as_str().or(attr["other"].as_str()) else {
+            continue;
+        };
+        if !attr.contains("cfg") && !attr.contains("Cfg") {
+            continue;
+        }
+        for pattern in ["feature = \"", "name: \"feature\", value: Some(\""] {
+            for (start, _) in attr.match_indices(pattern) {
+                let rest = &attr[start + pattern.len()..];
+                let Some(feature) = rest.split('"').next() else {
+                    continue;
+                };
+                if !features.iter().any(|known| known == feature) {
+                    features.push(feature.to_string());
+                }
+            }
+        }
+    }
+    features
+}
+
+/// The features that nom's items need as the build script reads them, a line
+/// like `multi::many0 alloc` per item that needs any, sorted by path, after a
+/// line with the version of nom
+fn features(rustdoc: &Value) -> String {
+    let features = rustdoc_items(rustdoc)
+        .into_iter()
+        .map(|(path, _, item)| (path, item_features(item)))
+        .filter(|(_, features)| !features.is_empty())
+        .collect::<BTreeMap<_, _>>();
+    let mut text = format!("# nom {NOM_VERSION}\n");
+    for (path, features) in features {
+        writeln!(text, "{path} {}", features.join(" ")).unwrap();
+    }
+    text
+}
+
+/// Checks `links` against the pages in nom's rustdoc JSON, for nom's docs at
+/// `docs`, returning the broken ones with the page that the item does have,
+/// if it's there under a different kind. Links to other crates can't be
+/// checked this way, and get counted.
+fn check_offline<'a>(
+    links: &BTreeSet<&'a str>,
+    pages: &HashMap<String, String>,
+    docs: &str,
+) -> (Vec<(&'a str, Option<String>)>, usize) {
+    let existing = pages.values().map(String::as_str).collect::<HashSet<_>>();
+    let mut broken = Vec::new();
+    let mut unchecked = 0;
+    for link in links {
+        let Some(page) = link.strip_prefix(docs) else {
+            unchecked += 1;
+            continue;
+        };
+        if page.is_empty() || page.ends_with("index.html") || existing.contains(page) {
+            continue;
+        }
+        // `bytes/complete/fn.tag.html` is the page of `bytes::complete::tag`
+        let (file, anchor) = page.split_once('#').unwrap_or((page, ""));
+        let (modules, file) = file.rsplit_once('/').unwrap_or(("", file));
+        let name = file
+            .strip_suffix(".html")
+            .and_then(|file| file.split_once('.'))
+            .map_or(file, |(_, name)| name);
+        let mut path = modules.replace('/', "::");
+        if !path.is_empty() {
+            path.push_str("::");
+        }
+        path.push_str(name);
+        if let Some(method) = anchor.strip_prefix("method.") {
+            path.push_str(&format!("::{method}"));
+        }
+        let right = pages.get(&path).map(|page| format!("{docs}{page}"));
+        broken.push((*link, right));
+    }
+    (broken, unchecked)
+}
+
+/// The kinds of nom's items as the build script reads them, a line like
+/// `fn bytes::complete::tag` per item, sorted by path, after a line with the
+/// version of nom that they're from
+fn item_kinds(pages: &HashMap<String, String>) -> String {
+    let mut items = pages
+        .iter()
+        .filter(|(_, page)| !page.contains('#'))
+        .filter_map(|(path, page)| {
+            let file = page.rsplit('/').next()?;
+            Some((path, file.split('.').next()?))
+        })
+        .collect::<Vec<_>>();
+    items.sort();
+    let mut kinds = format!("# nom {NOM_VERSION}\n");
+    for (path, kind) in items {
+        writeln!(kinds, "{kind} {path}").unwrap();
+    }
+    kinds
+}
+
+/// The paths of nom's items, with the methods of its traits, in the version
+/// of nom that the sheet is built with
+pub(crate) fn nom_item_paths() -> HashSet<&'static str> {
+    let items = NOM_ITEMS
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| Some(line.split_once(' ')?.1));
+    let functions = NOM_SIGNATURES
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| Some(line.split_once(' ')?.0));
+    items.chain(functions).collect()
+}
+
+/// Writes the kinds of nom's items to `build/nom-items.txt`, for the build
+/// script to link to the right docs pages with, and the signatures of its
+/// functions to `build/nom-signatures.txt`, for the `signature` column, and
+/// the summaries of their docs to `build/nom-summaries.txt`, for the tooltips
+/// of the HTML, and the features that they need to `build/nom-features.txt`,
+/// for the badges next to them. The rustdoc JSON they come from gets built
+/// with a nightly toolchain if it isn't given.
+pub fn update_items(rustdoc_json: Option<&Path>) -> Result<ExitCode> {
+    let rustdoc_json = match rustdoc_json {
+        Some(path) => path.to_path_buf(),
+        None => build_rustdoc_json()?,
+    };
+    let rustdoc: Value = serde_json::from_str(&fs::read_to_string(rustdoc_json)?)?;
+    let kinds = item_kinds(&rustdoc_pages(&rustdoc));
+    let build_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("build");
+    let path = build_dir.join("nom-items.txt");
+    fs::write(&path, &kinds)?;
+    println!(
+        "Wrote the kinds of {} items to {}",
+        kinds.lines().count() - 1,
+        path.display()
+    );
+    let signatures = signatures(&rustdoc);
+    let path = build_dir.join("nom-signatures.txt");
+    fs::write(&path, &signatures)?;
+    println!(
+        "Wrote the signatures of {} functions to {}",
+        signatures.lines().count() - 1,
+        path.display()
+    );
+    let summaries = summaries(&rustdoc);
+    let path = build_dir.join("nom-summaries.txt");
+    fs::write(&path, &summaries)?;
+    println!(
+        "Wrote the summaries of {} items to {}",
+        summaries.lines().count() - 1,
+        path.display()
+    );
+    let features = features(&rustdoc);
+    let path = build_dir.join("nom-features.txt");
+    fs::write(&path, &features)?;
+    println!(
+        "Wrote the features of {} items to {}",
+        features.lines().count() - 1,
+        path.display()
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Fetches `link` with curl, following redirects, and returns whether that
+/// works out. docs.rs doesn't know about anchors, so the methods of traits
+/// only get their trait's page checked.
+fn fetches(link: &str) -> Result<bool> {
+    let output = Command::new("curl")
+        .args(["--silent", "--location", "--output", "/dev/null"])
+        .args(["--write-out", "%{http_code}", link])
+        .output();
+    let output = match output {
+        Ok(output) => output,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(io::Error::other(
+                "Checking the links online needs curl, which isn't installed. Give nom's \
+                 rustdoc JSON to check them offline.",
+            ))
+        }
+        Err(e) => return Err(e),
+    };
+    Ok(String::from_utf8_lossy(&output.stdout) == "200")
+}
+
+/// Checks the docs.rs links in `markdown`, against `rustdoc`, nom's rustdoc
+/// JSON, if that's given, and by fetching them otherwise. Returns the
+/// messages for the broken ones.
+pub fn check_links(markdown: &str, rustdoc: Option<&Value>) -> Result<Vec<String>> {
+    let links = docs_links(markdown);
+    let Some(rustdoc) = rustdoc else {
+        println!("Fetching {} links to docs.rs", links.len());
+        let mut broken = Vec::new();
+        for link in links {
+            if !fetches(link)? {
+                broken.push(format!("{link} doesn't exist"));
+            }
+        }
+        return Ok(broken);
+    };
+    let (broken, unchecked) = check_offline(&links, &rustdoc_pages(rustdoc), NOM_DOCS);
+    if unchecked > 0 {
+        println!("{unchecked} links to crates other than nom aren't checked offline");
+    }
+    Ok(broken
+        .into_iter()
+        .map(|(link, right)| match right {
+            Some(right) => format!("{link} doesn't exist, the docs are at {right}"),
+            None => format!("{link} doesn't exist"),
+        })
+        .collect())
+}
+
+/// Checks the docs.rs links in `markdown` like [`check_links`], reading
+/// nom's rustdoc JSON from `rustdoc_json` if that's given, and prints the
+/// broken ones. Returns whether there weren't any.
+pub fn report_links(markdown: &str, rustdoc_json: Option<&Path>) -> Result<bool> {
+    let rustdoc = match rustdoc_json {
+        Some(path) => Some(serde_json::from_str::<Value>(&fs::read_to_string(path)?)?),
+        None => None,
+    };
+    let broken = check_links(markdown, rustdoc.as_ref())?;
+    for message in &broken {
+        println!("{message}");
+    }
+    if broken.is_empty() {
+        println!("All links to docs.rs go to pages that exist");
+    }
+    Ok(broken.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_docs_links() {
+        let markdown =
+            "bytes::complete::[tag](https://docs.rs/nom/latest/nom/bytes/complete/fn.tag.html) \
+             and [`tag`](#nom-bytes-complete-tag) and \
+             [again](https://docs.rs/nom/latest/nom/bytes/complete/fn.tag.html)";
+        assert_eq!(
+            docs_links(markdown).into_iter().collect::<Vec<_>>(),
+            ["https://docs.rs/nom/latest/nom/bytes/complete/fn.tag.html"]
+        );
+    }
+
+    #[test]
+    fn test_check_offline() {
+        let rustdoc = json!({
+            "root": 0,
+            "index": {
+                "0": { "id": 0, "name": "nom", "visibility": "public", "inner": { "module": { "items": [1, 3, 4, 9], "is_stripped": false } } },
+                "1": { "id": 1, "name": "bytes", "visibility": "public", "inner": { "module": { "items": [2], "is_stripped": false } } },
+                "2": { "id": 2, "name": "tag", "visibility": "public", "inner": { "function": {} } },
+                "3": { "id": 3, "name": "error", "visibility": "public", "inner": { "module": { "items": [5], "is_stripped": false } } },
+                "4": { "id": 4, "name": null, "visibility": "public", "inner": { "use": { "name": "internal", "id": 6, "is_glob": true } } },
+                "5": { "id": 5, "name": "Error", "visibility": "public", "inner": { "struct": {} } },
+                "6": { "id": 6, "name": "internal", "visibility": "crate", "inner": { "module": { "items": [7], "is_stripped": true } } },
+                "7": { "id": 7, "name": "Parser", "visibility": "public", "inner": { "trait": { "items": [8] } } },
+                "8": { "id": 8, "name": "map", "visibility": "default", "inner": { "function": {} } },
+                "9": { "id": 9, "name": "hidden", "visibility": "crate", "inner": { "function": {} } },
+            },
+        });
+        let pages = rustdoc_pages(&rustdoc);
+        assert_eq!(pages["Parser::map"], "trait.Parser.html#method.map");
+        assert!(!pages.contains_key("hidden"));
+        assert_eq!(
+            item_kinds(&pages),
+            format!(
+                "# nom {NOM_VERSION}\n\
+                 trait Parser\n\
+                 fn bytes::tag\n\
+                 struct error::Error\n"
+            )
+        );
+        let links = BTreeSet::from([
+            "https://docs.rs/nom/latest/nom/bytes/fn.tag.html",
+            "https://docs.rs/nom/latest/nom/trait.Parser.html#method.map",
+            "https://docs.rs/nom/latest/nom/trait.Parser.html#method.gone",
+            "https://docs.rs/nom/latest/nom/error/enum.Error.html",
+            "https://docs.rs/nom-supreme/latest/nom_supreme/tag/complete/fn.tag.html",
+        ]);
+        assert_eq!(
+            check_offline(&links, &pages, "https://docs.rs/nom/latest/nom/"),
+            (
+                vec![
+                    (
+                        "https://docs.rs/nom/latest/nom/error/enum.Error.html",
+                        Some("https://docs.rs/nom/latest/nom/error/struct.Error.html".to_string())
+                    ),
+                    (
+                        "https://docs.rs/nom/latest/nom/trait.Parser.html#method.gone",
+                        None
+                    ),
+                ],
+                1
+            )
+        );
+    }
+
+    #[test]
+    fn test_signatures() {
+        let generic = |name| json!({ "generic": name });
+        let rustdoc = json!({
+            "root": 0,
+            "index": {
+                "0": { "id": 0, "name": "nom", "visibility": "public", "inner": { "module": { "items": [1, 2, 3], "is_stripped": false } } },
+                "1": { "id": 1, "name": "count", "visibility": "public", "inner": { "function": { "sig": { "inputs": [["f", generic("F")], ["count", { "primitive": "usize" }]] } } } },
+                "2": { "id": 2, "name": "hex_u32", "visibility": "public", "inner": { "function": { "sig": { "inputs": [["input", { "borrowed_ref": { "is_mutable": false, "type": { "slice": { "primitive": "u8" } } } }]] } } } },
+                "3": { "id": 3, "name": "Parser", "visibility": "public", "inner": { "trait": { "items": [4, 5] } } },
+                "4": { "id": 4, "name": "parse", "visibility": "default", "inner": { "function": { "sig": { "inputs": [["self", { "borrowed_ref": { "is_mutable": true, "type": generic("Self") } }], ["input", generic("I")]] } } } },
+                "5": { "id": 5, "name": "map", "visibility": "default", "inner": { "function": { "decl": { "inputs": [["self", generic("Self")], ["g", { "resolved_path": { "path": "Box", "args": { "angle_bracketed": { "args": [{ "type": generic("G") }] } } } }]] } } } },
+            },
+        });
+        assert_eq!(
+            signatures(&rustdoc),
+            format!(
+                "# nom {NOM_VERSION}\n\
+                 Parser::map fn map(self, g)\n\
+                 Parser::parse fn parse(&mut self, input)\n\
+                 count fn count(f, count: usize)\n\
+                 hex_u32 fn hex_u32(input: &[u8])\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(
+            summary("Helper trait for the [alt()] combinator,\nlike [`tag`](fn.tag.html).\n\nMore"),
+            Some("Helper trait for the alt() combinator, like `tag`.".to_string())
+        );
+        assert_eq!(
+            summary("Takes a `&[u8]`"),
+            Some("Takes a `&[u8]`".to_string())
+        );
+        assert_eq!(summary(""), None);
+    }
+
+    #[test]
+    fn test_features() {
+        let rustdoc = json!({
+            "root": 0,
+            "index": {
+                "0": { "id": 0, "name": "nom", "visibility": "public", "inner": { "module": { "items": [1, 2, 3], "is_stripped": false } } },
+                "1": { "id": 1, "name": "many0", "visibility": "public", "attrs": ["#[cfg(feature = \"alloc\")]"], "inner": { "function": {} } },
+                "2": { "id": 2, "name": "dbg_dmp", "visibility": "public", "attrs": [{ "other": "#[attr = CfgTrace([NameValue { name: \"feature\", value: Some(\"std\"), span: src/error.rs:588:7: 588:22 (#0) }])]" }], "inner": { "function": {} } },
+                "3": { "id": 3, "name": "tag", "visibility": "public", "attrs": ["#[inline]"], "inner": { "function": {} } },
+            },
+        });
+        assert_eq!(
+            features(&rustdoc),
+            format!("# nom {NOM_VERSION}\ndbg_dmp std\nmany0 alloc\n")
+        );
+    }
+}
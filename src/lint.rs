@@ -0,0 +1,765 @@
+//! Checks the prose of a template, the preambles and the description cells,
+//! for spelling, terminology, and capitalization. Code spans and code blocks
+//! are left out, those get checked by the compiler instead. The rows of the
+//! tables get checked for mistakes that the build script would trip over, or
+//! let through.
+
+use crate::{
+    links::nom_item_paths,
+    migrate::split_cells,
+    versions::{parse_version, NOM_VERSION},
+};
+use nom_cheatsheet_shared::{expand_includes, split_front_matter, Expanded};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    io::{self, Result, Write},
+    path::Path,
+    process::{Command, ExitCode, Stdio},
+};
+
+/// Words that are spelled right for this sheet but aren't in a regular
+/// dictionary. The capitalization counts for headings, so names like `nom`
+/// can start one without a capital letter.
+static DICTIONARY: &str = include_str!("dictionary.txt");
+
+/// What the sheet calls things, with the other ways of saying it that should
+/// be avoided
+static TERMINOLOGY: &[(&str, &[&str])] = &[(
+    "remainder",
+    &["rest of the input", "leftover input", "unparsed input"],
+)];
+
+#[derive(Debug, PartialEq)]
+enum Prose {
+    Heading(String),
+    Description(String),
+    Text(String),
+}
+
+impl Prose {
+    fn text(&self) -> &str {
+        match self {
+            Prose::Heading(text) | Prose::Description(text) | Prose::Text(text) => text,
+        }
+    }
+}
+
+/// Whether `line` is the line under the column names of a table, like
+/// `|---|:---:|`
+fn is_separator(line: &str) -> bool {
+    split_cells(line.trim_end()).is_some_and(|cells| {
+        cells.iter().all(|cell| {
+            let dashes = cell.trim().trim_start_matches(':').trim_end_matches(':');
+            !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+        })
+    })
+}
+
+/// The names of the columns of a table, from the line above its separator
+fn column_names(line: &str) -> Vec<String> {
+    split_cells(line.trim_end())
+        .unwrap_or_default()
+        .iter()
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+/// The prose in a template, by line number. The front matter and table
+/// headers are left out, and so are the cells of rows other than the
+/// description.
+fn prose(template: &str) -> Vec<(usize, Prose)> {
+    let lines = template.lines().collect::<Vec<_>>();
+    let front_matter_lines = match split_front_matter(template) {
+        Ok((_, rest)) => template[..template.len() - rest.len()].lines().count(),
+        Err(_) => 0,
+    };
+    let mut prose = Vec::new();
+    let mut in_code_block = false;
+    let mut columns = Vec::new();
+    for (index, line) in lines.iter().enumerate().skip(front_matter_lines) {
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block || line.trim().is_empty() {
+            continue;
+        }
+        let text = if let Some(heading) = line.strip_prefix('#') {
+            Prose::Heading(heading.trim_start_matches('#').trim().to_string())
+        } else if line.starts_with('|') {
+            if lines.get(index + 1).is_some_and(|next| is_separator(next)) {
+                columns = column_names(line);
+                continue;
+            }
+            if is_separator(line) {
+                continue;
+            }
+            let Some(cells) = split_cells(line) else {
+                continue;
+            };
+            let Some(description) = columns
+                .iter()
+                .position(|column| column == "description")
+                .and_then(|index| cells.get(index))
+            else {
+                continue;
+            };
+            let description = match description.find("<!--") {
+                Some(attributes) => &description[..attributes],
+                None => description,
+            };
+            if description.trim().is_empty() {
+                continue;
+            }
+            Prose::Description(description.trim().to_string())
+        } else {
+            Prose::Text(line.to_string())
+        };
+        prose.push((index + 1, text));
+    }
+    prose
+}
+
+/// The words of some markdown, without code spans, HTML, and the targets of
+/// links
+fn plain(markdown: &str) -> String {
+    let mut plain = String::new();
+    let mut rest = markdown;
+    while let Some(index) = rest.find(['`', '<', ']']) {
+        plain.push_str(&rest[..index]);
+        let after = &rest[index..];
+        let end = if after.starts_with('`') {
+            let ticks = after.len() - after.trim_start_matches('`').len();
+            after[ticks..]
+                .find(&after[..ticks])
+                .map(|end| end + 2 * ticks)
+        } else if after.starts_with('<') {
+            after.find('>').map(|end| end + 1)
+        } else if after.starts_with("](") {
+            plain.push(' ');
+            after.find(')').map(|end| end + 1)
+        } else {
+            Some(1)
+        };
+        let Some(end) = end else {
+            break;
+        };
+        plain.push(' ');
+        rest = &after[end..];
+    }
+    plain.push_str(rest);
+    plain.replace(['[', ']'], " ")
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphabetic() || c == '\'' || c == '-'))
+        .map(|word| word.trim_matches(['\'', '-']))
+        .filter(|word| !word.is_empty())
+}
+
+fn in_dictionary(word: &str) -> bool {
+    DICTIONARY
+        .lines()
+        .any(|known| !known.starts_with('#') && known == word)
+}
+
+/// Checks the terminology and capitalization, returning the line numbers
+/// and messages of the problems
+fn check_style(template: &str) -> Vec<(usize, String)> {
+    let mut problems = Vec::new();
+    for (line, prose) in prose(template) {
+        let lowercase = plain(prose.text()).to_lowercase();
+        for (term, avoid) in TERMINOLOGY {
+            for phrase in avoid.iter().filter(|phrase| lowercase.contains(*phrase)) {
+                problems.push((line, format!("says \"{phrase}\", use \"{term}\" instead")));
+            }
+        }
+        match prose {
+            // Sentence case, so only the first word gets a capital letter,
+            // unless the dictionary has it that way
+            Prose::Heading(heading) => {
+                let plain = plain(&heading);
+                for (index, word) in words(&plain).enumerate() {
+                    let capitalized = word.starts_with(char::is_uppercase);
+                    let acronym = word.chars().all(|c| !c.is_lowercase());
+                    if index == 0 && !capitalized && !in_dictionary(word) {
+                        problems.push((line, format!("heading starts with lowercase `{word}`")));
+                    } else if index > 0 && capitalized && !acronym && !in_dictionary(word) {
+                        problems.push((
+                            line,
+                            format!("heading isn't in sentence case, `{word}` is capitalized"),
+                        ));
+                    }
+                }
+            }
+            // Descriptions can start with code or a link, those keep their
+            // own capitalization
+            Prose::Description(description) => {
+                if description.starts_with(char::is_lowercase) {
+                    problems.push((line, "description starts with a lowercase letter".into()));
+                }
+            }
+            Prose::Text(_) => {}
+        }
+    }
+    problems
+}
+
+/// Asks hunspell which of the words it doesn't know. Without hunspell, there
+/// is no spell-check.
+fn misspelled(words: &BTreeSet<&str>) -> Result<Option<BTreeSet<String>>> {
+    let child = Command::new("hunspell")
+        .args(["-l", "-d", "en_US"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut stdin = child.stdin.take().unwrap();
+    for word in words {
+        writeln!(stdin, "{word}")?;
+    }
+    drop(stdin);
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("hunspell failed"));
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+    ))
+}
+
+/// Checks the spelling, returning the line numbers and messages of the
+/// problems, or `None` if hunspell isn't installed
+fn check_spelling(template: &str) -> Result<Option<Vec<(usize, String)>>> {
+    let prose = prose(template)
+        .into_iter()
+        .map(|(line, prose)| (line, plain(prose.text())))
+        .collect::<Vec<_>>();
+    // Identifiers outside of code spans, like `ErrorKind::Tag`, aren't words
+    let is_word = |word: &&str| {
+        !word.chars().skip(1).any(char::is_uppercase)
+            && !in_dictionary(word)
+            && !in_dictionary(&word.to_lowercase())
+    };
+    let unique = prose
+        .iter()
+        .flat_map(|(_, text)| words(text))
+        .filter(is_word)
+        .collect::<BTreeSet<_>>();
+    let Some(unknown) = misspelled(&unique)? else {
+        return Ok(None);
+    };
+    let mut problems = Vec::new();
+    for (line, text) in &prose {
+        let mut seen = BTreeSet::new();
+        for word in words(text).filter(|word| unknown.contains(*word)) {
+            if seen.insert(word) {
+                problems.push((*line, format!("unknown word `{word}`")));
+            }
+        }
+    }
+    Ok(Some(problems))
+}
+
+/// A row in a table, for checking where its combinator comes from
+struct TableRow {
+    line: usize,
+    /// Whether the first cell is empty, so the row continues the
+    /// combinator of the row above
+    continues: bool,
+    /// Whether the first cell names combinators, instead of a regex
+    names_combinators: bool,
+}
+
+/// Checks that the rows of a table that continue the combinator of the row
+/// above have one to continue. Tables where no row names a combinator are
+/// about code that isn't any combinator in particular, like the fixtures.
+fn check_continuations(table: &[TableRow], problems: &mut Vec<(usize, String)>) {
+    if !table.iter().any(|row| row.names_combinators) {
+        return;
+    }
+    let mut above = None;
+    for row in table {
+        if row.continues {
+            match above {
+                None => problems.push((
+                    row.line,
+                    "row continues the combinator above, but it's the first row of its table"
+                        .to_string(),
+                )),
+                Some(false) => problems.push((
+                    row.line,
+                    "row continues the combinator above, but the row above doesn't name one"
+                        .to_string(),
+                )),
+                Some(true) => {}
+            }
+        } else {
+            above = Some(row.names_combinators);
+        }
+    }
+}
+
+/// Checks the rows of the tables, returning the line numbers and messages of
+/// the problems
+fn check_rows(template: &str) -> Vec<(usize, String)> {
+    let lines = template.lines().collect::<Vec<_>>();
+    let mut problems = Vec::new();
+    let mut in_code_block = false;
+    let mut table = Vec::new();
+    // With the versions of nom that they're for, as a combinator can have a
+    // row for each of them
+    let mut first_rows = HashMap::<String, Vec<(usize, Option<Vec<String>>)>>::new();
+    // The names of the columns of the table, for saying which cell a problem
+    // is in
+    let mut columns = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let line_number = index + 1;
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block || !line.starts_with('|') {
+            check_continuations(&table, &mut problems);
+            table.clear();
+            continue;
+        }
+        if lines.get(index + 1).is_some_and(|next| is_separator(next)) {
+            columns = column_names(line);
+            continue;
+        }
+        if is_separator(line) {
+            continue;
+        }
+        if *line != line.trim_end() {
+            problems.push((line_number, "row ends with whitespace".to_string()));
+        }
+        let Some(cells) = split_cells(line.trim_end()) else {
+            problems.push((
+                line_number,
+                "row doesn't end with a pipe, so it isn't part of the table".to_string(),
+            ));
+            continue;
+        };
+        if cells.len() != columns.len() {
+            problems.push((
+                line_number,
+                format!(
+                    "row has {} cells instead of {}, pipes in code need a backslash",
+                    cells.len(),
+                    columns.len()
+                ),
+            ));
+            continue;
+        }
+        for (cell, column) in cells.iter().zip(&columns) {
+            let padding = cell.len() - cell.trim_end().len();
+            if !cell.trim().is_empty() && padding > 1 {
+                problems.push((
+                    line_number,
+                    format!("trailing whitespace in the {column} cell"),
+                ));
+            }
+        }
+        let cell = |name: &str| {
+            columns
+                .iter()
+                .position(|column| column == name)
+                .map_or("", |index| cells[index].trim())
+        };
+        let (parser, usage, input) = (cells[0].trim(), cell("usage"), cell("input"));
+        match (usage.is_empty(), input.is_empty()) {
+            (false, true) => problems.push((line_number, "row has a usage but no input".into())),
+            (true, false) => problems.push((line_number, "row has an input but no usage".into())),
+            _ => {}
+        }
+        // Rows with a regex show no combinator in particular
+        let paths = match parser.is_empty() || parser.starts_with('`') {
+            true => Vec::new(),
+            false => parser.split("<br>").map(str::trim).collect::<Vec<_>>(),
+        };
+        table.push(TableRow {
+            line: line_number,
+            continues: parser.is_empty(),
+            names_combinators: !paths.is_empty(),
+        });
+        let versions = attribute(cell("description"), "nom")
+            .map(|versions| versions.into_iter().map(str::to_string).collect::<Vec<_>>());
+        let overlap = |other: &Option<Vec<String>>| match (&versions, other) {
+            (Some(versions), Some(other)) => versions.iter().any(|major| other.contains(major)),
+            _ => true,
+        };
+        // Rows that show several combinators side by side, which have rows
+        // of their own as well, say so with `repeats`, and don't count as
+        // their first row
+        let repeats = flag(cell("description"), "repeats");
+        let mut in_row = BTreeSet::new();
+        for path in paths {
+            let first = first_rows
+                .get(path)
+                .and_then(|rows| rows.iter().find(|(_, other)| overlap(other)));
+            if !in_row.insert(path) {
+                problems.push((line_number, format!("`{path}` is in the row twice")));
+            } else if repeats {
+                continue;
+            } else if let Some((first, _)) = first {
+                problems.push((
+                    line_number,
+                    format!("`{path}` already has a row on line {first}"),
+                ));
+            } else {
+                first_rows
+                    .entry(path.to_string())
+                    .or_default()
+                    .push((line_number, versions.clone()));
+            }
+        }
+    }
+    check_continuations(&table, &mut problems);
+    problems.sort_by_key(|(line, _)| *line);
+    problems
+}
+
+/// The value of the attribute `key` in the comment at the end of a
+/// description cell, like `["7"]` for `<!-- nom = 7 -->`, with lists split
+/// up and without the quotes
+fn attribute<'a>(description: &'a str, key: &str) -> Option<Vec<&'a str>> {
+    let comment = description.split_once("<!--")?.1.split("-->").next()?;
+    let value = comment.match_indices(key).find_map(|(start, _)| {
+        let before = comment[..start].trim_end();
+        if !before.is_empty() && !before.ends_with(',') {
+            return None;
+        }
+        comment[start + key.len()..].trim_start().strip_prefix('=')
+    })?;
+    let value = value.trim_start();
+    let unquote = |value: &'a str| value.trim().trim_matches('"');
+    if let Some(list) = value.strip_prefix('[') {
+        return Some(list.split(']').next()?.split(',').map(unquote).collect());
+    }
+    if let Some(string) = value.strip_prefix('"') {
+        return Some(vec![string.split('"').next()?]);
+    }
+    Some(vec![unquote(value.split(',').next()?)])
+}
+
+/// Whether the comment at the end of a description cell has the attribute
+/// `key` without a value, like `<!-- repeats -->`
+fn flag(description: &str, key: &str) -> bool {
+    description
+        .split_once("<!--")
+        .and_then(|(_, comment)| comment.split("-->").next())
+        .is_some_and(|comment| comment.split(',').any(|part| part.trim() == key))
+}
+
+/// Checks that the combinators of the rows are in `items`, the items of nom
+/// `version` that the sheet is built with, and that rows aren't for a newer
+/// version of nom with `since`. Rows for other major versions with `nom`
+/// are left out. These are warnings, as the build tells for sure.
+fn check_versions(template: &str, items: &HashSet<&str>, version: &str) -> Vec<(usize, String)> {
+    let major = version.split('.').next().unwrap_or_default();
+    let mut warnings = Vec::new();
+    let mut in_code_block = false;
+    let mut columns = Vec::new();
+    let lines = template.lines().collect::<Vec<_>>();
+    for (index, line) in lines.iter().enumerate() {
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block || !line.starts_with('|') || is_separator(line) {
+            continue;
+        }
+        if lines.get(index + 1).is_some_and(|next| is_separator(next)) {
+            columns = column_names(line);
+            continue;
+        }
+        let Some(cells) = split_cells(line.trim_end()) else {
+            continue;
+        };
+        let description = columns
+            .iter()
+            .position(|column| column == "description")
+            .and_then(|index| cells.get(index))
+            .map_or("", |cell| cell.trim());
+        if attribute(description, "nom").is_some_and(|majors| !majors.contains(&major)) {
+            continue;
+        }
+        if let Some(since) =
+            attribute(description, "since").and_then(|since| since.first().copied())
+        {
+            if parse_version(since) > parse_version(version) {
+                warnings.push((
+                    index + 1,
+                    format!("row is for nom {since} and later, but the sheet is built with nom {version}"),
+                ));
+            }
+        }
+        let parser = cells.first().map_or("", |cell| cell.trim());
+        if parser.is_empty() || parser.starts_with('`') {
+            continue;
+        }
+        // Other crates' combinators start with their crate, like `nom_locate`
+        for path in parser.split("<br>").map(str::trim) {
+            if !path.starts_with("nom_") && !items.contains(path) {
+                warnings.push((index + 1, format!("`{path}` isn't in nom {version}")));
+            }
+        }
+    }
+    warnings
+}
+
+/// Lints the prose of `template`, returning the line numbers and messages of
+/// the problems, in the order of the template
+pub fn lint(template: &str) -> Result<Vec<(usize, String)>> {
+    let mut problems = check_style(template);
+    if let Err(e) = split_front_matter(template) {
+        problems.push((1, e.to_string()));
+    }
+    match check_spelling(template)? {
+        Some(spelling) => problems.extend(spelling),
+        None => eprintln!("hunspell isn't installed, so the spelling isn't checked"),
+    }
+    problems.sort_by_key(|(line, _)| *line);
+    Ok(problems)
+}
+
+/// Prints the problems of a template with the file and line they're on,
+/// which can be in a file that the template includes
+pub(crate) fn print_problems(template: &Expanded, problems: &[(usize, String)]) {
+    for (line, message) in problems {
+        let (path, line) = template.location(*line);
+        println!("{}:{line}: {message}", path.display());
+    }
+}
+
+/// Checks both the rows and the prose of the template at `path`, without
+/// generating anything, and fails if there are any problems
+pub fn lint_template(path: &Path) -> Result<ExitCode> {
+    let template = expand_includes(path)?;
+    let mut problems = check_rows(&template.text);
+    problems.extend(lint(&template.text)?);
+    problems.sort_by_key(|(line, _)| *line);
+    let warnings = check_versions(&template.text, &nom_item_paths(), NOM_VERSION)
+        .into_iter()
+        .map(|(line, message)| (line, format!("warning: {message}")))
+        .collect::<Vec<_>>();
+    print_problems(&template, &warnings);
+    if problems.is_empty() {
+        println!("No problems in {}", path.display());
+        return Ok(ExitCode::SUCCESS);
+    }
+    print_problems(&template, &problems);
+    Ok(ExitCode::FAILURE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prose() {
+        let template = "\
+---
+title: Parsing cheatsheet
+---
+# Nom cheatsheet
+
+Some `text`.
+
+| parser | usage | input | output | description |
+|---|---|---|---|---|
+| combinator::map | `map(digit1, f)` | `\"1\"` |  | Maps it <!-- level = beginner --> |
+|  | `map(digit1, f)` | `\"a\"` |  |  |
+
+```rust
+// Not prose
+```
+";
+        assert_eq!(
+            prose(template),
+            [
+                (4, Prose::Heading("Nom cheatsheet".into())),
+                (6, Prose::Text("Some `text`.".into())),
+                (10, Prose::Description("Maps it".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plain() {
+        let plain = plain("Like [`map`](#combinator-map), but <br>`x` doesn't");
+        assert_eq!(
+            words(&plain).collect::<Vec<_>>(),
+            ["Like", "but", "doesn't"]
+        );
+    }
+
+    #[test]
+    fn test_check_style() {
+        let template = "\
+## Applying The parser
+## nom-supreme
+## From regex to ASCII
+
+Gives the rest of the input back.
+
+| parser | usage | input | output | description |
+|---|---|---|---|---|
+| combinator::map | `map(digit1, f)` | `\"1\"` |  | maps it |
+| combinator::opt | `opt(digit1)` | `\"1\"` |  | `opt` makes it optional |
+";
+        assert_eq!(
+            check_style(template),
+            [
+                (
+                    1,
+                    "heading isn't in sentence case, `The` is capitalized".to_string()
+                ),
+                (
+                    5,
+                    "says \"rest of the input\", use \"remainder\" instead".to_string()
+                ),
+                (9, "description starts with a lowercase letter".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_rows() {
+        let template = "\
+| parser | usage | input | output | description |
+|---|---|---|---|---|
+| | `alpha1` | `\"a\"` |  |  |
+| combinator::map<br>combinator::map | `map(digit1, f)` |  |  | Maps it |
+| `\\d+` | `digit1` | `\"1\"` |  | A regex |
+| | `digit1` | `\"a\"` |  | Nothing to continue |
+| character::complete::digit1 | `digit1`   | `\"1\"` |  | Digits |
+| character::complete::digit1 | `digit1` | `\"1\"` |  | Again |\x20
+| combinator::opt | `opt(digit1)` | `\"1\"` | | No pipe
+| combinator::opt | `opt(a | b)` | `\"1\"` |  | Too many |
+
+| parser | usage | input | output | description |
+|---|---|---|---|---|
+| | `request_line` | `\"GET /\"` |  | Not a combinator at all |
+
+| parser | description | usage |
+| :--- | :---: | --- |
+| combinator::eof | At the end | `eof` |
+| combinator::rest | Everything |
+";
+        assert_eq!(
+            check_rows(template),
+            [
+                (
+                    3,
+                    "row continues the combinator above, but it's the first row of its table"
+                        .to_string()
+                ),
+                (4, "row has a usage but no input".to_string()),
+                (4, "`combinator::map` is in the row twice".to_string()),
+                (
+                    6,
+                    "row continues the combinator above, but the row above doesn't name one"
+                        .to_string()
+                ),
+                (7, "trailing whitespace in the usage cell".to_string()),
+                (8, "row ends with whitespace".to_string()),
+                (
+                    8,
+                    "`character::complete::digit1` already has a row on line 7".to_string()
+                ),
+                (
+                    9,
+                    "row doesn't end with a pipe, so it isn't part of the table".to_string()
+                ),
+                (
+                    10,
+                    "row has 6 cells instead of 5, pipes in code need a backslash".to_string()
+                ),
+                (18, "row has a usage but no input".to_string()),
+                (
+                    19,
+                    "row has 2 cells instead of 3, pipes in code need a backslash".to_string()
+                ),
+            ]
+        );
+
+        // A combinator can have a row for each version of nom
+        let template = "\
+| parser | usage | input | output | description |
+|---|---|---|---|---|
+| combinator::fail | `fail` | `\"\"` |  | Fails <!-- nom = 7 --> |
+| combinator::fail | `fail()` | `\"\"` |  | Fails <!-- nom = 8 --> |
+| combinator::fail | `fail()` | `\"\"` |  | Again <!-- nom = [\"7\", \"8\"] --> |
+";
+        assert_eq!(
+            check_rows(template),
+            [(
+                5,
+                "`combinator::fail` already has a row on line 3".to_string()
+            )]
+        );
+
+        // Rows that show several combinators together can say they repeat
+        // them
+        let template = "\
+| parser | usage | input | output | description |
+|---|---|---|---|---|
+| character::complete::digit0<br>character::complete::digit1 | `digit0` | `\"1\"` |  | Both <!-- repeats --> |
+| character::complete::digit0 | `digit0` | `\"1\"` |  | Zero or more |
+| character::complete::digit1 | `digit1` | `\"1\"` |  | One or more |
+";
+        assert_eq!(check_rows(template), []);
+    }
+
+    #[test]
+    fn test_attribute() {
+        let description =
+            r#"Keeps them <!-- nom = 7, requires = ["alloc", "std"], since = "7.1" -->"#;
+        assert_eq!(attribute(description, "nom"), Some(vec!["7"]));
+        assert_eq!(
+            attribute(description, "requires"),
+            Some(vec!["alloc", "std"])
+        );
+        assert_eq!(attribute(description, "since"), Some(vec!["7.1"]));
+        assert_eq!(attribute(description, "level"), None);
+        assert_eq!(attribute("No comment", "nom"), None);
+        assert!(flag(
+            "Shows both <!-- repeats, level = beginner -->",
+            "repeats"
+        ));
+        assert!(!flag("Shows both <!-- nom = 7 -->", "repeats"));
+        assert!(!flag("No comment", "repeats"));
+    }
+
+    #[test]
+    fn test_check_versions() {
+        let template = "\
+| parser | usage | input | output | description |
+|---|---|---|---|---|
+| multi::many0 | `many0(tag(\"a\"))` | `\"aa\"` |  | Repeats it |
+| multi::many | `many(0.., tag(\"a\"))` | `\"aa\"` |  | Repeats it <!-- since = \"8.0\" --> |
+| multi::many | `many(0.., tag(\"a\"))` | `\"aa\"` |  | Only in nom 8 <!-- nom = 8 --> |
+| nom_locate::LocatedSpan | `LocatedSpan::new` | `\"a\"` |  | Not nom's |
+| `\\d+` | `digit1` | `\"1\"` |  | A regex |
+";
+        let items = HashSet::from(["multi::many0"]);
+        assert_eq!(
+            check_versions(template, &items, "7.1.3"),
+            [
+                (
+                    4,
+                    "row is for nom 8.0 and later, but the sheet is built with nom 7.1.3"
+                        .to_string()
+                ),
+                (4, "`multi::many` isn't in nom 7.1.3".to_string()),
+            ]
+        );
+    }
+}
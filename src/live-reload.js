@@ -0,0 +1,19 @@
+// Reloads the page when `serve` has regenerated the sheet. The server holds
+// on to each request until that happens, or until it's been a while.
+(async () => {
+    let generation = "";
+    for (;;) {
+        try {
+            const response = await fetch(`/live-reload?generation=${generation}`);
+            const current = String((await response.json()).generation);
+            if (generation !== "" && current !== generation) {
+                location.reload();
+                return;
+            }
+            generation = current;
+        } catch {
+            // The server is restarting, or gone
+            await new Promise((resolve) => setTimeout(resolve, 1000));
+        }
+    }
+})();
@@ -0,0 +1,155 @@
+//! Writes the rows of the cheatsheet as plain text, in the style of
+//! `llms.txt`, for feeding to assistants and retrieval pipelines. Every row
+//! gets a block of `Field: value` lines, in the order of the template, and
+//! nothing in there changes from run to run.
+
+use crate::{sheet::Row, versions::NOM_VERSION};
+use std::{fmt::Write as _, fs, io::Result, path::Path};
+
+/// Markdown from a cell as plain text, keeping the backticks of code spans.
+/// Links are replaced by their text, and line breaks become newlines.
+pub(crate) fn plain(markdown: &str) -> String {
+    let mut plain = String::new();
+    let mut rest = markdown;
+    while let Some(index) = rest.find(['[', '<']) {
+        let (before, after) = rest.split_at(index);
+        plain.push_str(before);
+        if let Some(after) = after.strip_prefix("<br>") {
+            plain.push('\n');
+            rest = after;
+        } else if let Some(end) = after.strip_prefix("<!--").and_then(|_| after.find("-->")) {
+            rest = &after[end + 3..];
+        } else if let Some((text, target)) = after[1..].split_once("](") {
+            match target.find(')') {
+                Some(end) if after.starts_with('[') && !text.contains(['[', ']']) => {
+                    plain.push_str(text);
+                    rest = &target[end + 1..];
+                }
+                _ => {
+                    plain.push_str(&after[..1]);
+                    rest = &after[1..];
+                }
+            }
+        } else {
+            plain.push_str(&after[..1]);
+            rest = &after[1..];
+        }
+    }
+    plain.push_str(rest);
+    plain.trim().to_string()
+}
+
+/// A field that can go over several lines, with the ones after the first
+/// indented
+fn field(text: &mut String, name: &str, value: &str) {
+    let mut lines = value.lines();
+    let first = lines.next().unwrap_or_default();
+    if first.is_empty() {
+        writeln!(text, "{name}:").unwrap();
+    } else {
+        writeln!(text, "{name}: {first}").unwrap();
+    }
+    for line in lines {
+        writeln!(text, "  {line}").unwrap();
+    }
+}
+
+fn llms_txt(rows: &[Row]) -> String {
+    let mut text = format!(
+        "# nom cheatsheet\n\n> Examples of the parsers and combinators of nom {NOM_VERSION}, along \
+         with what they return. The results come from running the examples.\n"
+    );
+    for row in rows {
+        let name = if row.combinators.is_empty() {
+            row.section.to_string()
+        } else {
+            row.combinators
+                .iter()
+                .map(|link| format!("{}::{}", link.module, link.name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(text, "\n## {name}\n").unwrap();
+        field(&mut text, "Section", row.section);
+        field(&mut text, "Level", row.level.name());
+        // The `complete` and `streaming` versions of a combinator have the
+        // same signature
+        let mut signatures = Vec::new();
+        for signature in row.combinators.iter().filter_map(|link| link.signature) {
+            if !signatures.contains(&signature) {
+                field(&mut text, "Signature", signature);
+                signatures.push(signature);
+            }
+        }
+        for link in &row.combinators {
+            field(&mut text, "Docs", &link.url);
+        }
+        field(&mut text, "Usage", row.usage);
+        for input in &row.inputs {
+            field(&mut text, "Input", input);
+        }
+        // Outputs start with what kind of result they are, like `Result:`
+        // or `Error`, so they get a line of their own
+        field(&mut text, "Output", &format!("\n{}", plain(&row.output)));
+        let description = plain(row.description);
+        if !description.is_empty() {
+            field(&mut text, "Description", &description);
+        }
+    }
+    text
+}
+
+/// Writes the rows to `path` as plain text
+pub fn write_llms_txt(path: &Path, rows: &[Row]) -> Result<()> {
+    fs::write(path, llms_txt(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet::Link;
+
+    #[test]
+    fn test_plain() {
+        assert_eq!(
+            plain("Like [`map`](#combinator-map), see [docs](https://docs.rs)<br>`a[0]`"),
+            "Like `map`, see docs\n`a[0]`"
+        );
+        assert_eq!(plain("Matches <!-- level = beginner -->"), "Matches");
+    }
+
+    #[test]
+    fn test_llms_txt() {
+        let row = Row {
+            combinators: vec![Link {
+                module: "character::complete",
+                name: "alpha1",
+                url: "https://docs.rs/nom/latest/nom/character/complete/fn.alpha1.html".into(),
+                summary: None,
+                signature: Some("fn alpha1(input)"),
+            }],
+            usage: "alpha1",
+            inputs: vec!["\"abc123\""],
+            output: "Result: `\"abc\"`<br>Remainder: `\"123\"`".to_string(),
+            description: "Matches [`alpha`](#x) characters",
+            ..Row::for_test("Basic elements")
+        };
+        let text = llms_txt(&[row]);
+        assert!(text.ends_with(
+            "
+## character::complete::alpha1
+
+Section: Basic elements
+Level: beginner
+Signature: fn alpha1(input)
+Docs: https://docs.rs/nom/latest/nom/character/complete/fn.alpha1.html
+Usage: alpha1
+Input: \"abc123\"
+Output:
+  Result: `\"abc\"`
+  Remainder: `\"123\"`
+Description: Matches `alpha` characters
+"
+        ));
+    }
+}
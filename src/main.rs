@@ -1,288 +1,949 @@
-use comrak::{
-    markdown_to_html_with_plugins, plugins::syntect::SyntectAdapterBuilder, Options, Plugins,
+use clap::{Parser, Subcommand, ValueEnum};
+use nom::IResult;
+use nom_cheatsheet_shared::{
+    expand_includes, format_iresult, markdown_format_code, Numbers, Whitespace,
 };
-use nom::{character::complete::digit1, combinator::map, IResult};
-use nom_cheatsheet_shared::markdown_format_code;
+use sha2::{Digest, Sha256};
 use std::{
-    fs::File,
+    env,
+    fs::{self, File},
     io::{BufWriter, Result, Write},
-    path::Path,
+    path::{Path, PathBuf},
+    process::ExitCode,
     str,
-};
-use syntect::{
-    highlighting::ThemeSet,
-    html::{css_for_theme_with_class_style, ClassStyle},
+    time::Duration,
 };
 
+mod anki;
+mod api;
+mod book;
+mod bundle;
+mod cache;
+mod coverage;
+mod docset;
+mod evaluate;
 mod generated;
-use generated::generate;
-
-trait SubsliceOffset {
-    /**
-    Returns the index of the first character of the subslice in the original slice.
-
-    # Example
-    ```
-    let string = "a\nb\nc";
-    let lines: Vec<&str> = string.lines().collect();
-    assert_eq!(string.subslice_offset(lines[0]), Some(0));
-    assert_eq!(string.subslice_offset(lines[1]), Some(2));
-    assert_eq!(string.subslice_offset(lines[2]), Some(4));
-    assert_eq!(string.subslice_offset("other"), None);
-    assert_eq!(string.subslice_offset("a"), None);
-    ```
-    */
-    fn subslice_offset_bytes(&self, subslice: &Self) -> Option<usize>;
+mod hover;
+mod html;
+mod json;
+mod links;
+mod lint;
+mod llms;
+mod manifest;
+mod migrate;
+mod pandoc;
+mod pdf;
+mod seed;
+mod serve;
+mod server;
+mod sheet;
+mod show;
+mod site;
+mod snapshot;
+mod sqlite;
+mod text;
+mod tui;
+mod validate;
+mod versions;
+mod wasm;
+mod watch;
+use cache::Cache;
+use evaluate::{evaluate_rows, Evaluated, Output};
+use generated::{generate_filtered, FILTER_NAMES};
+use html::Artifact;
+use sheet::{Filter, Level, Link, Row, Sheet};
+
+#[derive(Parser)]
+#[command(about = "Generates a cheatsheet for nom, with examples that are actually run")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-impl SubsliceOffset for str {
-    fn subslice_offset_bytes(&self, subslice: &str) -> Option<usize> {
-        let self_ptr = self.as_ptr() as usize;
-        let self_end = self_ptr.checked_add(self.len())?;
-        let subslice_ptr = subslice.as_ptr() as usize;
-        let subslice_end = subslice_ptr.checked_add(subslice.len())?;
-        if subslice_ptr < self_ptr || subslice_end > self_end {
-            return None;
-        }
-        if subslice_ptr < self_ptr || subslice_ptr > self_ptr.checked_add(self.len())? {
-            return None;
+/// The sheets that `generate` writes
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    /// The markdown, and the variants of it for beginners and for
+    /// intermediate users
+    Markdown,
+    Html,
+    /// The rows as data, in `nom-cheatsheet.json`
+    Json,
+    /// The HTML, made printable with weasyprint or Chromium, whichever is
+    /// installed
+    Pdf,
+    /// The HTML as a docset for Dash and Zeal, with a search index of the
+    /// combinators
+    Docset,
+    /// A page per section, with a sidebar to get around, in `site/`
+    Site,
+    /// An mdBook book with a chapter per section, in `book/`
+    Book,
+    /// Flashcards for importing into Anki, in `nom-cheatsheet-anki.txt`
+    Anki,
+}
+
+#[derive(Parser)]
+struct GenerateArgs {
+    /// The directory to write everything to
+    #[arg(long, default_value = "dist")]
+    out_dir: PathBuf,
+    /// Which sheets to write, all but the PDF, the docset, the site, the
+    /// book, and the flashcards if not given. Can be given more than once.
+    #[arg(long = "format", value_enum)]
+    formats: Vec<Format>,
+    /// The templates that the sheet should be from. The examples are
+    /// compiled in, so if the binary wasn't built from these, it gets
+    /// rebuilt with `NOM_CHEATSHEET_TEMPLATES` set to them first.
+    #[arg(long = "template")]
+    templates: Vec<PathBuf>,
+    /// Write to `<out dir>/<nom version>/`, and update `<out dir>/latest`
+    /// and the landing page that links to all versions
+    #[arg(long)]
+    versioned: bool,
+    /// Also pack everything that was generated into a single archive
+    #[arg(long, value_enum)]
+    bundle: Option<bundle::Format>,
+    /// Also write a `SHA256SUMS` file, for checking the artifacts with
+    /// `sha256sum --check`
+    #[arg(long)]
+    checksums: bool,
+    /// Only put in the rows for these top level modules of nom or these
+    /// tags, like `bytes,character`, and only the sections that have any of
+    /// them, e.g. for printing a shorter sheet
+    #[arg(long, value_delimiter = ',')]
+    only: Vec<String>,
+    /// Leave out the rows for these top level modules of nom or these tags,
+    /// like `bits`, and the sections that have nothing else
+    #[arg(long, value_delimiter = ',')]
+    exclude: Vec<String>,
+    /// Show how long each example took to run in the HTML, to spot slow
+    /// ones
+    #[arg(long)]
+    timings: bool,
+    /// Also write the sheet as a Pandoc JSON document, which pandoc can
+    /// convert to formats like docx, ODT, or MediaWiki
+    #[arg(long)]
+    pandoc: bool,
+    /// Also write the rows as plain text, one block per row, for
+    /// assistants and retrieval pipelines
+    #[arg(long)]
+    llms_txt: bool,
+    /// Also write the description and examples of every combinator as
+    /// JSON, keyed by its full path, for showing in editors on hover
+    #[arg(long)]
+    hover: bool,
+    /// Where the links to nom's docs should go instead of docs.rs, like
+    /// `http://localhost:8000/nom/` for an offline mirror of `cargo doc`
+    #[arg(long)]
+    docs_base_url: Option<String>,
+    /// Run all examples, instead of reusing their outputs from earlier
+    /// runs
+    #[arg(long)]
+    no_cache: bool,
+    /// Keep running, and generate everything again whenever the templates
+    /// or the shared formatting code change
+    #[arg(long)]
+    watch: bool,
+    /// Don't write anything, only check that the sheets in `<out dir>`, like
+    /// `nom-cheatsheet.md` and `nom-cheatsheet.html`, are what the templates
+    /// make now, and show the differences if they aren't
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "versioned", "watch", "bundle", "only", "exclude", "timings", "checksums"
+        ]
+    )]
+    check: bool,
+    /// With `--check`, also check that the links to docs.rs go to pages
+    /// that exist, like `validate --links`
+    #[arg(long, requires = "check")]
+    links: bool,
+    /// Check the links against nom's rustdoc JSON instead, without going
+    /// online
+    #[arg(long, requires = "links")]
+    rustdoc_json: Option<PathBuf>,
+}
+
+impl GenerateArgs {
+    /// The rows that `--only` and `--exclude` keep
+    fn filter(&self) -> Filter {
+        Filter {
+            only: self.only.clone(),
+            exclude: self.exclude.clone(),
         }
-        // This is safe because we've already checked that subslice_ptr is never
-        // smaller than self_ptr.
-        Some(subslice_ptr - self_ptr)
     }
-}
 
-impl SubsliceOffset for &str {
-    fn subslice_offset_bytes(&self, subslice: &Self) -> Option<usize> {
-        (*self).subslice_offset_bytes(*subslice)
+    /// The sheet with the links to nom's docs where `--docs-base-url` says
+    fn rebase(&self, sheet: Sheet) -> Sheet {
+        match &self.docs_base_url {
+            Some(base) => sheet.with_docs_base(base),
+            None => sheet,
+        }
     }
-}
 
-impl SubsliceOffset for [u8] {
-    fn subslice_offset_bytes(&self, subslice: &Self) -> Option<usize> {
-        let self_ptr = self.as_ptr() as usize;
-        let self_end = self_ptr.checked_add(self.len())?;
-        let subslice_ptr = subslice.as_ptr() as usize;
-        let subslice_end = subslice_ptr.checked_add(subslice.len())?;
-        if subslice_ptr < self_ptr || subslice_end > self_end {
-            return None;
+    fn writes(&self, format: Format) -> bool {
+        if self.formats.is_empty() {
+            !matches!(
+                format,
+                Format::Pdf | Format::Docset | Format::Site | Format::Book | Format::Anki
+            )
+        } else {
+            self.formats.contains(&format)
         }
-        // This is safe because we've already checked that subslice_ptr is never
-        // smaller than self_ptr.
-        Some(subslice_ptr - self_ptr)
     }
 }
 
-impl SubsliceOffset for &[u8] {
-    fn subslice_offset_bytes(&self, subslice: &Self) -> Option<usize> {
-        (*self).subslice_offset_bytes(*subslice)
-    }
+#[derive(Subcommand)]
+enum Command {
+    /// Generate the markdown and HTML cheatsheets (the default)
+    Generate(GenerateArgs),
+    /// Check that the sheets in `<out dir>` are what the templates make now,
+    /// like `generate --check`
+    Check {
+        /// The directory with the sheet to check
+        #[arg(long, default_value = "dist")]
+        out_dir: PathBuf,
+        /// Where the links to nom's docs go in the sheet, like with
+        /// `generate --docs-base-url`
+        #[arg(long)]
+        docs_base_url: Option<String>,
+        /// Run all examples, instead of reusing their outputs from earlier
+        /// runs
+        #[arg(long)]
+        no_cache: bool,
+        /// Also check that the links to docs.rs go to pages that exist, like
+        /// `validate --links`
+        #[arg(long)]
+        links: bool,
+        /// Check the links against nom's rustdoc JSON instead, without going
+        /// online
+        #[arg(long, requires = "links")]
+        rustdoc_json: Option<PathBuf>,
+    },
+    /// Report compiler warnings in the generated examples, per template row
+    Validate {
+        /// Also check the spelling, terminology, and capitalization of the
+        /// prose in the template. The spelling needs hunspell.
+        #[arg(long)]
+        lint: bool,
+        /// The template to lint
+        #[arg(long, default_value = "src/nom-cheatsheet-template.md")]
+        template: PathBuf,
+        /// Also check that the links to docs.rs go to pages that exist, by
+        /// fetching them with curl
+        #[arg(long)]
+        links: bool,
+        /// Check the links against nom's rustdoc JSON instead, without going
+        /// online
+        #[arg(long, requires = "links")]
+        rustdoc_json: Option<PathBuf>,
+    },
+    /// Check the rows and the prose of a template for mistakes, without
+    /// generating anything
+    Lint {
+        /// The template to lint
+        #[arg(default_value = "src/nom-cheatsheet-template.md")]
+        template: PathBuf,
+    },
+    /// Rewrite a template that uses older row syntax to the current syntax
+    Migrate {
+        /// The template to migrate, in place
+        #[arg(default_value = "src/nom-cheatsheet-template.md")]
+        template: PathBuf,
+        /// Only report what would change, and fail if anything would
+        #[arg(long)]
+        check: bool,
+    },
+    /// Print draft rows for the combinators that the template doesn't cover
+    /// yet, from the examples in nom's docs
+    Seed {
+        /// nom's rustdoc JSON, which gets built with a nightly toolchain if
+        /// this isn't given
+        #[arg(long)]
+        rustdoc_json: Option<PathBuf>,
+        /// The template to check for existing rows
+        #[arg(long, default_value = "src/nom-cheatsheet-template.md")]
+        template: PathBuf,
+    },
+    /// Report which of nom's public functions the template doesn't have a
+    /// row for yet, by module
+    Coverage {
+        /// nom's rustdoc JSON, which gets built with a nightly toolchain if
+        /// this isn't given
+        #[arg(long)]
+        rustdoc_json: Option<PathBuf>,
+        /// The template to check for existing rows
+        #[arg(long, default_value = "src/nom-cheatsheet-template.md")]
+        template: PathBuf,
+        /// Write the report to this file instead of printing it
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Update the kinds of nom's items that the build script links to the
+    /// docs with, from nom's rustdoc JSON
+    UpdateItems {
+        /// nom's rustdoc JSON, which gets built with a nightly toolchain if
+        /// this isn't given
+        #[arg(long)]
+        rustdoc_json: Option<PathBuf>,
+    },
+    /// Print the rows for a combinator, like `show take_until`
+    #[command(visible_alias = "lookup")]
+    Show {
+        /// The name of the combinator, or its path if the name is ambiguous,
+        /// like `bytes::complete::tag`
+        name: String,
+    },
+    /// Print the rows that match some words best, like
+    /// `search "until the next quote"`
+    Search {
+        /// Words from the names of the combinators, their usage, or their
+        /// description
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// How many rows to print at most
+        #[arg(long, default_value_t = 5)]
+        limit: usize,
+    },
+    /// Browse the sections and combinators in the terminal, with fuzzy
+    /// search
+    Tui,
+    /// Print the sheet as text that fits the terminal, like
+    /// `text --color always | less -R`
+    Text {
+        /// How many columns wide the text can be, instead of as wide as the
+        /// terminal
+        #[arg(long)]
+        width: Option<usize>,
+        #[arg(long, value_enum, default_value = "auto")]
+        color: text::Color,
+    },
+    /// Serve the HTML, regenerating it and reloading it in the browser
+    /// whenever the template changes
+    Serve {
+        /// The address to listen on
+        #[arg(long, default_value = "127.0.0.1:8000")]
+        address: String,
+        /// The directory to generate into
+        #[arg(long, default_value = "dist")]
+        out_dir: PathBuf,
+    },
+    /// Serve the cheatsheet as JSON over HTTP
+    ApiServe {
+        /// The address to listen on
+        #[arg(long, default_value = "127.0.0.1:8000")]
+        address: String,
+    },
 }
 
-trait Length {
-    fn length(&self) -> usize;
-    fn is_empty(&self) -> bool {
-        self.length() == 0
+/// Makes sure that the parser of the row with `label` fails on an input that
+/// the template marks with `Err: `, so the sheet doesn't claim it does
+fn expect_error<T, E>(output: &std::result::Result<T, E>, label: &str, code: &str) -> Result<()> {
+    if output.is_err() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "In row `{label}`, `{code}` is marked with `Err: `, but the parser doesn't fail on it"
+        )))
     }
 }
 
-impl Length for str {
-    fn length(&self) -> usize {
-        self.len()
+/// Makes sure that the output cell of the row with `label` is what the
+/// template has in it, if anything, printing what changed if it isn't
+fn expect_output(output: &str, expected: &str, label: &str) -> Result<()> {
+    if output == expected {
+        return Ok(());
     }
+    eprintln!("In row `{label}`, the output isn't the one in the template:");
+    eprint!(
+        "{}",
+        snapshot::diff(
+            &expected.replace("<br>", "\n"),
+            &output.replace("<br>", "\n"),
+            &show::Style::stderr()
+        )
+    );
+    Err(std::io::Error::other(format!(
+        "The output of row `{label}` changed"
+    )))
 }
 
-impl Length for &str {
-    fn length(&self) -> usize {
-        self.len()
-    }
+/// Joins the output cells for each of a row's inputs, keeping the `Debug`
+/// output of the results apart for the row's test
+fn join_outputs(outputs: Vec<(String, String)>) -> (String, Vec<String>) {
+    let (cells, debugs): (Vec<_>, Vec<_>) = outputs.into_iter().unzip();
+    (cells.join("<br><br>"), debugs)
 }
 
-impl Length for [u8] {
-    fn length(&self) -> usize {
-        self.len()
+/// Fills in what the test of a row expects for each input, which is the
+/// `Debug` output of the result, as a string literal
+fn fill_test(template: &str, debugs: &[String]) -> String {
+    let mut test = template.to_string();
+    for (index, debug) in debugs.iter().enumerate() {
+        let literal = if debug.contains(['"', '\\']) {
+            let mut hashes = "#".to_string();
+            while debug.contains(&format!("\"{hashes}")) {
+                hashes.push('#');
+            }
+            format!("r{hashes}\"{debug}\"{hashes}")
+        } else {
+            format!("\"{debug}\"")
+        };
+        test = test.replace(&format!("\"__expected_{index}__\""), &literal);
     }
+    test
 }
 
-impl Length for &[u8] {
-    fn length(&self) -> usize {
-        self.len()
-    }
+/// Shows how long an example took to run, below its output
+fn format_timing(elapsed: Duration) -> String {
+    format!(r#"<br><small class="timing">{elapsed:.1?}</small>"#)
+}
+
+/// Numbers the steps of an example, the values of its `let` bindings or what
+/// its traced parsers got, and joins them with `<br>` for a table cell
+fn format_steps(steps: &[String]) -> String {
+    steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| format!("{}. {step}", index + 1))
+        .collect::<Vec<_>>()
+        .join("<br>")
 }
 
-fn number(input: &str) -> IResult<&str, usize> {
-    map(digit1, |s: &str| s.parse().unwrap())(input)
+/// `std::any::type_name` returns fully qualified paths, which are too noisy
+/// for a table cell. This strips the module paths, turning
+/// `core::option::Option<alloc::string::String>` into `Option<String>`.
+fn short_type_name<T: ?Sized>() -> String {
+    let full = std::any::type_name::<T>();
+    let mut short = String::with_capacity(full.len());
+    let mut segment_start = 0;
+    for (index, c) in full.char_indices() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            continue;
+        }
+        let path = &full[segment_start..index];
+        short.push_str(path.rsplit("::").next().unwrap());
+        short.push(c);
+        segment_start = index + c.len_utf8();
+    }
+    short.push_str(full[segment_start..].rsplit("::").next().unwrap());
+    short
 }
 
-// Just to make the example compile
-fn my_alpha1(input: &str) -> IResult<&str, &str> {
-    nom::character::complete::alpha1(input)
+/// Shows what the generic parameters of `IResult` were inferred as
+fn format_types<I, O, E>(_result: &IResult<I, O, E>) -> String {
+    format!(
+        "I = {}<br>O = {}<br>E = {}",
+        markdown_format_code(&short_type_name::<I>()),
+        markdown_format_code(&short_type_name::<O>()),
+        markdown_format_code(&short_type_name::<E>()),
+    )
 }
 
-fn format_remainder<I>(remainder: &I) -> String
-where
-    I: std::fmt::Debug + SubsliceOffset,
-{
-    markdown_format_code(&format!("{remainder:#04x?}"))
-        .replace(['\n', ' '], "")
-        .replace(",]", "]")
-        .replace(',', ", ")
-        .replace('[', "&[")
+/// Whether the templates at `paths` are the ones that the examples were
+/// compiled from, going by their hash with the files they include
+fn built_from_templates(paths: &[PathBuf]) -> Result<bool> {
+    let mut hasher = Sha256::new();
+    for path in paths {
+        hasher.update(expand_includes(path)?.text);
+    }
+    Ok(format!("{:x}", hasher.finalize()) == env!("NOM_CHEATSHEET_TEMPLATE_SHA256"))
 }
 
-fn format_iresult<I, O>(input: &I, result: &IResult<I, O>) -> String
-where
-    I: std::fmt::Debug + SubsliceOffset + Length,
-    O: std::fmt::Debug,
-{
-    match result {
-        Ok((remainder, value)) => {
-            let value = markdown_format_code(&format!("{value:?}"));
-            if remainder.is_empty() {
-                format!("Result: {value}<br>No remainder")
-            } else {
-                let remainder = format_remainder(remainder);
-                format!("Result: {value}<br>Remainder: {remainder}")
-            }
+/// Writes all the artifacts to `dist`, and returns them
+fn generate_files(dist: &Path, args: &GenerateArgs, cache: &mut Cache) -> Result<Vec<Artifact>> {
+    let filter = args.filter();
+    for name in filter.only.iter().chain(&filter.exclude) {
+        if !FILTER_NAMES.contains(&name.as_str()) {
+            return Err(std::io::Error::other(format!(
+                "Nothing in the sheet is in `{name}`, expected one of {FILTER_NAMES:?}"
+            )));
         }
-        Err(e) => match e {
-            nom::Err::Incomplete(needed) => match needed {
-                nom::Needed::Size(size) => format!("Incomplete<br>Needed: {size} items"),
-                nom::Needed::Unknown => "Incomplete<br>Needed: unknown".to_string(),
-            },
-            nom::Err::Error(nom::error::Error {
-                input: location,
-                code,
-            })
-            | nom::Err::Failure(nom::error::Error {
-                input: location,
-                code,
-            }) => {
-                let kind = match e {
-                    nom::Err::Error(_) => "Error",
-                    nom::Err::Failure(_) => "Failure",
-                    nom::Err::Incomplete(_) => unreachable!(),
-                };
-                let offset = input.subslice_offset_bytes(location).unwrap();
-                format!("{kind}<br>Byte offset: {offset}<br>Code: {code:?}")
-            }
-        },
     }
+    let Sheet { markdown, rows } =
+        args.rebase(generate_filtered(Level::Advanced, &filter, false, cache)?);
+    let mut artifacts = Vec::new();
+
+    if args.writes(Format::Markdown) {
+        let markdown_path = dist.join("nom-cheatsheet.md");
+        println!("Markdown file: {markdown_path:?}");
+        let mut markdown_file = BufWriter::new(File::create(&markdown_path)?);
+        markdown_file
+            .write_all(sheet::markdown_output(str::from_utf8(&markdown).unwrap()).as_bytes())?;
+        artifacts.push(Artifact {
+            label: "Markdown",
+            path: markdown_path,
+        });
+
+        // Shorter variants of the markdown that leave out the harder rows,
+        // for readers that are just getting started
+        for (level, label) in [
+            (Level::Beginner, "Markdown for beginners"),
+            (Level::Intermediate, "Markdown without advanced rows"),
+        ] {
+            let Sheet { markdown, .. } =
+                args.rebase(generate_filtered(level, &filter, false, cache)?);
+            let path = dist.join(format!("nom-cheatsheet-{}.md", level.name()));
+            println!("Markdown file: {path:?}");
+            fs::write(
+                &path,
+                sheet::markdown_output(str::from_utf8(&markdown).unwrap()),
+            )?;
+            artifacts.push(Artifact { label, path });
+        }
+    }
+
+    if args.writes(Format::Json) {
+        let path = dist.join("nom-cheatsheet.json");
+        println!("JSON file: {path:?}");
+        json::write_json(&path, &rows)?;
+        artifacts.push(Artifact {
+            label: "JSON",
+            path,
+        });
+    }
+
+    if args.writes(Format::Anki) {
+        let path = dist.join("nom-cheatsheet-anki.txt");
+        println!("Anki file: {path:?}");
+        anki::write_anki(&path, &rows)?;
+        artifacts.push(Artifact {
+            label: "Flashcards for Anki",
+            path,
+        });
+    }
+
+    if args.writes(Format::Book) {
+        let path = book::write_book(
+            dist,
+            &sheet::book_output(str::from_utf8(&markdown).unwrap()),
+        )?;
+        println!("Book: {path:?}");
+        artifacts.push(Artifact {
+            label: "Book for mdBook",
+            path,
+        });
+    }
+
+    if args.pandoc {
+        let path = dist.join("nom-cheatsheet.pandoc.json");
+        println!("Pandoc file: {path:?}");
+        pandoc::write_pandoc(
+            &path,
+            &sheet::markdown_output(str::from_utf8(&markdown).unwrap()),
+        )?;
+        artifacts.push(Artifact {
+            label: "Pandoc JSON",
+            path,
+        });
+    }
+
+    if args.llms_txt {
+        let path = dist.join("llms.txt");
+        println!("Plain text file: {path:?}");
+        llms::write_llms_txt(&path, &rows)?;
+        artifacts.push(Artifact {
+            label: "Plain text for LLMs",
+            path,
+        });
+    }
+
+    if args.hover {
+        let path = dist.join("nom-cheatsheet-hover.json");
+        println!("Hover documentation: {path:?}");
+        hover::write_hover(&path, &rows)?;
+        artifacts.push(Artifact {
+            label: "Hover documentation for editors",
+            path,
+        });
+    }
+
+    // The module is for the pages to run the examples with, so it's only
+    // needed next to them
+    if [Format::Html, Format::Site]
+        .into_iter()
+        .any(|format| args.writes(format))
+    {
+        wasm::build(dist, &mut artifacts)?;
+    }
+
+    let mut manifest = manifest::Manifest::new();
+
+    // Timings differ from run to run, so only the HTML gets them, and only
+    // when asked for
+    let Sheet { markdown, rows } = if args.timings {
+        args.rebase(generate_filtered(
+            Level::Advanced,
+            &filter,
+            true,
+            &mut Cache::disabled(),
+        )?)
+    } else {
+        Sheet { markdown, rows }
+    };
+    // The PDF and the docset are made from the HTML
+    if [Format::Html, Format::Pdf, Format::Docset]
+        .into_iter()
+        .any(|format| args.writes(format))
+    {
+        let html_path = dist.join("nom-cheatsheet.html");
+        // The footer links to the PDF and the docset, which can only be made
+        // once the HTML is written
+        let pdf_path = dist.join("nom-cheatsheet.pdf");
+        if args.writes(Format::Pdf) {
+            artifacts.push(Artifact {
+                label: "PDF",
+                path: pdf_path.clone(),
+            });
+        }
+        if args.writes(Format::Docset) {
+            artifacts.push(Artifact {
+                label: "Docset for Dash and Zeal",
+                path: docset::archive_path(dist),
+            });
+        }
+        println!("HTML file: {html_path:?}");
+        html::write_html(
+            &html_path,
+            &sheet::html_output(str::from_utf8(&markdown).unwrap(), &rows),
+            &rows,
+            &artifacts,
+            &manifest.summary(),
+        )?;
+        // Replace \ with / in the path
+        let url_path = html_path.to_str().unwrap().replace('\\', "/");
+        println!("URL: file:///{url_path}");
+        if args.writes(Format::Pdf) {
+            println!("PDF file: {pdf_path:?}");
+            pdf::write_pdf(&html_path, &pdf_path)?;
+        }
+        if args.writes(Format::Docset) {
+            let path = docset::write_docset(dist, &html_path, &rows)?;
+            println!("Docset: {path:?}");
+        }
+        artifacts.push(Artifact {
+            label: "HTML",
+            path: html_path,
+        });
+    }
+    if args.writes(Format::Site) {
+        let path = site::write_site(
+            dist,
+            &sheet::site_output(str::from_utf8(&markdown).unwrap(), &rows),
+            &rows,
+            &artifacts,
+            &manifest.summary(),
+        )?;
+        println!("Site: {path:?}");
+        artifacts.push(Artifact {
+            label: "Website",
+            path: path.parent().unwrap().to_path_buf(),
+        });
+    }
+
+    // The manifest gets written last, so it can have the checksums of
+    // everything else. The pages don't link to it, as it changes with every
+    // run, so it isn't committed with them.
+    let manifest_path = dist.join("manifest.json");
+    artifacts.push(Artifact {
+        label: "Build manifest",
+        path: manifest_path.clone(),
+    });
+    println!("Manifest: {manifest_path:?}");
+    manifest.add_checksums(&artifacts, &manifest_path)?;
+    manifest.write(&manifest_path)?;
+
+    if args.checksums {
+        let path = manifest::write_sha256sums(dist, &artifacts)?;
+        println!("Checksums: {path:?}");
+        artifacts.push(Artifact {
+            label: "Checksums",
+            path,
+        });
+    }
+
+    Ok(artifacts)
 }
 
-fn main() -> Result<()> {
-    let markdown = generate()?;
-
-    let markdown_path = Path::new("dist/nom-cheatsheet.md");
-    println!("Markdown file: {markdown_path:?}");
-    let mut markdown_file = BufWriter::new(File::create(markdown_path)?);
-    markdown_file.write_all(&markdown)?;
-
-    let mut options = Options::default();
-    options.extension.table = true;
-    options.extension.header_ids = Some(String::new());
-    options.render.unsafe_ = true;
-    let mut plugins = Plugins::default();
-    let syntect = SyntectAdapterBuilder::new().css().build();
-    plugins.render.codefence_syntax_highlighter = Some(&syntect);
-    let html =
-        markdown_to_html_with_plugins(str::from_utf8(&markdown).unwrap(), &options, &plugins);
-
-    let html_path = Path::new("dist/nom-cheatsheet.html");
-    println!("HTML file: {html_path:?}");
-    // Replace \ with / in the path
-    let html_path = html_path.to_str().unwrap().replace('\\', "/");
-    println!("URL: file:///{html_path}");
-
-    let themeset = ThemeSet::load_defaults();
-    let dark_theme = &themeset.themes["Solarized (dark)"];
-    let css_dark = css_for_theme_with_class_style(dark_theme, ClassStyle::Spaced).unwrap();
-    let light_theme = &themeset.themes["Solarized (light)"];
-    let css_light = css_for_theme_with_class_style(light_theme, ClassStyle::Spaced).unwrap();
-
-    let mut html_file = BufWriter::new(File::create(html_path)?);
-    html_file.write_all(
-        r#"<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="UTF-8">
-    <title>Nom Cheatsheet</title>
-    <style>
-"#
-        .as_bytes(),
-    )?;
-    html_file.write_all(include_bytes!("github-markdown.css"))?;
-    html_file.write_all(r"@media (prefers-color-scheme: dark) {".as_bytes())?;
-    html_file.write_all(css_dark.as_bytes())?;
-    html_file.write_all(
-        r"}
-@media (prefers-color-scheme: light) {"
-            .as_bytes(),
-    )?;
-    html_file.write_all(css_light.as_bytes())?;
-    html_file.write_all(r"}".as_bytes())?;
-    html_file.write_all(
-        r#"
-
-.markdown-body {
-    margin: 0 auto;
-    padding: 45px;
+/// What `generate --check` and `check` do: checks that the sheets in the
+/// out dir are what the templates make now, without writing to it. They're
+/// generated into a scratch directory, and every one but the WebAssembly
+/// module, which differs between machines, and the manifest, which differs
+/// between runs, gets compared with the one in the out dir. With `--links`,
+/// broken links to docs.rs fail the check too.
+fn check(args: &GenerateArgs, cache: &mut Cache) -> Result<ExitCode> {
+    let scratch = env::temp_dir().join(format!("nom-cheatsheet-check-{}", std::process::id()));
+    fs::create_dir_all(&scratch)?;
+    let result = generate_files(&scratch, args, cache).and_then(|artifacts| {
+        let mut exit_code = ExitCode::SUCCESS;
+        for artifact in &artifacts {
+            for (name, path) in manifest::artifact_files(&artifact.path)? {
+                if name == "manifest.json" || name.ends_with(".wasm") {
+                    continue;
+                }
+                let generated = fs::read_to_string(&path)?;
+                if snapshot::check(&args.out_dir.join(&name), &generated)? != ExitCode::SUCCESS {
+                    exit_code = ExitCode::FAILURE;
+                }
+            }
+        }
+        if args.links {
+            // The links are the same in every format, so the markdown that
+            // was just written will do, if it was
+            let markdown = if args.writes(Format::Markdown) {
+                fs::read_to_string(scratch.join("nom-cheatsheet.md"))?
+            } else {
+                let sheet = generate_filtered(Level::Advanced, &args.filter(), false, cache)?;
+                String::from_utf8_lossy(&args.rebase(sheet).markdown).into_owned()
+            };
+            if !links::report_links(&markdown, args.rustdoc_json.as_deref())? {
+                exit_code = ExitCode::FAILURE;
+            }
+        }
+        Ok(exit_code)
+    });
+    fs::remove_dir_all(&scratch)?;
+    result
 }
 
-@media (max-width: 767px) {
-    .markdown-body {
-        padding: 15px;
+fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
+    match cli
+        .command
+        .unwrap_or_else(|| Command::Generate(GenerateArgs::parse_from(["generate"])))
+    {
+        Command::Generate(args) => {
+            if !args.templates.is_empty() && !built_from_templates(&args.templates)? {
+                // A binary that was rebuilt for the templates has it set
+                if env::var_os("NOM_CHEATSHEET_TEMPLATES").is_some() {
+                    eprintln!(
+                        "The examples weren't built from these templates, even with \
+                         NOM_CHEATSHEET_TEMPLATES set"
+                    );
+                    return Ok(ExitCode::FAILURE);
+                }
+                println!("Rebuilding with the examples of these templates");
+                let generate_args = env::args_os()
+                    .skip_while(|arg| arg != "generate")
+                    .skip(1)
+                    .collect::<Vec<_>>();
+                return Ok(
+                    if watch::regenerate_from(&args.templates, &generate_args)? {
+                        ExitCode::SUCCESS
+                    } else {
+                        ExitCode::FAILURE
+                    },
+                );
+            }
+            let mut cache = if args.no_cache {
+                Cache::disabled()
+            } else {
+                Cache::new()
+            };
+            let dist = &args.out_dir;
+            if args.check {
+                return check(&args, &mut cache);
+            }
+            let output_dir = if args.versioned {
+                versions::versioned_dir(dist)?
+            } else {
+                fs::create_dir_all(dist)?;
+                dist.to_path_buf()
+            };
+            let artifacts = generate_files(&output_dir, &args, &mut cache)?;
+            if !args.no_cache {
+                println!(
+                    "Example cache: {} hits, {} misses",
+                    cache.hits, cache.misses
+                );
+            }
+            if let Some(format) = args.bundle {
+                let bundle_path = bundle::bundle(&output_dir, &artifacts, format)?;
+                println!("Bundle: {bundle_path:?}");
+            }
+            if args.versioned {
+                versions::update_latest_and_index(dist)?;
+            }
+            if args.watch {
+                // The examples need a rebuild, after which the new binary
+                // gets the same arguments, without this one
+                let args = env::args_os()
+                    .skip_while(|arg| arg != "generate")
+                    .skip(1)
+                    .filter(|arg| arg != "--watch")
+                    .collect::<Vec<_>>();
+                println!("Watching for changes");
+                watch::watch(&watch::sources(), || {
+                    println!("The sources changed, regenerating");
+                    watch::regenerate(&args)?;
+                    Ok(())
+                })?;
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Check {
+            out_dir,
+            docs_base_url,
+            no_cache,
+            links,
+            rustdoc_json,
+        } => {
+            let args = GenerateArgs {
+                out_dir,
+                docs_base_url,
+                no_cache,
+                check: true,
+                links,
+                rustdoc_json,
+                ..GenerateArgs::parse_from(["generate"])
+            };
+            let mut cache = if no_cache {
+                Cache::disabled()
+            } else {
+                Cache::new()
+            };
+            check(&args, &mut cache)
+        }
+        Command::Validate {
+            lint,
+            template,
+            links,
+            rustdoc_json,
+        } => validate::validate(lint.then_some(&*template), links, rustdoc_json.as_deref()),
+        Command::Lint { template } => lint::lint_template(&template),
+        Command::Migrate { template, check } => migrate::migrate(&template, check),
+        Command::Seed {
+            rustdoc_json,
+            template,
+        } => seed::seed(rustdoc_json.as_deref(), &template),
+        Command::Coverage {
+            rustdoc_json,
+            template,
+            output,
+        } => coverage::coverage(rustdoc_json.as_deref(), &template, output.as_deref()),
+        Command::UpdateItems { rustdoc_json } => links::update_items(rustdoc_json.as_deref()),
+        Command::Show { name } => show::show(&name),
+        Command::Search { query, limit } => show::search(&query.join(" "), limit),
+        Command::Tui => tui::tui(),
+        Command::Text { width, color } => text::text(width, color),
+        Command::Serve { address, out_dir } => serve::serve(&address, &out_dir),
+        Command::ApiServe { address } => api::serve(&address),
     }
 }
-    </style>
-</head>
-<body class="markdown-body">
-<article>
-"#
-        .as_bytes(),
-    )?;
-    html_file.write_all(html.as_bytes())?;
-    html_file.write_all(
-        "</article>
-</body>
-</html>
-"
-        .as_bytes(),
-    )?;
-
-    Ok(())
-}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_subslice_offset() {
-        let string = "a\nb\nc";
-        let lines: Vec<&str> = string.lines().collect();
-        assert_eq!(string.subslice_offset_bytes(lines[0]), Some(0));
-        assert_eq!(string.subslice_offset_bytes(lines[1]), Some(2));
-        assert_eq!(string.subslice_offset_bytes(lines[2]), Some(4));
-        assert_eq!(string.subslice_offset_bytes("other"), None);
-        assert_eq!(string.subslice_offset_bytes("a"), None);
-
-        let string = "foobar";
-        let str1 = &string[0..3];
-        let str2 = &string[3..];
-        let str3 = &string[3..3];
-        let str4 = &string[2..3];
-        assert_eq!(str1.subslice_offset_bytes(str2), None);
-        assert_eq!(str1.subslice_offset_bytes(str3), Some(3));
-        assert_eq!(str1.subslice_offset_bytes(str4), Some(2));
+    fn test_short_type_name() {
+        assert_eq!(short_type_name::<u8>(), "u8");
+        assert_eq!(short_type_name::<&str>(), "&str");
+        assert_eq!(short_type_name::<Option<String>>(), "Option<String>");
+        assert_eq!(
+            short_type_name::<nom::error::Error<&[u8]>>(),
+            "Error<&[u8]>"
+        );
+        assert_eq!(
+            short_type_name::<(Vec<&str>, std::collections::HashMap<u8, u8>)>(),
+            "(Vec<&str>, HashMap<u8, u8>)"
+        );
+    }
+
+    #[test]
+    fn test_generate_args() {
+        let Some(Command::Generate(args)) = Cli::parse_from(["nom-cheatsheet", "generate"]).command
+        else {
+            panic!("not the generate command");
+        };
+        assert_eq!(args.out_dir, Path::new("dist"));
+        assert!(args.writes(Format::Markdown) && args.writes(Format::Html));
+        assert!(!args.writes(Format::Pdf));
+
+        let Some(Command::Generate(args)) = Cli::parse_from([
+            "nom-cheatsheet",
+            "generate",
+            "--out-dir",
+            "site",
+            "--format",
+            "html",
+        ])
+        .command
+        else {
+            panic!("not the generate command");
+        };
+        assert_eq!(args.out_dir, Path::new("site"));
+        assert!(!args.writes(Format::Markdown) && args.writes(Format::Html));
+        assert!(matches!(
+            Cli::parse_from(["nom-cheatsheet", "lookup", "tag"]).command,
+            Some(Command::Show { .. })
+        ));
+        let Some(Command::Check {
+            out_dir, no_cache, ..
+        }) = Cli::parse_from(["nom-cheatsheet", "check", "--no-cache"]).command
+        else {
+            panic!("not the check command");
+        };
+        assert_eq!(out_dir, Path::new("dist"));
+        assert!(no_cache);
+        // The links only get checked along with the sheets
+        assert!(Cli::try_parse_from(["nom-cheatsheet", "generate", "--links"]).is_err());
+        let Some(Command::Generate(args)) =
+            Cli::parse_from(["nom-cheatsheet", "generate", "--check", "--links"]).command
+        else {
+            panic!("not the generate command");
+        };
+        assert!(args.check && args.links && args.rustdoc_json.is_none());
     }
 
     #[test]
-    fn test_format_remainder() {
+    fn test_fill_test() {
+        let template = "assert_eq!(a, \"__expected_0__\");\nassert_eq!(b, \"__expected_1__\");";
+        assert_eq!(
+            fill_test(
+                template,
+                &["Ok(1)".to_string(), r##"Ok(("\"#", 'a'))"##.to_string()]
+            ),
+            "assert_eq!(a, \"Ok(1)\");\nassert_eq!(b, r##\"Ok((\"\\\"#\", 'a'))\"##);"
+        );
+    }
+
+    #[test]
+    fn test_expect_error() {
         let input = "abc";
-        assert_eq!(format_remainder(&input), "`\"abc\"`");
-        let input = &[0_u8, 1, 2, 3][..];
-        assert_eq!(format_remainder(&input), "`&[0x00, 0x01, 0x02, 0x03]`");
+        let failed: IResult<&str, &str> = nom::bytes::complete::tag("x")(input);
+        assert!(expect_error(&failed, "tag", "\"abc\"").is_ok());
+        let parsed: IResult<&str, &str> = nom::bytes::complete::tag("a")(input);
+        let error = expect_error(&parsed, "tag", "\"abc\"").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "In row `tag`, `\"abc\"` is marked with `Err: `, but the parser doesn't fail on it"
+        );
+    }
+
+    #[test]
+    fn test_expect_output() {
+        let output = "Result: `'a'`<br>Remainder: `\"bc\"`";
+        assert!(expect_output(output, output, "char").is_ok());
+        let error =
+            expect_output(output, "Result: `'b'`<br>Remainder: `\"bc\"`", "char").unwrap_err();
+        assert_eq!(error.to_string(), "The output of row `char` changed");
+    }
+
+    /// Rows that need features that are off still show their usage as code
+    #[cfg(not(feature = "alloc"))]
+    #[test]
+    fn test_unavailable_row() {
+        let markdown = generated::generate(Level::Advanced, false, &mut Cache::disabled())
+            .unwrap()
+            .markdown;
+        let markdown = sheet::markdown_output(str::from_utf8(&markdown).unwrap());
+        let row = markdown
+            .lines()
+            .find(|line| line.contains("many0(tag(\"ab\"))"))
+            .unwrap();
+        assert!(row.contains("| `many0(tag(\"ab\"))` |"), "{row}");
+        assert!(row.contains("Not available with current features"), "{row}");
     }
 }
@@ -0,0 +1,233 @@
+use crate::{html::Artifact, versions::NOM_VERSION};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::Result,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Where a generated cheatsheet came from, so that a published one can be
+/// traced back to the exact template and generator that made it.
+#[derive(Serialize)]
+pub struct Manifest {
+    nom_version: &'static str,
+    generator_version: &'static str,
+    /// The locked versions of the generator's dependencies, as `name version`
+    dependencies: Vec<&'static str>,
+    /// `None` when not generated from a git checkout
+    git_commit: Option<String>,
+    /// Whether there were uncommitted changes on top of `git_commit`
+    git_dirty: bool,
+    template_sha256: &'static str,
+    features: Vec<&'static str>,
+    generated_at: String,
+    /// The SHA-256 of every other artifact of this run, by file name
+    artifacts: BTreeMap<String, String>,
+}
+
+fn sha256(path: &Path) -> Result<String> {
+    let digest = Sha256::digest(fs::read(path)?);
+    Ok(digest.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().into_owned()
+}
+
+/// The files of the artifact at `path`, by their path next to the other
+/// artifacts: just the file itself, or everything in a directory like
+/// `site/`, as `site/index.html`, sorted
+pub(crate) fn artifact_files(path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let name = file_name(path);
+    if !path.is_dir() {
+        return Ok(vec![(name, path.to_path_buf())]);
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        for (file, path) in artifact_files(&entry.path())? {
+            files.push((format!("{name}/{file}"), path));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Runs git in the checkout that the generator was built from, wherever
+/// it's run from, so the paths are relative to that too
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// When this build happened, in seconds since the Unix epoch. Reproducible
+/// builds set `SOURCE_DATE_EPOCH`, to get the same output every time.
+pub fn build_time() -> u64 {
+    env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|seconds| seconds.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs())
+        })
+}
+
+/// Splits seconds since the Unix epoch into the year, month, day, hour,
+/// minute, and second in UTC
+pub fn civil_time(seconds: u64) -> [u64; 6] {
+    let days = seconds / 86400;
+    let (hour, minute, second) = (seconds / 3600 % 24, seconds / 60 % 60, seconds % 60);
+    // Howard Hinnant's `civil_from_days`, for days after the epoch only
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    [year, month, day, hour, minute, second]
+}
+
+/// Formats seconds since the Unix epoch as an RFC 3339 timestamp in UTC
+fn format_timestamp(seconds: u64) -> String {
+    let [year, month, day, hour, minute, second] = civil_time(seconds);
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+}
+
+/// The features of the crate that the generator was built with
+pub fn features() -> Vec<&'static str> {
+    [
+        ("alloc", cfg!(feature = "alloc")),
+        ("std", cfg!(feature = "std")),
+        ("nightly", cfg!(feature = "nightly")),
+        ("nom8", cfg!(feature = "nom8")),
+    ]
+    .into_iter()
+    .filter_map(|(feature, enabled)| enabled.then_some(feature))
+    .collect()
+}
+
+impl Manifest {
+    pub fn new() -> Self {
+        let git_commit = git(&["rev-parse", "HEAD"]);
+        let git_dirty = git_commit.is_some()
+            // The output itself doesn't count, as it's being written now
+            && git(&["status", "--porcelain", "--", ".", ":(exclude)dist"])
+                .is_some_and(|status| !status.is_empty());
+        Self {
+            nom_version: NOM_VERSION,
+            generator_version: env!("CARGO_PKG_VERSION"),
+            dependencies: env!("NOM_CHEATSHEET_DEPENDENCIES").split(',').collect(),
+            git_commit,
+            git_dirty,
+            template_sha256: env!("NOM_CHEATSHEET_TEMPLATE_SHA256"),
+            features: features(),
+            generated_at: format_timestamp(build_time()),
+            artifacts: BTreeMap::new(),
+        }
+    }
+
+    /// Records the checksums of the artifacts, except the one that is the
+    /// manifest itself, as that can't contain its own checksum
+    pub fn add_checksums(&mut self, artifacts: &[Artifact], manifest_path: &Path) -> Result<()> {
+        for artifact in artifacts
+            .iter()
+            .filter(|artifact| artifact.path != manifest_path)
+        {
+            for (name, path) in artifact_files(&artifact.path)? {
+                self.artifacts.insert(name, sha256(&path)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// A one line summary, for the footer of the HTML. The commit and the
+    /// date are only in the manifest itself, as they change with every run,
+    /// and the committed HTML should only change when the sheet does.
+    pub fn summary(&self) -> String {
+        format!("nom {}", self.nom_version)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+    }
+}
+
+/// Writes a `SHA256SUMS` file for the artifacts to `dist`, in the format
+/// that `sha256sum --check` reads, and returns its path
+pub fn write_sha256sums(dist: &Path, artifacts: &[Artifact]) -> Result<PathBuf> {
+    let mut sums = BTreeMap::new();
+    for artifact in artifacts {
+        for (name, path) in artifact_files(&artifact.path)? {
+            sums.insert(name, sha256(&path)?);
+        }
+    }
+    let lines = sums
+        .iter()
+        .map(|(name, sum)| format!("{sum}  {name}\n"))
+        .collect::<String>();
+    let path = dist.join("SHA256SUMS");
+    fs::write(&path, lines)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1_791_979_199), "2026-10-14T11:59:59Z");
+    }
+
+    #[test]
+    fn test_summary() {
+        let manifest = Manifest::new();
+        assert_eq!(manifest.summary(), format!("nom {NOM_VERSION}"));
+        assert_eq!(manifest.features.contains(&"nom8"), cfg!(feature = "nom8"));
+    }
+
+    #[test]
+    fn test_write_sha256sums() {
+        let dir = std::env::temp_dir().join(format!("nom-cheatsheet-sums-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::create_dir_all(dir.join("site/css")).unwrap();
+        for name in ["b.md", "a.html", "site/index.html", "site/css/sheet.css"] {
+            fs::write(dir.join(name), "abc").unwrap();
+        }
+        let artifacts = ["b.md", "a.html", "site"].map(|name| Artifact {
+            label: "Test",
+            path: dir.join(name),
+        });
+        let path = write_sha256sums(&dir, &artifacts).unwrap();
+        let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            format!(
+                "{abc}  a.html\n{abc}  b.md\n{abc}  site/css/sheet.css\n{abc}  site/index.html\n"
+            )
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,173 @@
+//! Rewrites templates that use older row syntax to the current syntax, so
+//! that custom templates keep working as the syntax evolves. Only the cells
+//! that need changing are touched, everything else is kept byte for byte.
+
+use std::{fs, io::Result, path::Path, process::ExitCode};
+
+/// A change to the row syntax. `migrate` gets the cells of a row, without
+/// the outer pipes, and returns whether it changed any of them.
+struct Migration {
+    description: &'static str,
+    migrate: fn(&mut [String]) -> bool,
+}
+
+static MIGRATIONS: &[Migration] = &[Migration {
+    description: "`let output: Type = usage` became `usage -> Type`",
+    migrate: output_type_annotation,
+}];
+
+/// Splits a table row into its cells, keeping their padding. Pipes that are
+/// escaped with a backslash, like in code spans, don't count.
+pub(crate) fn split_cells(line: &str) -> Option<Vec<String>> {
+    let inner = line.strip_prefix('|')?.strip_suffix('|')?;
+    let mut cells = vec![String::new()];
+    let mut escaped = false;
+    for c in inner.chars() {
+        if c == '|' && !escaped {
+            cells.push(String::new());
+        } else {
+            cells.last_mut().unwrap().push(c);
+        }
+        escaped = c == '\\';
+    }
+    Some(cells)
+}
+
+/// The code in a cell that is a single code span, along with the backticks
+/// around it, so it can be put back the same way
+fn code_span(cell: &str) -> Option<(&str, &str)> {
+    let cell = cell.trim();
+    let backticks = &cell[..cell.len() - cell.trim_start_matches('`').len()];
+    if backticks.is_empty() {
+        return None;
+    }
+    let code = cell.strip_prefix(backticks)?.strip_suffix(backticks)?;
+    Some((backticks, code))
+}
+
+fn output_type_annotation(cells: &mut [String]) -> bool {
+    let Some(usage) = cells.get(1) else {
+        return false;
+    };
+    let Some((backticks, code)) = code_span(usage) else {
+        return false;
+    };
+    let Some((output_type, expression)) = code
+        .trim()
+        .strip_prefix("let output:")
+        .and_then(|rest| rest.split_once(" = "))
+    else {
+        return false;
+    };
+    let expression = expression.trim().trim_end_matches(';').trim_end();
+    let padding_start = &usage[..usage.len() - usage.trim_start().len()];
+    let padding_end = &usage[usage.trim_end().len()..];
+    cells[1] = format!(
+        "{padding_start}{backticks}{expression} -> {}{backticks}{padding_end}",
+        output_type.trim()
+    );
+    true
+}
+
+/// Returns the migrated template, along with the line numbers and
+/// descriptions of the migrations that were applied
+fn migrate_template(template: &str) -> (String, Vec<(usize, &'static str)>) {
+    let mut output = String::with_capacity(template.len());
+    let mut applied = Vec::new();
+    let mut in_code_block = false;
+    for (index, line) in template.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let line_ending = &line[content.len()..];
+        if content.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        let cells = if in_code_block {
+            None
+        } else {
+            split_cells(content)
+        };
+        let Some(mut cells) = cells else {
+            output.push_str(line);
+            continue;
+        };
+        let mut changed = false;
+        for migration in MIGRATIONS {
+            if (migration.migrate)(&mut cells) {
+                applied.push((index + 1, migration.description));
+                changed = true;
+            }
+        }
+        if changed {
+            output.push('|');
+            output.push_str(&cells.join("|"));
+            output.push('|');
+            output.push_str(line_ending);
+        } else {
+            output.push_str(line);
+        }
+    }
+    (output, applied)
+}
+
+/// Migrates the template at `path` in place. With `check`, it only reports
+/// what would change, and fails if anything would.
+pub fn migrate(path: &Path, check: bool) -> Result<ExitCode> {
+    let template = fs::read_to_string(path)?;
+    let (migrated, applied) = migrate_template(&template);
+    if applied.is_empty() {
+        println!("{} already uses the current syntax", path.display());
+        return Ok(ExitCode::SUCCESS);
+    }
+    for (line, description) in &applied {
+        println!("{}:{line}: {description}", path.display());
+    }
+    if check {
+        return Ok(ExitCode::FAILURE);
+    }
+    fs::write(path, migrated)?;
+    println!("Migrated {} rows", applied.len());
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_cells() {
+        assert_eq!(
+            split_cells(r"| a | `b \| c` |  |").unwrap(),
+            [" a ", r" `b \| c` ", "  "]
+        );
+        assert_eq!(split_cells("Not a row"), None);
+    }
+
+    #[test]
+    fn test_migrate_template() {
+        let template = "\
+Some prose with `let output: IResult<&str, &str> = x` in it
+| combinator::into | `let output: IResult<&str, Vec<u8>> = into(my_alpha1)` | `\"abcd\"` |  | Keep me |\r
+```rust
+| `let output: u8 = x` |
+```
+| | `tag(\"a\")` | `\"a\"` |  |  |
+";
+        let (migrated, applied) = migrate_template(template);
+        assert_eq!(
+            migrated,
+            "\
+Some prose with `let output: IResult<&str, &str> = x` in it
+| combinator::into | `into(my_alpha1) -> IResult<&str, Vec<u8>>` | `\"abcd\"` |  | Keep me |\r
+```rust
+| `let output: u8 = x` |
+```
+| | `tag(\"a\")` | `\"a\"` |  |  |
+"
+        );
+        assert_eq!(applied, [(2, MIGRATIONS[0].description)]);
+
+        let (again, applied) = migrate_template(&migrated);
+        assert_eq!(again, migrated);
+        assert!(applied.is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+// A command palette for jumping around the cheatsheet with the keyboard.
+// Ctrl-K (or Cmd-K) opens it, typing filters the sections and combinators
+// with fuzzy matching, the arrow keys pick one, and Enter jumps to it.
+(() => {
+    const dialog = document.getElementById("palette");
+    const input = dialog.querySelector("input");
+    const list = dialog.querySelector("ul");
+    const article = document.querySelector("article");
+
+    // Sections link to their headings, combinators to the first row that
+    // shows them. Rows that continue a combinator have an empty first cell.
+    const entries = [];
+    // The ids of headings are on the anchors inside them
+    for (const anchor of article.querySelectorAll(":is(h2, h3) > a.anchor[id]")) {
+        const heading = anchor.parentElement;
+        entries.push({ label: heading.textContent, kind: "section", target: anchor });
+    }
+    const seen = new Set();
+    article.querySelectorAll("tbody tr").forEach((row, index) => {
+        for (const link of row.cells[0].querySelectorAll("a[href]")) {
+            // The module is the text right in front of the link
+            const module = link.previousSibling?.nodeType === Node.TEXT_NODE
+                ? link.previousSibling.textContent.trim()
+                : "";
+            const path = module + link.textContent;
+            if (seen.has(path)) {
+                continue;
+            }
+            seen.add(path);
+            row.id ||= `row-${index}`;
+            entries.push({ label: path, kind: "combinator", target: row });
+        }
+    });
+
+    // Every character of the query has to be in the label, in order. Matches
+    // at the start of a word and runs of consecutive characters score higher.
+    const score = (query, label) => {
+        let total = 0;
+        let position = 0;
+        let previous = -2;
+        const lower = label.toLowerCase();
+        for (const c of query.toLowerCase()) {
+            const found = lower.indexOf(c, position);
+            if (found < 0) {
+                return null;
+            }
+            total += found === previous + 1 ? 3 : 1;
+            if (found === 0 || /[\s:_]/.test(lower[found - 1])) {
+                total += 2;
+            }
+            previous = found;
+            position = found + 1;
+        }
+        return total - label.length / 100;
+    };
+
+    let matches = [];
+    let selected = 0;
+    const render = () => {
+        const query = input.value.trim();
+        matches = entries
+            .map((entry) => ({ entry, score: query ? score(query, entry.label) : 0 }))
+            .filter((match) => match.score !== null)
+            .sort((a, b) => b.score - a.score)
+            .slice(0, 50)
+            .map((match) => match.entry);
+        selected = Math.min(selected, Math.max(matches.length - 1, 0));
+        list.replaceChildren(
+            ...matches.map((entry, index) => {
+                const item = document.createElement("li");
+                item.textContent = entry.label;
+                item.dataset.kind = entry.kind;
+                item.setAttribute("aria-selected", index === selected);
+                item.addEventListener("click", () => jump(entry));
+                return item;
+            }),
+        );
+        list.children[selected]?.scrollIntoView({ block: "nearest" });
+    };
+
+    const jump = (entry) => {
+        dialog.close();
+        // Rows that the level selector hides need to be shown first
+        const level = document.getElementById("level");
+        if (entry.target.hidden && level) {
+            level.value = "2";
+            level.dispatchEvent(new Event("change"));
+        }
+        location.hash = entry.target.id;
+        entry.target.scrollIntoView();
+    };
+
+    document.addEventListener("keydown", (event) => {
+        if (event.key === "k" && (event.ctrlKey || event.metaKey)) {
+            event.preventDefault();
+            input.value = "";
+            selected = 0;
+            render();
+            dialog.showModal();
+        }
+    });
+    input.addEventListener("input", () => {
+        selected = 0;
+        render();
+    });
+    input.addEventListener("keydown", (event) => {
+        if (event.key === "ArrowDown" || event.key === "ArrowUp") {
+            event.preventDefault();
+            const step = event.key === "ArrowDown" ? 1 : -1;
+            selected = (selected + step + matches.length) % Math.max(matches.length, 1);
+            render();
+        } else if (event.key === "Enter" && matches[selected]) {
+            event.preventDefault();
+            jump(matches[selected]);
+        }
+    });
+})();
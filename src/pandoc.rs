@@ -0,0 +1,284 @@
+//! Writes the cheatsheet as a Pandoc JSON document, for the formats that
+//! aren't generated here. `pandoc nom-cheatsheet.pandoc.json -o sheet.docx`
+//! turns it into a Word document, for example, and the same goes for ODT,
+//! MediaWiki, and everything else pandoc can write.
+
+use comrak::{
+    nodes::{AstNode, ListDelimType, ListType, NodeValue, TableAlignment},
+    parse_document, Anchorizer, Arena, Options,
+};
+use serde_json::{json, Value};
+use std::{
+    fs::File,
+    io::{BufWriter, Result, Write},
+    path::Path,
+};
+
+/// The version of pandoc's document model that gets written
+const PANDOC_API_VERSION: [u32; 3] = [1, 23, 1];
+
+/// The identifier, classes, and key-value pairs that many elements have
+fn attr(id: &str, classes: &[&str]) -> Value {
+    json!([id, classes, []])
+}
+
+/// The text of a node and everything in it, which headings get their
+/// identifier from
+fn collect_text<'a>(node: &'a AstNode<'a>, text: &mut String) {
+    match &node.data.borrow().value {
+        NodeValue::Text(literal) => text.push_str(literal),
+        NodeValue::Code(code) => text.push_str(&code.literal),
+        _ => {
+            for child in node.children() {
+                collect_text(child, text);
+            }
+        }
+    }
+}
+
+struct Writer {
+    /// Headings get the same identifiers as in the HTML, so that links to
+    /// them keep working
+    anchorizer: Anchorizer,
+}
+
+impl Writer {
+    fn inlines<'a>(&mut self, node: &'a AstNode<'a>) -> Vec<Value> {
+        let mut inlines = Vec::new();
+        for child in node.children() {
+            self.inline(child, &mut inlines);
+        }
+        inlines
+    }
+
+    fn inline<'a>(&mut self, node: &'a AstNode<'a>, inlines: &mut Vec<Value>) {
+        let value = node.data.borrow().value.clone();
+        match value {
+            NodeValue::Text(text) => {
+                for (index, word) in text.split(' ').enumerate() {
+                    if index > 0 {
+                        inlines.push(json!({"t": "Space"}));
+                    }
+                    if !word.is_empty() {
+                        inlines.push(json!({"t": "Str", "c": word}));
+                    }
+                }
+            }
+            NodeValue::SoftBreak => inlines.push(json!({"t": "SoftBreak"})),
+            NodeValue::LineBreak => inlines.push(json!({"t": "LineBreak"})),
+            NodeValue::Code(code) => {
+                inlines.push(json!({"t": "Code", "c": [attr("", &[]), code.literal]}));
+            }
+            NodeValue::Emph => inlines.push(json!({"t": "Emph", "c": self.inlines(node)})),
+            NodeValue::Strong => inlines.push(json!({"t": "Strong", "c": self.inlines(node)})),
+            NodeValue::Strikethrough => {
+                inlines.push(json!({"t": "Strikeout", "c": self.inlines(node)}));
+            }
+            NodeValue::Link(link) => inlines.push(json!({
+                "t": "Link",
+                "c": [attr("", &[]), self.inlines(node), [link.url, link.title]],
+            })),
+            NodeValue::Image(link) => inlines.push(json!({
+                "t": "Image",
+                "c": [attr("", &[]), self.inlines(node), [link.url, link.title]],
+            })),
+            // Table cells use `<br>` for line breaks, and the rows that
+            // mentions link to have an empty anchor in front of them
+            NodeValue::HtmlInline(html) => {
+                if html == "<br>" {
+                    inlines.push(json!({"t": "LineBreak"}));
+                } else if let Some(id) = html
+                    .strip_prefix("<a id=\"")
+                    .and_then(|rest| rest.strip_suffix("\">"))
+                {
+                    inlines.push(json!({"t": "Span", "c": [attr(id, &[]), []]}));
+                } else if html != "</a>" {
+                    inlines.push(json!({"t": "RawInline", "c": ["html", html]}));
+                }
+            }
+            _ => inlines.extend(self.inlines(node)),
+        }
+    }
+
+    fn blocks<'a>(&mut self, node: &'a AstNode<'a>, tight: bool) -> Vec<Value> {
+        let mut blocks = Vec::new();
+        for child in node.children() {
+            self.block(child, tight, &mut blocks);
+        }
+        blocks
+    }
+
+    fn block<'a>(&mut self, node: &'a AstNode<'a>, tight: bool, blocks: &mut Vec<Value>) {
+        let value = node.data.borrow().value.clone();
+        match value {
+            // Items of tight lists don't have blank lines between them,
+            // which pandoc has its own kind of paragraph for
+            NodeValue::Paragraph => {
+                let kind = if tight { "Plain" } else { "Para" };
+                blocks.push(json!({"t": kind, "c": self.inlines(node)}));
+            }
+            NodeValue::Heading(heading) => {
+                let mut text = String::new();
+                collect_text(node, &mut text);
+                let id = self.anchorizer.anchorize(text);
+                blocks.push(json!({
+                    "t": "Header",
+                    "c": [heading.level, attr(&id, &[]), self.inlines(node)],
+                }));
+            }
+            // Only the language of `rust,fixture` and the like
+            NodeValue::CodeBlock(code_block) => {
+                let language = code_block.info.split([',', ' ']).next().unwrap_or_default();
+                let classes = if language.is_empty() {
+                    vec![]
+                } else {
+                    vec![language]
+                };
+                blocks.push(json!({
+                    "t": "CodeBlock",
+                    "c": [attr("", &classes), code_block.literal],
+                }));
+            }
+            NodeValue::HtmlBlock(html) => {
+                blocks.push(json!({"t": "RawBlock", "c": ["html", html.literal]}));
+            }
+            NodeValue::BlockQuote => {
+                blocks.push(json!({"t": "BlockQuote", "c": self.blocks(node, false)}));
+            }
+            NodeValue::List(list) => {
+                let items = node
+                    .children()
+                    .map(|item| self.blocks(item, list.tight))
+                    .collect::<Vec<_>>();
+                match list.list_type {
+                    ListType::Bullet => blocks.push(json!({"t": "BulletList", "c": items})),
+                    ListType::Ordered => {
+                        let delimiter = match list.delimiter {
+                            ListDelimType::Period => "Period",
+                            ListDelimType::Paren => "OneParen",
+                        };
+                        blocks.push(json!({
+                            "t": "OrderedList",
+                            "c": [[list.start, {"t": "Decimal"}, {"t": delimiter}], items],
+                        }));
+                    }
+                }
+            }
+            NodeValue::ThematicBreak => blocks.push(json!({"t": "HorizontalRule"})),
+            NodeValue::Table(table) => {
+                let column_specs = table
+                    .alignments
+                    .iter()
+                    .map(|alignment| {
+                        let alignment = match alignment {
+                            TableAlignment::None => "AlignDefault",
+                            TableAlignment::Left => "AlignLeft",
+                            TableAlignment::Center => "AlignCenter",
+                            TableAlignment::Right => "AlignRight",
+                        };
+                        json!([{"t": alignment}, {"t": "ColWidthDefault"}])
+                    })
+                    .collect::<Vec<_>>();
+                let mut head = Vec::new();
+                let mut body = Vec::new();
+                for row in node.children() {
+                    let is_header = matches!(row.data.borrow().value, NodeValue::TableRow(true));
+                    let cells = row
+                        .children()
+                        .map(|cell| {
+                            let content = json!([{"t": "Plain", "c": self.inlines(cell)}]);
+                            json!([attr("", &[]), {"t": "AlignDefault"}, 1, 1, content])
+                        })
+                        .collect::<Vec<_>>();
+                    let row = json!([attr("", &[]), cells]);
+                    if is_header {
+                        head.push(row);
+                    } else {
+                        body.push(row);
+                    }
+                }
+                blocks.push(json!({
+                    "t": "Table",
+                    "c": [
+                        attr("", &[]),
+                        [null, []],
+                        column_specs,
+                        [attr("", &[]), head],
+                        [[attr("", &[]), 0, [], body]],
+                        [attr("", &[]), []],
+                    ],
+                }));
+            }
+            _ => blocks.extend(self.blocks(node, tight)),
+        }
+    }
+}
+
+/// The Pandoc JSON document for some markdown
+fn pandoc_document(markdown: &str) -> Value {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    let root = parse_document(&arena, markdown, &options);
+    let mut writer = Writer {
+        anchorizer: Anchorizer::new(),
+    };
+    json!({
+        "pandoc-api-version": PANDOC_API_VERSION,
+        "meta": {},
+        "blocks": writer.blocks(root, false),
+    })
+}
+
+/// Writes the cheatsheet to `path` as a Pandoc JSON document
+pub fn write_pandoc(path: &Path, markdown: &str) -> Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut file, &pandoc_document(markdown))?;
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pandoc_document() {
+        let document = pandoc_document(
+            "## Basic elements\n\nUse `tag`, [or not](#x).\n\n| a | b |\n|---|---|\n| <a \
+             id=\"x\"></a>1 | 2<br>3 |\n",
+        );
+        let blocks = document["blocks"].as_array().unwrap();
+        assert_eq!(
+            blocks[0],
+            json!({
+                "t": "Header",
+                "c": [2, ["basic-elements", [], []], [
+                    {"t": "Str", "c": "Basic"}, {"t": "Space"}, {"t": "Str", "c": "elements"}
+                ]],
+            })
+        );
+        assert_eq!(
+            blocks[1]["c"],
+            json!([
+                {"t": "Str", "c": "Use"},
+                {"t": "Space"},
+                {"t": "Code", "c": [["", [], []], "tag"]},
+                {"t": "Str", "c": ","},
+                {"t": "Space"},
+                {"t": "Link", "c": [["", [], []], [
+                    {"t": "Str", "c": "or"}, {"t": "Space"}, {"t": "Str", "c": "not"}
+                ], ["#x", ""]]},
+                {"t": "Str", "c": "."},
+            ])
+        );
+        let body = &blocks[2]["c"][4][0][3];
+        assert_eq!(
+            body[0][1][0][4][0]["c"],
+            json!([{"t": "Span", "c": [["x", [], []], []]}, {"t": "Str", "c": "1"}])
+        );
+        assert_eq!(
+            body[0][1][1][4][0]["c"],
+            json!([{"t": "Str", "c": "2"}, {"t": "LineBreak"}, {"t": "Str", "c": "3"}])
+        );
+    }
+}
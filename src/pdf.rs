@@ -0,0 +1,54 @@
+//! Turns the HTML of the cheatsheet into a PDF, for printing. That's left to
+//! an HTML renderer that's installed, as the print styles of the HTML
+//! already say how the sheet should look on paper. Without JavaScript, all
+//! rows are shown, whatever level they're for.
+
+use std::{
+    io::{self, Result},
+    path::Path,
+    process::Command,
+};
+
+/// The arguments for a renderer, for the HTML file and the PDF file
+type Args = fn(&Path, &Path) -> Vec<String>;
+
+/// The renderers that get tried, in this order
+static RENDERERS: &[(&str, Args)] = &[
+    ("weasyprint", |html, pdf| {
+        vec![html.display().to_string(), pdf.display().to_string()]
+    }),
+    ("chromium", chromium_args),
+    ("google-chrome", chromium_args),
+];
+
+fn chromium_args(html: &Path, pdf: &Path) -> Vec<String> {
+    vec![
+        "--headless".to_string(),
+        "--no-pdf-header-footer".to_string(),
+        format!("--print-to-pdf={}", pdf.display()),
+        html.display().to_string(),
+    ]
+}
+
+/// Writes the HTML file at `html` to `pdf` as a PDF, with the first
+/// renderer that's installed
+pub fn write_pdf(html: &Path, pdf: &Path) -> Result<()> {
+    // Chromium takes URLs, which relative paths aren't
+    let html = &html.canonicalize()?;
+    for (renderer, args) in RENDERERS {
+        let status = match Command::new(renderer).args(args(html, pdf)).status() {
+            Ok(status) => status,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "{renderer} couldn't turn the HTML into a PDF"
+            )));
+        }
+        return Ok(());
+    }
+    Err(io::Error::other(
+        "Writing a PDF needs weasyprint or Chromium, and neither is installed",
+    ))
+}
@@ -0,0 +1,33 @@
+// Filters the rows of the tables while typing in the search box. Every word
+// of the query has to be in the combinators, the description, or the tags of
+// a row, going by the index that was written into the page when it was
+// generated.
+// Rows carry their id in an HTML comment like `<!-- row = 12 -->`.
+(() => {
+    const input = document.getElementById("search");
+    const index = JSON.parse(document.getElementById("search-index").textContent);
+    const texts = new Map(
+        index.map((entry) => [
+            String(entry.row),
+            [...entry.combinators, entry.description, ...entry.tags].join(" ").toLowerCase(),
+        ]),
+    );
+    const rows = [];
+    const comments = document.createTreeWalker(
+        document.querySelector("article"),
+        NodeFilter.SHOW_COMMENT,
+    );
+    while (comments.nextNode()) {
+        const match = comments.currentNode.data.match(/row = (\d+)/);
+        const row = comments.currentNode.parentElement.closest("tr");
+        if (match && row && texts.has(match[1])) {
+            rows.push([row, texts.get(match[1])]);
+        }
+    }
+    input.addEventListener("input", () => {
+        const words = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+        for (const [row, text] of rows) {
+            row.classList.toggle("search-miss", !words.every((word) => text.includes(word)));
+        }
+    });
+})();
@@ -0,0 +1,345 @@
+//! Drafts template rows from the examples in nom's own documentation, for
+//! the combinators the template doesn't cover yet. The drafts still need a
+//! human to look them over, but they take care of the typing.
+
+use crate::versions::NOM_VERSION;
+use nom_cheatsheet_shared::expand_includes;
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Result},
+    path::{Path, PathBuf},
+    process::{Command, ExitCode},
+};
+
+#[derive(Debug, PartialEq)]
+struct DraftRow {
+    path: String,
+    usage: Option<String>,
+    input: Option<String>,
+    description: String,
+}
+
+impl DraftRow {
+    fn to_markdown(&self) -> String {
+        let (usage, input) = match (&self.usage, &self.input) {
+            (Some(usage), Some(input)) => (format!("`{usage}`"), format!("`{input}`")),
+            // Rows need both or neither
+            _ => (String::new(), String::new()),
+        };
+        format!(
+            "| {} | {} | {} |  | {} |",
+            self.path,
+            usage.replace('|', "\\|"),
+            input.replace('|', "\\|"),
+            self.description.replace('|', "\\|")
+        )
+    }
+}
+
+/// rustdoc JSON is only available on nightly, so this needs a nightly
+/// toolchain to be installed
+pub(crate) fn build_rustdoc_json() -> Result<PathBuf> {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = manifest_dir.join("target/rustdoc-json");
+    let status = Command::new("cargo")
+        .arg("+nightly")
+        .arg("rustdoc")
+        .args(["-p", &format!("nom@{NOM_VERSION}"), "--lib", "--target-dir"])
+        .arg(&target_dir)
+        .args(["--", "-Z", "unstable-options", "--output-format", "json"])
+        .current_dir(manifest_dir)
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(
+            "Building rustdoc JSON for nom failed, is a nightly toolchain installed?",
+        ));
+    }
+    Ok(target_dir.join("doc/nom.json"))
+}
+
+/// The code of the first Rust code block in some docs, without the lines
+/// that rustdoc hides
+fn first_doctest(docs: &str) -> Option<String> {
+    let mut lines = docs.lines();
+    lines.find(|line| {
+        line.strip_prefix("```")
+            .is_some_and(|language| language.is_empty() || language == "rust")
+    })?;
+    let code = lines
+        .take_while(|line| !line.starts_with("```"))
+        .filter(|line| *line != "#" && !line.starts_with("# "))
+        .collect::<Vec<_>>();
+    Some(code.join("\n"))
+}
+
+/// The text between the parenthesis at the start of `code` and the one that
+/// closes it
+fn parenthesized(code: &str) -> Option<&str> {
+    let mut depth = 0;
+    for (index, c) in code.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&code[1..index]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Finds how a doctest sets up its parser. Most of nom's examples either
+/// define a `fn parser(s: &str)` or a `let parser = |s| ...` closure around
+/// the combinator, or store it with `let mut parser = ...`. Only bodies of a
+/// single line are used, those fit in a table cell.
+fn parser_usage(lines: &[&str]) -> Option<(usize, String)> {
+    let start = lines.iter().position(|line| {
+        let line = line.trim_start();
+        line.starts_with("fn parser(")
+            || line.starts_with("let parser =")
+            || line.starts_with("let mut parser =")
+    })?;
+    let line = lines[start].trim();
+    let body_below = || {
+        let closing = lines.get(start + 2)?.trim();
+        (closing == "}" || closing == "};").then(|| lines[start + 1].trim())
+    };
+    let (parameter, body) = if let Some(rest) = line.strip_prefix("fn parser(") {
+        (rest.split_once(':')?.0.trim(), body_below()?)
+    } else {
+        let value = line.split_once('=')?.1.trim();
+        match value.strip_prefix('|') {
+            Some(closure) => {
+                let (parameter, body) = closure.split_once('|')?;
+                let body = body.trim();
+                let body = if body == "{" {
+                    body_below()?
+                } else {
+                    body.strip_suffix(';')?
+                };
+                (parameter.trim(), body)
+            }
+            None => return Some((start, value.strip_suffix(';')?.to_string())),
+        }
+    };
+    let usage = body.strip_suffix(&format!("({parameter})"))?;
+    Some((start, usage.to_string()))
+}
+
+/// Turns a doctest into a row. The usage comes from how the example sets up
+/// its parser, or if that parser is called directly in an `assert_eq!`, it's
+/// just the name. The input comes from the first `assert_eq!`.
+fn draft_row(path: &str, name: &str, docs: &str) -> DraftRow {
+    let description = docs.lines().next().unwrap_or_default().trim().to_string();
+    let mut row = DraftRow {
+        path: path.to_string(),
+        usage: None,
+        input: None,
+        description,
+    };
+    let Some(code) = first_doctest(docs) else {
+        return row;
+    };
+    let lines = code.lines().collect::<Vec<_>>();
+    let (called, end_of_imports, usage) = match parser_usage(&lines) {
+        Some((start, usage)) => ("parser", start, Some(usage)),
+        None => (name, lines.len(), Some(name.to_string())),
+    };
+    // Examples pick their own error type with a turbofish, but the rows leave
+    // that to inference
+    let usage = usage.map(|usage| {
+        if usage.starts_with(&format!("{name}::<")) && usage.ends_with('>') {
+            name.to_string()
+        } else {
+            usage
+        }
+    });
+    row.usage = usage.filter(|usage| usage.contains(name)).map(|usage| {
+        // The combinator itself gets imported from the path cell, anything
+        // else the example needs goes in the usage cell
+        let own_import = format!("::{name};");
+        let imports = lines[..end_of_imports]
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| line.starts_with("use ") && !line.ends_with(&own_import))
+            .collect::<Vec<_>>();
+        if imports.is_empty() {
+            usage
+        } else {
+            format!("{} {usage}", imports.join(" "))
+        }
+    });
+    row.input = code
+        .split_once(&format!("assert_eq!({called}"))
+        .and_then(|(_, rest)| {
+            // Skip over a turbofish, like in `digit1::<_, (_, ErrorKind)>`
+            let rest = match rest.strip_prefix("::<") {
+                Some(turbofish) => {
+                    let mut depth = 1;
+                    let end = turbofish.char_indices().find_map(|(index, c)| {
+                        match c {
+                            '<' => depth += 1,
+                            '>' => depth -= 1,
+                            _ => {}
+                        }
+                        (depth == 0).then_some(index + 1)
+                    })?;
+                    &turbofish[end..]
+                }
+                None => rest,
+            };
+            rest.starts_with('(').then(|| parenthesized(rest))?
+        })
+        .map(str::to_string);
+    if row.input.is_none() {
+        row.usage = None;
+    }
+    row
+}
+
+/// The `module::name` paths that the template already has rows for
+pub(crate) fn covered_paths(template: &str) -> HashSet<String> {
+    template
+        .lines()
+        .filter_map(|line| line.strip_prefix('|')?.split('|').next())
+        .flat_map(|cell| cell.split("<br>"))
+        .map(|path| path.trim().to_string())
+        .filter(|path| path.contains("::"))
+        .collect()
+}
+
+fn draft_rows(rustdoc: &Value, covered: &HashSet<String>) -> Vec<DraftRow> {
+    let empty = serde_json::Map::new();
+    let index = rustdoc["index"].as_object().unwrap_or(&empty);
+    let mut rows = rustdoc["paths"]
+        .as_object()
+        .unwrap_or(&empty)
+        .iter()
+        .filter(|(_, summary)| summary["crate_id"] == 0 && summary["kind"] == "function")
+        .filter_map(|(id, summary)| {
+            let segments = summary["path"]
+                .as_array()?
+                .iter()
+                .map(|segment| segment.as_str())
+                .collect::<Option<Vec<_>>>()?;
+            let (name, module) = segments.split_last()?;
+            // Skip `nom` itself, and only look at functions in modules
+            if module.len() < 2 {
+                return None;
+            }
+            let path = format!("{}::{name}", module[1..].join("::"));
+            let item = index.get(id)?;
+            if covered.contains(&path)
+                || item["visibility"] != "public"
+                || !item["deprecation"].is_null()
+            {
+                return None;
+            }
+            Some(draft_row(
+                &path,
+                name,
+                item["docs"].as_str().unwrap_or_default(),
+            ))
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by(|a, b| a.path.cmp(&b.path));
+    rows
+}
+
+/// Prints draft rows for every public nom function that the template has no
+/// row for yet, ready to be pasted into a table.
+pub fn seed(rustdoc_json: Option<&Path>, template: &Path) -> Result<ExitCode> {
+    let rustdoc_json = match rustdoc_json {
+        Some(path) => path.to_path_buf(),
+        None => build_rustdoc_json()?,
+    };
+    let rustdoc: Value = serde_json::from_str(&fs::read_to_string(rustdoc_json)?)?;
+    let covered = covered_paths(&expand_includes(template)?.text);
+    let rows = draft_rows(&rustdoc, &covered);
+    println!("| parser | usage | input | output | description |");
+    println!("|---|---|---|---|---|");
+    for row in &rows {
+        println!("{}", row.to_markdown());
+    }
+    eprintln!("Drafted {} rows for uncovered combinators", rows.len());
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TAG_DOCS: &str = r#"Recognizes a pattern.
+
+# Example
+```rust
+# use nom::{Err, error::{Error, ErrorKind}, Needed, IResult};
+use nom::bytes::complete::tag;
+
+fn parser(s: &str) -> IResult<&str, &str> {
+  tag("Hello")(s)
+}
+
+assert_eq!(parser("Hello, World!"), Ok((", World!", "Hello")));
+```"#;
+
+    #[test]
+    fn test_draft_row() {
+        assert_eq!(
+            draft_row("bytes::complete::tag", "tag", TAG_DOCS),
+            DraftRow {
+                path: "bytes::complete::tag".to_string(),
+                usage: Some(r#"tag("Hello")"#.to_string()),
+                input: Some(r#""Hello, World!""#.to_string()),
+                description: "Recognizes a pattern.".to_string(),
+            }
+        );
+
+        let docs = "Gets a number.\n```rust\nuse nom::character::complete::digit1;\nuse nom::combinator::consumed;\nfn parser(i: &str) -> IResult<&str, &str> {\n  consumed(digit1)(i)\n}\nassert_eq!(parser(\"1(2)\"), Ok((\"(2)\", \"1\")));\n```";
+        let row = draft_row("combinator::consumed", "consumed", docs);
+        assert_eq!(
+            row.usage.as_deref(),
+            Some("use nom::character::complete::digit1; consumed(digit1)")
+        );
+        assert_eq!(row.input.as_deref(), Some("\"1(2)\""));
+
+        let docs = "Digits.\n```rust\nassert_eq!(digit1::<_, (_, ErrorKind)>(\"21c\"), Ok((\"c\", \"21\")));\n```";
+        let row = draft_row("character::complete::digit1", "digit1", docs);
+        assert_eq!(row.usage.as_deref(), Some("digit1"));
+        assert_eq!(row.input.as_deref(), Some("\"21c\""));
+
+        let docs = "A float.\n```rust\nlet parser = |s| {\n  double::<_, (_, ErrorKind)>(s)\n};\nassert_eq!(parser(\"1.1\"), Ok((\"\", 1.1)));\n```";
+        let row = draft_row("number::complete::double", "double", docs);
+        assert_eq!(row.usage.as_deref(), Some("double"));
+        assert_eq!(row.input.as_deref(), Some("\"1.1\""));
+
+        let docs = "Many.\n```rust\nlet mut parser = many0(tag(\"ab\"));\nassert_eq!(parser(\"abab\"), Ok((\"\", vec![])));\n```";
+        let row = draft_row("multi::many0", "many0", docs);
+        assert_eq!(row.usage.as_deref(), Some("many0(tag(\"ab\"))"));
+
+        let row = draft_row("combinator::thing", "thing", "No example here");
+        assert_eq!(
+            row.to_markdown(),
+            "| combinator::thing |  |  |  | No example here |"
+        );
+    }
+
+    #[test]
+    fn test_covered_paths() {
+        let covered = covered_paths(
+            "| bytes::complete::tag<br>bytes::streaming::tag | `tag(\"a\")` |\n| | `x` |\nText",
+        );
+        assert_eq!(
+            covered,
+            HashSet::from([
+                "bytes::complete::tag".to_string(),
+                "bytes::streaming::tag".to_string()
+            ])
+        );
+    }
+}
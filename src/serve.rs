@@ -0,0 +1,166 @@
+//! Serves the generated sheet over HTTP while the templates get edited,
+//! regenerating it whenever they or the shared formatting code change, and
+//! reloading it in the browser when that's done.
+
+use crate::{
+    server::{self, Response},
+    watch,
+};
+use std::{
+    collections::HashMap,
+    env,
+    ffi::OsString,
+    fs,
+    io::Result,
+    path::{Component, Path, PathBuf},
+    process::ExitCode,
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// How many times the sheet was regenerated, and a way to wait for the next
+type Generation = (Mutex<u64>, Condvar);
+
+/// How long a browser waits to hear about a change before asking again
+const RELOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("md") => "text/markdown; charset=utf-8",
+        Some("txt") | None => "text/plain; charset=utf-8",
+        Some("js") => "text/javascript",
+        Some("json") => "application/json",
+        Some("wasm") => "application/wasm",
+        Some("pdf") => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The file that a request is for, which has to be in `out_dir`
+fn file_path(out_dir: &Path, path: &str) -> Option<PathBuf> {
+    let path = Path::new(path.trim_start_matches('/'));
+    if path.as_os_str().is_empty() {
+        return Some(out_dir.join("nom-cheatsheet.html"));
+    }
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_)))
+        .then(|| out_dir.join(path))
+}
+
+/// Adds the script that reloads the page to the end of its body
+fn with_live_reload(html: &str) -> String {
+    let script = format!("<script>\n{}</script>\n", include_str!("live-reload.js"));
+    match html.rfind("</body>") {
+        Some(end) => format!("{}{script}{}", &html[..end], &html[end..]),
+        None => format!("{html}{script}"),
+    }
+}
+
+fn route(
+    out_dir: &Path,
+    generation: &Generation,
+    path: &str,
+    parameters: &HashMap<String, String>,
+) -> Response {
+    if path == "/live-reload" {
+        let (count, changed) = generation;
+        let mut current = count.lock().unwrap();
+        if parameters.get("generation") == Some(&current.to_string()) {
+            current = changed.wait_timeout(current, RELOAD_TIMEOUT).unwrap().0;
+        }
+        return Response::json(&serde_json::json!({ "generation": *current }));
+    }
+    let Some(path) = file_path(out_dir, path) else {
+        return Response::error(404, "Not found");
+    };
+    let Ok(body) = fs::read(&path) else {
+        return Response::error(404, "Not found");
+    };
+    let content_type = content_type(&path);
+    let body = if content_type.starts_with("text/html") {
+        with_live_reload(&String::from_utf8_lossy(&body)).into_bytes()
+    } else {
+        body
+    };
+    Response {
+        status: 200,
+        content_type,
+        body,
+    }
+}
+
+/// Generates the sheet into `out_dir` and serves it on `address`, until the
+/// process gets stopped
+pub fn serve(address: &str, out_dir: &Path) -> Result<ExitCode> {
+    let out_dir = env::current_dir()?.join(out_dir);
+    let args = [OsString::from("--out-dir"), out_dir.clone().into()];
+    if !watch::regenerate(&args)? {
+        return Ok(ExitCode::FAILURE);
+    }
+    let generation = Arc::new(Generation::default());
+    thread::spawn({
+        let generation = generation.clone();
+        move || {
+            let result = watch::watch(&watch::sources(), || {
+                println!("The sources changed, regenerating");
+                if watch::regenerate(&args)? {
+                    *generation.0.lock().unwrap() += 1;
+                    generation.1.notify_all();
+                }
+                Ok(())
+            });
+            if let Err(error) = result {
+                eprintln!("Watching the sources failed, so it won't regenerate: {error}");
+            }
+        }
+    });
+    println!("Serving the cheatsheet on http://{address}/");
+    server::serve(address, |path, parameters| {
+        route(&out_dir, &generation, path, parameters)
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_path() {
+        let out_dir = Path::new("/srv/dist");
+        assert_eq!(
+            file_path(out_dir, "/"),
+            Some(PathBuf::from("/srv/dist/nom-cheatsheet.html"))
+        );
+        assert_eq!(
+            file_path(out_dir, "/nom-cheatsheet.docset/Contents/Info.plist"),
+            Some(PathBuf::from(
+                "/srv/dist/nom-cheatsheet.docset/Contents/Info.plist"
+            ))
+        );
+        assert_eq!(file_path(out_dir, "/../Cargo.toml"), None);
+        assert_eq!(content_type(Path::new("a.wasm")), "application/wasm");
+    }
+
+    #[test]
+    fn test_with_live_reload() {
+        let html = with_live_reload("<body><p>Hi</p></body>\n</html>\n");
+        assert!(html.starts_with("<body><p>Hi</p><script>\n"));
+        assert!(html.ends_with("</script>\n</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn test_live_reload_route() {
+        let generation = Generation::default();
+        let parameters = HashMap::from([("generation".to_string(), "1".to_string())]);
+        let response = route(Path::new("dist"), &generation, "/live-reload", &parameters);
+        assert_eq!(
+            response.body,
+            br#"{
+  "generation": 0
+}"#
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    io::{self, Result},
+    thread,
+};
+use tiny_http::{Header, Method, Request, Server};
+
+/// What a handler answers to a request
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(value: &impl Serialize) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: serde_json::to_vec_pretty(value).unwrap(),
+        }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(&serde_json::json!({ "error": message })).unwrap(),
+        }
+    }
+}
+
+/// Decodes `%XX` escapes, and `+` as a space, like in query strings
+fn percent_decode(input: &str) -> String {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut rest = input.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let decoded = tail
+                    .get(..2)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(decoded) = decoded {
+                    bytes.push(decoded);
+                    rest = &tail[2..];
+                } else {
+                    bytes.push(b'%');
+                }
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Splits a request URL into its path and its query parameters
+fn split_url(url: &str) -> (&str, HashMap<String, String>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let parameters = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+    (path, parameters)
+}
+
+fn respond(request: Request, handler: &impl Fn(&str, &HashMap<String, String>) -> Response) {
+    let response = if *request.method() == Method::Get {
+        let (path, parameters) = split_url(request.url());
+        handler(path, &parameters)
+    } else {
+        Response::error(405, "Only GET requests are supported")
+    };
+    let header = Header::from_bytes("Content-Type", response.content_type).unwrap();
+    let http_response = tiny_http::Response::from_data(response.body)
+        .with_status_code(response.status)
+        .with_header(header);
+    // A client that went away shouldn't take the server down with it
+    if let Err(error) = request.respond(http_response) {
+        eprintln!("Failed to respond: {error}");
+    }
+}
+
+/// Answers GET requests on `address` with whatever `handler` makes of their
+/// path and query parameters, until the process gets stopped. Every request
+/// gets a thread of its own, so a handler can wait for something without
+/// holding up the others.
+pub fn serve(
+    address: &str,
+    handler: impl Fn(&str, &HashMap<String, String>) -> Response + Sync,
+) -> Result<()> {
+    let server = Server::http(address).map_err(io::Error::other)?;
+    thread::scope(|scope| {
+        for request in server.incoming_requests() {
+            let handler = &handler;
+            scope.spawn(move || respond(request, handler));
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_url() {
+        let (path, parameters) = split_url("/api/search?q=take%28+5&empty&x=%zz");
+        assert_eq!(path, "/api/search");
+        assert_eq!(parameters["q"], "take( 5");
+        assert_eq!(parameters["empty"], "");
+        assert_eq!(parameters["x"], "%zz");
+
+        let (path, parameters) = split_url("/api/rows");
+        assert_eq!(path, "/api/rows");
+        assert!(parameters.is_empty());
+    }
+}
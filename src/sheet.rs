@@ -0,0 +1,870 @@
+use crate::llms::plain;
+use comrak::Anchorizer;
+use serde::Serialize;
+use std::borrow::Cow;
+
+/// Where the sheet links to nom's docs, which the build picks from the front
+/// matter or the version of nom
+pub static NOM_DOCS: &str = env!("NOM_CHEATSHEET_DOCS_BASE");
+
+/// The generated cheatsheet, both as markdown and as data
+pub struct Sheet {
+    pub markdown: Vec<u8>,
+    pub rows: Vec<Row>,
+}
+
+impl Sheet {
+    /// Leaves out the level 2 sections that have none of the rows in them,
+    /// like the ones that a filter took all rows out of. Sections with other
+    /// tables stay if what's kept links to them, like the byte reference.
+    /// What comes before the first section stays, and so does the closing
+    /// `# ` heading. The offsets of the rows are moved along.
+    pub fn without_empty_sections(self) -> Sheet {
+        let markdown = String::from_utf8(self.markdown).unwrap();
+        let headings = headings(&markdown);
+        let sections = headings
+            .iter()
+            .filter(|heading| heading.level == 2)
+            .map(|heading| heading.offset)
+            .collect::<Vec<_>>();
+        let closing = headings
+            .iter()
+            .rfind(|heading| heading.level == 1 && sections.first() < Some(&heading.offset))
+            .map_or(markdown.len(), |heading| heading.offset);
+        let mut bounds = vec![0];
+        bounds.extend(sections.into_iter().filter(|offset| *offset < closing));
+        bounds.push(closing);
+        bounds.push(markdown.len());
+        let ranges = bounds
+            .windows(2)
+            .map(|range| range[0]..range[1])
+            .collect::<Vec<_>>();
+        let mut rows = self.rows;
+        let with_rows = ranges
+            .iter()
+            .enumerate()
+            .map(|(index, range)| {
+                index == 0
+                    || index == ranges.len() - 1
+                    || rows.iter().any(|row| range.contains(&row.offset))
+            })
+            .collect::<Vec<_>>();
+        let links = ranges
+            .iter()
+            .zip(&with_rows)
+            .filter(|(_, with_rows)| **with_rows)
+            .map(|(range, _)| &markdown[range.clone()])
+            .collect::<String>();
+        let keep = ranges
+            .iter()
+            .zip(&with_rows)
+            .map(|(range, with_rows)| {
+                *with_rows
+                    || markdown[range.clone()]
+                        .lines()
+                        .any(|line| line.starts_with('|'))
+                        && headings.iter().any(|heading| {
+                            range.contains(&heading.offset)
+                                && links.contains(&format!("](#{})", heading.anchor))
+                        })
+            })
+            .collect::<Vec<_>>();
+        let mut kept = String::new();
+        for (range, _) in ranges.into_iter().zip(keep).filter(|(_, keep)| *keep) {
+            for row in rows.iter_mut() {
+                if range.contains(&row.offset) {
+                    row.offset = row.offset - range.start + kept.len();
+                }
+            }
+            kept.push_str(&markdown[range]);
+        }
+        Sheet {
+            markdown: kept.into_bytes(),
+            rows,
+        }
+    }
+
+    /// Points the links to anchors that aren't in the sheet anymore, like
+    /// those of rows and sections that a filter took out, at the docs that
+    /// `docs` has for the anchor. Links without any docs become their text.
+    /// The offsets of the rows are moved along.
+    pub fn without_dangling_links(self, docs: &[(&str, &str)]) -> Sheet {
+        let markdown = String::from_utf8(self.markdown).unwrap();
+        let mut anchors = headings(&markdown)
+            .into_iter()
+            .map(|heading| heading.anchor)
+            .collect::<Vec<_>>();
+        anchors.extend(
+            markdown
+                .split(r#"id=""#)
+                .skip(1)
+                .filter_map(|rest| rest.split_once('"'))
+                .map(|(anchor, _)| anchor.to_string()),
+        );
+        // Each replaced range of the markdown, with what replaces it
+        let mut edits = Vec::new();
+        for (index, _) in markdown.match_indices("](#") {
+            let Some(length) = markdown[index + 3..].find(')') else {
+                continue;
+            };
+            let anchor = &markdown[index + 3..index + 3 + length];
+            if anchors.iter().any(|known| known == anchor) {
+                continue;
+            }
+            let end = index + 3 + length + 1;
+            match docs.iter().find(|(known, _)| *known == anchor) {
+                Some((_, url)) => edits.push((index + 1..end, format!("({url})"))),
+                None => {
+                    let Some(start) = markdown[..index].rfind('[') else {
+                        continue;
+                    };
+                    edits.push((start..end, markdown[start + 1..index].to_string()));
+                }
+            }
+        }
+        let mut rows = self.rows;
+        for row in &mut rows {
+            let before = edits.iter().filter(|(range, _)| range.end <= row.offset);
+            row.offset = before.fold(row.offset, |offset, (range, with)| {
+                offset + with.len() - range.len()
+            });
+        }
+        let mut linked = String::new();
+        let mut rest = 0;
+        for (range, with) in edits {
+            linked.push_str(&markdown[rest..range.start]);
+            linked.push_str(&with);
+            rest = range.end;
+        }
+        linked.push_str(&markdown[rest..]);
+        Sheet {
+            markdown: linked.into_bytes(),
+            rows,
+        }
+    }
+
+    /// Moves the links to nom's docs from `NOM_DOCS` to `base`, like an
+    /// offline mirror. The offsets of the rows are moved along.
+    pub fn with_docs_base(self, base: &str) -> Sheet {
+        let mut base = base.to_string();
+        if !base.ends_with('/') {
+            base.push('/');
+        }
+        let markdown = String::from_utf8(self.markdown).unwrap();
+        let mut rows = self.rows;
+        for row in &mut rows {
+            let before = markdown[..row.offset].matches(NOM_DOCS).count();
+            row.offset = row.offset + before * base.len() - before * NOM_DOCS.len();
+            for link in &mut row.combinators {
+                if let Some(page) = link.url.strip_prefix(NOM_DOCS) {
+                    link.url = Cow::Owned(format!("{base}{page}"));
+                }
+            }
+        }
+        Sheet {
+            markdown: markdown.replace(NOM_DOCS, &base).into_bytes(),
+            rows,
+        }
+    }
+}
+
+/// Which rows go into the sheet with `--only` and `--exclude`, by the crates
+/// or top level modules of nom that their combinators are in, like `bytes`,
+/// or by their tags. Without any `only`, all rows that aren't excluded go in.
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub only: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl Filter {
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether a row that can be picked by `names` goes in
+    pub fn keeps(&self, names: &[&str]) -> bool {
+        let picks = |name: &String| names.contains(&name.as_str());
+        (self.only.is_empty() || self.only.iter().any(picks)) && !self.exclude.iter().any(picks)
+    }
+}
+
+/// How far along in learning nom a reader needs to be for a row to be of
+/// use. Rows are `Intermediate` unless the template says otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Beginner,
+    Intermediate,
+    Advanced,
+}
+
+impl Level {
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Beginner => "beginner",
+            Level::Intermediate => "intermediate",
+            Level::Advanced => "advanced",
+        }
+    }
+}
+
+/// A link to the documentation of a combinator
+#[derive(Clone, Debug, Serialize)]
+pub struct Link {
+    pub module: &'static str,
+    pub name: &'static str,
+    /// Only owned when `--docs-base-url` moved it to another host
+    pub url: Cow<'static, str>,
+    /// The first paragraph of the docs, for nom's own combinators that have
+    /// any
+    #[serde(skip)]
+    pub summary: Option<&'static str>,
+    /// The signature, like `fn tag(tag)`, for nom's own functions
+    #[serde(skip)]
+    pub signature: Option<&'static str>,
+}
+
+/// A row of the cheatsheet that has an example. The id is the same index as
+/// in `ROW_LABELS`, which stays the same as long as the template does.
+#[derive(Clone, Debug, Serialize)]
+pub struct Row {
+    pub id: usize,
+    /// The heading of the section that the row is in
+    pub section: &'static str,
+    /// Rows that continue the one above them get the same combinators
+    pub combinators: Vec<Link>,
+    pub usage: &'static str,
+    pub inputs: Vec<&'static str>,
+    /// The output as it appears in the markdown
+    pub output: String,
+    pub description: &'static str,
+    /// The id of the row in the HTML, for linking to it
+    pub anchor: &'static str,
+    pub level: Level,
+    /// How long running the example and formatting its output took
+    pub elapsed_ns: u64,
+    /// A `#[test]` function that runs the example and checks its result,
+    /// for pasting into a test suite. Examples that use helpers from the
+    /// sheet, or that show steps, don't have one.
+    pub test: Option<String>,
+    /// A program that prints what the example gives, for running it on the
+    /// Rust Playground. Only rows with a test and without crates other than
+    /// nom have one.
+    pub program: Option<&'static str>,
+    /// The generated code that made the output cell
+    pub code: &'static str,
+    /// Where the row's line of the template can be edited
+    pub edit: Option<&'static str>,
+    /// What the combinators are called in winnow, if the row says
+    pub winnow: Vec<Link>,
+    /// What the row is for, like `numbers` or `whitespace`
+    pub tags: Vec<&'static str>,
+    /// Where the row's line starts in the markdown
+    #[serde(skip)]
+    pub offset: usize,
+}
+
+impl Row {
+    /// Whether the query appears anywhere in the row, ignoring case
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.combinators
+            .iter()
+            .map(|link| link.name)
+            .chain([self.section, self.usage, self.description])
+            .chain(self.tags.iter().copied())
+            .chain(self.inputs.iter().copied())
+            .any(|text| text.to_lowercase().contains(&query))
+    }
+}
+
+#[cfg(test)]
+impl Row {
+    /// An empty beginner row in `section`, for tests to fill in the fields
+    /// that they check with `..Row::for_test(section)`
+    pub fn for_test(section: &'static str) -> Self {
+        Row {
+            id: 0,
+            section,
+            combinators: Vec::new(),
+            usage: "",
+            inputs: Vec::new(),
+            output: String::new(),
+            description: "",
+            anchor: "",
+            level: Level::Beginner,
+            elapsed_ns: 0,
+            test: None,
+            program: None,
+            code: "",
+            edit: None,
+            winnow: Vec::new(),
+            tags: Vec::new(),
+            offset: 0,
+        }
+    }
+}
+
+/// The outputs that prose in the template can be limited to, by putting it
+/// between `<!-- only = html -->` and `<!-- end only -->` lines
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Only {
+    Markdown,
+    Html,
+    Print,
+}
+
+/// Splits markdown into the parts that are for every output, and the
+/// blocks that are only for some, without the comments around them. The
+/// build already checked that the blocks are closed and for known outputs.
+fn only_blocks(markdown: &str) -> Vec<(Option<Only>, String)> {
+    let mut blocks = vec![(None, String::new())];
+    for line in markdown.split_inclusive('\n') {
+        let only = match line.trim_end() {
+            "<!-- only = markdown -->" => Some(Only::Markdown),
+            "<!-- only = html -->" => Some(Only::Html),
+            "<!-- only = print -->" => Some(Only::Print),
+            "<!-- end only -->" => None,
+            _ => {
+                blocks.last_mut().unwrap().1.push_str(line);
+                continue;
+            }
+        };
+        blocks.push((only, String::new()));
+    }
+    blocks
+}
+
+/// Puts the blocks back together, with `render` picking what to put in for
+/// each, or `None` to leave it out. Leaving out a block that had blank
+/// lines on both sides keeps just one of them.
+fn join_blocks(markdown: &str, render: impl Fn(Option<Only>, String) -> Option<String>) -> String {
+    let mut joined = String::new();
+    let mut dropped = false;
+    for (only, text) in only_blocks(markdown) {
+        match render(only, text) {
+            Some(text) => {
+                let text = match text.strip_prefix('\n') {
+                    Some(rest) if dropped && joined.ends_with("\n\n") => rest.to_string(),
+                    _ => text,
+                };
+                joined.push_str(&text);
+                dropped = false;
+            }
+            None => dropped = true,
+        }
+    }
+    joined
+}
+
+/// A heading in the markdown, with the anchor that GitHub and the HTML give
+/// it
+struct Heading {
+    /// Where its line starts
+    offset: usize,
+    level: usize,
+    /// Without links, which can't be nested in the links to it
+    title: String,
+    anchor: String,
+}
+
+/// The headings outside of code blocks. Every heading has to go through the
+/// anchorizer, in order, for the ones that repeat to get the same suffix as
+/// they do on GitHub.
+fn headings(markdown: &str) -> Vec<Heading> {
+    let mut anchorizer = Anchorizer::new();
+    let mut headings = Vec::new();
+    let mut in_code = false;
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        if line.starts_with("```") {
+            in_code = !in_code;
+        } else if !in_code {
+            let level = line.bytes().take_while(|&byte| byte == b'#').count();
+            if let Some(heading) = line[level..].strip_prefix(' ').filter(|_| level > 0) {
+                let title = plain(heading);
+                let anchor = anchorizer.anchorize(title.replace('`', ""));
+                headings.push(Heading {
+                    offset,
+                    level,
+                    title,
+                    anchor,
+                });
+            }
+        }
+        offset += line.len();
+    }
+    headings
+}
+
+/// Puts a table of contents in front of the first section, with links to
+/// the level 2 and 3 headings
+fn with_contents(markdown: &str) -> String {
+    let mut contents = String::new();
+    let mut first_section = None;
+    for heading in headings(markdown) {
+        if heading.level == 2 || heading.level == 3 {
+            first_section.get_or_insert(heading.offset);
+            let indent = "  ".repeat(heading.level - 2);
+            contents.push_str(&format!(
+                "{indent}- [{}](#{})\n",
+                heading.title, heading.anchor
+            ));
+        }
+    }
+    match first_section {
+        Some(offset) => format!(
+            "{}**Contents**\n\n{contents}\n{}",
+            &markdown[..offset],
+            &markdown[offset..]
+        ),
+        None => markdown.to_string(),
+    }
+}
+
+/// A part of the sheet that starts with a level 2 heading
+#[derive(Debug, PartialEq)]
+pub struct Section {
+    /// The heading, without links, with the backticks of code left in
+    pub title: String,
+    /// The anchor of the heading, which is unique within the sheet
+    pub anchor: String,
+    /// The anchors of all the headings in the section, this one first
+    pub anchors: Vec<String>,
+    /// Everything up to the next level 2 heading, starting with this one
+    pub markdown: String,
+}
+
+/// Splits the markdown at its level 2 headings, returning what comes
+/// before the first one, and the sections
+pub fn sections(markdown: &str) -> (String, Vec<Section>) {
+    let headings = headings(markdown);
+    let starts = headings
+        .iter()
+        .filter(|heading| heading.level == 2)
+        .collect::<Vec<_>>();
+    let Some(first) = starts.first() else {
+        return (markdown.to_string(), Vec::new());
+    };
+    let ends = starts
+        .iter()
+        .skip(1)
+        .map(|heading| heading.offset)
+        .chain([markdown.len()]);
+    let sections = starts
+        .iter()
+        .zip(ends)
+        .map(|(heading, end)| Section {
+            title: heading.title.clone(),
+            anchor: heading.anchor.clone(),
+            anchors: headings
+                .iter()
+                .filter(|inner| (heading.offset..end).contains(&inner.offset))
+                .map(|inner| inner.anchor.clone())
+                .collect(),
+            markdown: markdown[heading.offset..end].to_string(),
+        })
+        .collect();
+    (markdown[..first.offset].to_string(), sections)
+}
+
+/// The markdown for the markdown files, without the prose that is only for
+/// the HTML or for printing, and with a table of contents
+pub fn markdown_output(markdown: &str) -> String {
+    with_contents(&book_output(markdown))
+}
+
+/// The markdown for the book, which is that of the markdown files without
+/// the table of contents, as mdBook has navigation of its own. The comments
+/// that the HTML puts copy buttons after are left out too.
+pub fn book_output(markdown: &str) -> String {
+    join_blocks(markdown, |only, text| {
+        matches!(only, None | Some(Only::Markdown)).then_some(text)
+    })
+    .replace("<!-- usage -->", "")
+}
+
+/// A link that opens the Rust Playground with `code` in it
+fn playground_url(code: &str) -> String {
+    let mut url =
+        "https://play.rust-lang.org/?version=stable&mode=debug&edition=2021&code=".to_string();
+    for byte in code.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                url.push(byte as char);
+            }
+            _ => url.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    url
+}
+
+/// Puts a permalink at the start of every row, along with the anchor it
+/// goes to if the row doesn't have that yet. At the end of the row's last
+/// cell go a comment with the row's id, for finding the row in the search
+/// index, a link that runs the example on the Rust Playground, and a button
+/// for copying the test of the rows that have one. The test is in an
+/// attribute, so it has to be on one line, and can't have pipes that would
+/// end the cell. The links to the docs of the row's combinators get the first
+/// paragraph of those docs as their title, which shows when hovering them.
+fn with_row_markup(markdown: &str, rows: &[Row]) -> String {
+    let mut with_markup = String::new();
+    let mut rest = 0;
+    for row in rows {
+        let start = row.offset + "| ".len();
+        let end = row.offset + markdown[row.offset..].find(" |\n").unwrap();
+        with_markup.push_str(&markdown[rest..start]);
+        if !markdown[start..end].contains(&format!(r#"id="{}""#, row.anchor)) {
+            with_markup.push_str(&format!(r#"<a id="{}"></a>"#, row.anchor));
+        }
+        with_markup.push_str(&format!(
+            r##"<a class="permalink" href="#{}" title="Link to this row">🔗</a>"##,
+            row.anchor
+        ));
+        let mut cells = markdown[start..end].to_string();
+        for link in &row.combinators {
+            if let Some(summary) = link.summary {
+                cells = cells.replace(
+                    &format!("]({})", link.url),
+                    &format!(r#"]({} "{}")"#, link.url, cell_text(summary)),
+                );
+            }
+        }
+        with_markup.push_str(&cells);
+        with_markup.push_str(&format!(" <!-- row = {} -->", row.id));
+        rest = end;
+        if let Some(program) = row.program {
+            with_markup.push_str(&format!(
+                r#" <a class="playground" href="{}" title="Run on play.rust-lang.org">Run</a>"#,
+                playground_url(program)
+            ));
+        }
+        if let Some(edit) = row.edit {
+            with_markup.push_str(&format!(
+                r#" <a class="edit" href="{edit}" title="Edit this row of the template">Edit</a>"#
+            ));
+        }
+        if let Some(test) = &row.test {
+            let mut escaped = String::new();
+            for c in test.chars() {
+                match c {
+                    '&' => escaped.push_str("&amp;"),
+                    '<' => escaped.push_str("&lt;"),
+                    '>' => escaped.push_str("&gt;"),
+                    '"' => escaped.push_str("&quot;"),
+                    '|' => escaped.push_str("&#124;"),
+                    '`' => escaped.push_str("&#96;"),
+                    '\\' => escaped.push_str("&#92;"),
+                    '\n' => escaped.push_str("&#10;"),
+                    c => escaped.push(c),
+                }
+            }
+            with_markup.push_str(&format!(
+                r#" <button class="copy-test" data-test="{escaped}" title="Copy as a test">Copy test</button>"#
+            ));
+        }
+        if !row.code.is_empty() {
+            with_markup.push_str(&format!(
+                r#"<details class="row-code"><summary>Generated code</summary><pre><code>{}</code></pre></details>"#,
+                cell_text(row.code)
+            ));
+        }
+    }
+    with_markup.push_str(&markdown[rest..]);
+    with_markup
+}
+
+/// Text for going in a table cell as it is. Everything that markdown could
+/// make something of is written as a character reference, and newlines too,
+/// as a row has to stay on one line.
+fn cell_text(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\n' | '&' | '<' | '>' | '"' | '|' | '`' | '\\' | '*' | '_' | '[' | ']' | '~' | '!' => {
+                format!("&#{};", u32::from(c))
+            }
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// The markdown for the HTML, without the prose that is only for the
+/// markdown files. Printing the HTML is how the sheet gets printed, so the
+/// prose for printing is kept, in elements that the CSS only shows when
+/// printing. The prose for the HTML is hidden when printing. Rows get their
+/// id, a button for copying their test, and the code that made their output,
+/// and there's a table of contents.
+pub fn html_output(markdown: &str, rows: &[Row]) -> String {
+    with_contents(&site_output(markdown, rows))
+}
+
+/// The markdown for the HTML, but without the table of contents, as the
+/// pages of the site have a sidebar for getting around instead
+pub fn site_output(markdown: &str, rows: &[Row]) -> String {
+    join_blocks(&with_row_markup(markdown, rows), |only, text| match only {
+        None => Some(text),
+        Some(Only::Markdown) => None,
+        Some(Only::Html) => Some(format!("<div class=\"screen-only\">\n\n{text}\n</div>\n")),
+        Some(Only::Print) => Some(format!("<div class=\"print-only\">\n\n{text}\n</div>\n")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_blocks() {
+        let markdown = "\
+Everywhere
+
+<!-- only = html -->
+Press Ctrl-K
+<!-- end only -->
+<!-- only = markdown -->
+See the HTML
+<!-- end only -->
+<!-- only = print -->
+Printed
+<!-- end only -->
+
+Also everywhere
+";
+        assert_eq!(
+            markdown_output(markdown),
+            "Everywhere\n\nSee the HTML\n\nAlso everywhere\n"
+        );
+        assert_eq!(
+            markdown_output("| `a` | <!-- usage -->`tag(\"a\")` |\n"),
+            "| `a` | `tag(\"a\")` |\n"
+        );
+        assert_eq!(
+            html_output(markdown, &[]),
+            "Everywhere\n\n<div class=\"screen-only\">\n\nPress Ctrl-K\n\n</div>\n<div \
+             class=\"print-only\">\n\nPrinted\n\n</div>\n\nAlso everywhere\n"
+        );
+    }
+
+    #[test]
+    fn test_with_contents() {
+        let markdown = "\
+# Nom cheatsheet
+
+Intro
+
+## Basic elements
+
+```rust
+## not a heading
+```
+
+### `tag` and [friends](#x)
+
+#### Deep
+
+## Basic elements
+";
+        let with_contents = with_contents(markdown);
+        let (before, after) = with_contents.split_once("**Contents**\n\n").unwrap();
+        assert_eq!(before, "# Nom cheatsheet\n\nIntro\n\n");
+        assert_eq!(
+            after,
+            "\
+- [Basic elements](#basic-elements)
+  - [`tag` and friends](#tag-and-friends)
+- [Basic elements](#basic-elements-1)
+
+"
+            .to_string()
+                + &markdown[before.len()..]
+        );
+    }
+
+    #[test]
+    fn test_sections() {
+        let markdown = "# Nom cheatsheet\n\n## Basic elements\n\n### `tag`\n\n## Basic elements\n";
+        let (intro, split) = sections(markdown);
+        assert_eq!(intro, "# Nom cheatsheet\n\n");
+        assert_eq!(
+            split,
+            [
+                Section {
+                    title: "Basic elements".to_string(),
+                    anchor: "basic-elements".to_string(),
+                    anchors: vec!["basic-elements".to_string(), "tag".to_string()],
+                    markdown: "## Basic elements\n\n### `tag`\n\n".to_string(),
+                },
+                Section {
+                    title: "Basic elements".to_string(),
+                    anchor: "basic-elements-1".to_string(),
+                    anchors: vec!["basic-elements-1".to_string()],
+                    markdown: "## Basic elements\n".to_string(),
+                },
+            ]
+        );
+        assert_eq!(sections("Intro\n"), ("Intro\n".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn test_with_row_markup() {
+        let markdown = "| a | b |\n|---|---|\n| `a` \\| b | c |\n| <a id=\"nom-d\"></a>d | e |\n";
+        let row = |id, anchor, offset, test: Option<&str>| Row {
+            id,
+            anchor,
+            level: Level::Intermediate,
+            test: test.map(str::to_string),
+            offset,
+            ..Row::for_test("Basic elements")
+        };
+        let mut rows = [
+            row(0, "nom-a", 20, Some("fn t() {\n    a(\"|\");\n}")),
+            row(1, "nom-d", 37, None),
+        ];
+        rows[1].code = "let x = [*a];\n";
+        rows[1].edit = Some("https://example.com/t.md#L4");
+        assert_eq!(
+            with_row_markup(markdown, &rows),
+            "| a | b |\n|---|---|\n| <a id=\"nom-a\"></a><a class=\"permalink\" href=\"#nom-a\" \
+             title=\"Link to this row\">🔗</a>`a` \\| b | c <!-- row = 0 --> <button \
+             class=\"copy-test\" data-test=\"fn t() {&#10;    a(&quot;&#124;&quot;);&#10;}\" \
+             title=\"Copy as a test\">Copy test</button> |\n| <a class=\"permalink\" \
+             href=\"#nom-d\" title=\"Link to this row\">🔗</a><a id=\"nom-d\"></a>d | e <!-- row = \
+             1 --> <a class=\"edit\" href=\"https://example.com/t.md#L4\" title=\"Edit this row of \
+             the template\">Edit</a><details class=\"row-code\"><summary>Generated code</summary><pre><code>let x = \
+             &#91;&#42;a&#93;;&#10;</code></pre></details> |\n"
+        );
+    }
+
+    #[test]
+    fn test_summary_titles() {
+        let markdown = "| a |\n|---|\n| bytes::complete::[tag](https://x/fn.tag.html) |\n";
+        let row = Row {
+            anchor: "nom-bytes-complete-tag",
+            combinators: vec![Link {
+                module: "bytes::complete",
+                name: "tag",
+                url: "https://x/fn.tag.html".into(),
+                summary: Some("Recognizes a \"pattern\" [or] `|`"),
+                signature: None,
+            }],
+            level: Level::Intermediate,
+            offset: 12,
+            ..Row::for_test("Basic elements")
+        };
+        let html = crate::html::render(&with_row_markup(markdown, &[row]));
+        assert!(html.contains(
+            r#"<a href="https://x/fn.tag.html" title="Recognizes a &quot;pattern&quot; [or] `|`">tag</a>"#
+        ));
+    }
+
+    #[test]
+    fn test_playground_url() {
+        assert_eq!(
+            playground_url("fn main() {\n    println!(\"{:?}\", \"a|b\");\n}"),
+            "https://play.rust-lang.org/?version=stable&mode=debug&edition=2021&code=fn%20main%28%29\
+             %20%7B%0A%20%20%20%20println%21%28%22%7B%3A%3F%7D%22%2C%20%22a%7Cb%22%29%3B%0A%7D"
+        );
+    }
+
+    #[test]
+    fn test_without_empty_sections() {
+        let markdown = "# Sheet\n\nIntro\n\n## Bits\n\nGone\n\n## Bytes\n\n| a |\n|---|\n\
+                        | [x](#reference) |\n\n## Unlinked\n\n| b |\n|---|\n\n\
+                        ## Reference\n\n| c |\n|---|\n\n# Fin\n";
+        let row = Row {
+            anchor: "nom-x",
+            offset: markdown.find("| [x]").unwrap(),
+            ..Row::for_test("Bytes")
+        };
+        let sheet = Sheet {
+            markdown: markdown.as_bytes().to_vec(),
+            rows: vec![row],
+        }
+        .without_empty_sections();
+        let markdown = String::from_utf8(sheet.markdown).unwrap();
+        assert_eq!(
+            markdown,
+            "# Sheet\n\nIntro\n\n## Bytes\n\n| a |\n|---|\n| [x](#reference) |\n\n\
+             ## Reference\n\n| c |\n|---|\n\n# Fin\n"
+        );
+        assert!(markdown[sheet.rows[0].offset..].starts_with("| [x]"));
+    }
+
+    #[test]
+    fn test_without_dangling_links() {
+        let markdown = "# Sheet\n\nSee [alt](#nom-branch-alt) and [Choice](#choice-combinators), \
+                        [Bytes](#bytes) and [tag](#nom-bytes-complete-tag).\n\n## Bytes\n\n\
+                        | <a id=\"nom-bytes-complete-tag\"></a>[tag](#bytes) |\n";
+        let row = Row {
+            anchor: "nom-bytes-complete-tag",
+            offset: markdown.find("| <a").unwrap(),
+            ..Row::for_test("Bytes")
+        };
+        let sheet = Sheet {
+            markdown: markdown.as_bytes().to_vec(),
+            rows: vec![row],
+        }
+        .without_dangling_links(&[("nom-branch-alt", "https://docs.rs/nom/fn.alt.html")]);
+        let markdown = String::from_utf8(sheet.markdown).unwrap();
+        assert_eq!(
+            markdown,
+            "# Sheet\n\nSee [alt](https://docs.rs/nom/fn.alt.html) and Choice, [Bytes](#bytes) \
+             and [tag](#nom-bytes-complete-tag).\n\n## Bytes\n\n\
+             | <a id=\"nom-bytes-complete-tag\"></a>[tag](#bytes) |\n"
+        );
+        assert!(markdown[sheet.rows[0].offset..].starts_with("| <a"));
+    }
+
+    #[test]
+    fn test_with_docs_base() {
+        let tag = format!("{NOM_DOCS}bytes/complete/fn.tag.html");
+        let markdown = format!("See [tag]({tag}).\n\n| [tag]({tag}) |\n");
+        let row = Row {
+            anchor: "nom-bytes-complete-tag",
+            combinators: vec![Link {
+                module: "bytes::complete",
+                name: "tag",
+                url: tag.clone().into(),
+                summary: None,
+                signature: None,
+            }],
+            offset: markdown.find('|').unwrap(),
+            ..Row::for_test("Bytes")
+        };
+        let sheet = Sheet {
+            markdown: markdown.into_bytes(),
+            rows: vec![row],
+        }
+        .with_docs_base("http://localhost:8000/nom");
+        let markdown = String::from_utf8(sheet.markdown).unwrap();
+        let mirrored = "http://localhost:8000/nom/bytes/complete/fn.tag.html";
+        assert_eq!(
+            markdown,
+            format!("See [tag]({mirrored}).\n\n| [tag]({mirrored}) |\n")
+        );
+        assert!(markdown[sheet.rows[0].offset..].starts_with("| [tag]"));
+        assert_eq!(sheet.rows[0].combinators[0].url, mirrored);
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = Filter::default();
+        assert!(filter.is_empty());
+        assert!(filter.keeps(&["bytes"]));
+        let filter = Filter {
+            only: vec!["bytes".to_string(), "character".to_string()],
+            exclude: vec!["whitespace".to_string()],
+        };
+        assert!(!filter.is_empty());
+        assert!(filter.keeps(&["bytes", "numbers"]));
+        assert!(!filter.keeps(&["bits"]));
+        assert!(!filter.keeps(&["character", "whitespace"]));
+        let filter = Filter {
+            only: Vec::new(),
+            exclude: vec!["bits".to_string()],
+        };
+        assert!(filter.keeps(&["bytes"]));
+        assert!(!filter.keeps(&["bits", "bytes"]));
+    }
+}
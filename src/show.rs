@@ -0,0 +1,328 @@
+//! Prints the rows for a single combinator in the terminal, like
+//! `nom-cheatsheet show take_until`, for looking something up without
+//! opening the sheet.
+
+use crate::{
+    cache::Cache,
+    generated::generate,
+    llms::plain,
+    sheet::{Level, Row},
+};
+use std::{
+    collections::BTreeMap,
+    env,
+    io::{self, IsTerminal, Result},
+    process::ExitCode,
+};
+
+/// The rows that show the combinator, by every path that `name` could mean.
+/// That's usually just one, or several that share their rows, like the
+/// complete and streaming versions of a parser. The paths of nom's own
+/// combinators can start with `nom::`, like in the docs.
+fn candidates<'a>(rows: &'a [Row], name: &str) -> BTreeMap<String, Vec<&'a Row>> {
+    let name = name.strip_prefix("nom::").unwrap_or(name);
+    let mut candidates = BTreeMap::<String, Vec<&Row>>::new();
+    for row in rows {
+        for link in &row.combinators {
+            let path = format!("{}::{}", link.module, link.name);
+            if link.name == name || path == name {
+                let rows = candidates.entry(path).or_default();
+                if !rows.iter().any(|known| known.id == row.id) {
+                    rows.push(row);
+                }
+            }
+        }
+    }
+    candidates
+}
+
+/// Colors if the terminal is likely to show them, leaving them out when
+/// `NO_COLOR` is set, as https://no-color.org asks
+pub(crate) struct Style {
+    pub(crate) color: bool,
+}
+
+impl Style {
+    fn terminal(is_terminal: bool) -> Self {
+        Style {
+            color: is_terminal && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+        }
+    }
+
+    pub(crate) fn stdout() -> Self {
+        Style::terminal(io::stdout().is_terminal())
+    }
+
+    pub(crate) fn stderr() -> Self {
+        Style::terminal(io::stderr().is_terminal())
+    }
+
+    pub(crate) fn paint(&self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+
+    pub(crate) fn bold(&self, text: &str) -> String {
+        self.paint("1", text)
+    }
+
+    fn dim(&self, text: &str) -> String {
+        self.paint("2", text)
+    }
+}
+
+/// A labeled field, with the lines after the first lined up under it
+fn field(text: &mut String, style: &Style, label: &str, value: &str) {
+    let indent = " ".repeat(label.len() + 1);
+    for (index, line) in value.lines().enumerate() {
+        if index == 0 {
+            text.push_str(&format!("  {} {line}\n", style.bold(label)));
+        } else {
+            text.push_str(&format!("  {indent}{line}\n"));
+        }
+    }
+}
+
+pub(crate) fn format_rows(rows: &[&Row], style: &Style) -> String {
+    let mut text = String::new();
+    let first = rows[0];
+    let paths = first
+        .combinators
+        .iter()
+        .map(|link| format!("{}::{}", link.module, link.name))
+        .collect::<Vec<_>>();
+    text.push_str(&format!("{}\n", style.bold(&paths.join(", "))));
+    for link in &first.combinators {
+        text.push_str(&format!("{}\n", style.dim(&link.url)));
+    }
+    text.push_str(&format!("In \"{}\"", first.section));
+    if first.level != Level::Intermediate {
+        text.push_str(&format!(", for {} users", first.level.name()));
+    }
+    text.push('\n');
+    for row in rows {
+        text.push('\n');
+        field(&mut text, style, "Usage: ", row.usage);
+        for input in &row.inputs {
+            field(&mut text, style, "Input: ", input);
+        }
+        field(&mut text, style, "Output:", &plain(&row.output));
+        let description = plain(row.description);
+        if !description.is_empty() {
+            text.push('\n');
+            for line in description.lines() {
+                text.push_str(&format!("  {line}\n"));
+            }
+        }
+    }
+    text
+}
+
+/// Prints the rows that show the combinator called `name`, which can also
+/// be its path, like `bytes::complete::tag` or `nom::bytes::complete::tag`. If that could mean combinators
+/// with different rows, it lists them instead.
+pub fn show(name: &str) -> Result<ExitCode> {
+    let rows = generate(Level::Advanced, false, &mut Cache::new())?.rows;
+    let candidates = candidates(&rows, name);
+    let style = Style::stdout();
+    // Names like `tag` are in several modules, but when they're in the same
+    // row, like the complete and streaming versions, it's one combinator
+    let mut groups = BTreeMap::<usize, (Vec<&str>, Vec<&Row>)>::new();
+    for (path, rows) in &candidates {
+        let (paths, group_rows) = groups.entry(rows[0].id).or_default();
+        paths.push(path);
+        for row in rows {
+            if !group_rows.iter().any(|known| known.id == row.id) {
+                group_rows.push(row);
+            }
+        }
+    }
+    let mut groups = groups.into_values().collect::<Vec<_>>();
+    match groups.as_mut_slice() {
+        [] => {
+            eprintln!("No combinator called `{name}` in the cheatsheet");
+            Ok(ExitCode::FAILURE)
+        }
+        [(_, rows)] => {
+            rows.sort_by_key(|row| row.id);
+            print!("{}", format_rows(rows, &style));
+            Ok(ExitCode::SUCCESS)
+        }
+        _ => {
+            eprintln!("`{name}` could be any of these, pick one by its path:");
+            for (paths, _) in groups {
+                eprintln!("    {}", paths.join(", "));
+            }
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+/// How well the row matches the words of the query, as how many of the words
+/// it has, and how much they count for where they are: most in the names of
+/// the combinators, then in the usage and the tags, and then in the rest of
+/// the row. `None` if it has none of them.
+fn search_score(row: &Row, words: &[String]) -> Option<(usize, usize)> {
+    let paths = row
+        .combinators
+        .iter()
+        .map(|link| format!("{}::{}", link.module, link.name))
+        .collect::<Vec<_>>();
+    let fields = paths
+        .iter()
+        .map(|path| (path.as_str(), 4))
+        .chain([(row.usage, 2), (row.description, 1), (row.section, 1)])
+        .chain(row.tags.iter().map(|tag| (*tag, 2)))
+        .chain(row.inputs.iter().map(|input| (*input, 1)))
+        .map(|(text, weight)| (text.to_lowercase(), weight))
+        .collect::<Vec<_>>();
+    let mut found = 0;
+    let mut total = 0;
+    for word in words {
+        let weight = fields
+            .iter()
+            .filter(|(text, _)| text.contains(word.as_str()))
+            .map(|(_, weight)| weight)
+            .sum::<usize>();
+        if weight > 0 {
+            found += 1;
+            total += weight;
+        }
+    }
+    (found > 0).then_some((found, total))
+}
+
+/// The rows that match the query best, best first, at most `limit` of them
+fn search_rows<'a>(rows: &'a [Row], query: &str, limit: usize) -> Vec<&'a Row> {
+    let words = query
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    let mut matches = rows
+        .iter()
+        .filter_map(|row| Some((search_score(row, &words)?, row)))
+        .collect::<Vec<_>>();
+    // Stable, so rows that score the same stay in the order of the sheet
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches
+        .into_iter()
+        .take(limit)
+        .map(|(_, row)| row)
+        .collect()
+}
+
+/// Prints the rows that match the words of `query` best, from anywhere in
+/// the names of their combinators, their usage, or their description
+pub fn search(query: &str, limit: usize) -> Result<ExitCode> {
+    let rows = generate(Level::Advanced, false, &mut Cache::new())?.rows;
+    let matches = search_rows(&rows, query, limit);
+    if matches.is_empty() {
+        eprintln!("No rows in the cheatsheet match `{query}`");
+        return Ok(ExitCode::FAILURE);
+    }
+    let style = Style::stdout();
+    let texts = matches
+        .iter()
+        .map(|row| format_rows(&[row], &style))
+        .collect::<Vec<_>>();
+    print!("{}", texts.join("\n"));
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet::Link;
+
+    fn row(id: usize, module: &'static str, usage: &'static str) -> Row {
+        Row {
+            id,
+            combinators: vec![Link {
+                module,
+                name: "tag",
+                url: "https://docs.rs/nom/latest/nom/bytes/complete/fn.tag.html".into(),
+                summary: None,
+                signature: None,
+            }],
+            usage,
+            inputs: vec!["\"hello world\""],
+            output: "Result: `\"hello\"`<br>Remainder: `\" world\"`".to_string(),
+            description: "Recognizes a [`tag`](#x)",
+            ..Row::for_test("Basic elements")
+        }
+    }
+
+    #[test]
+    fn test_candidates() {
+        let rows = [
+            row(0, "bytes::complete", "tag(\"hello\")"),
+            row(1, "bytes::complete", "tag(\"world\")"),
+            row(2, "nom_supreme::tag::complete", "tag(\"hello\")"),
+        ];
+        let candidates = candidates(&rows, "tag");
+        assert_eq!(
+            candidates.keys().collect::<Vec<_>>(),
+            ["bytes::complete::tag", "nom_supreme::tag::complete::tag"]
+        );
+        assert_eq!(candidates["bytes::complete::tag"].len(), 2);
+        assert_eq!(
+            super::candidates(&rows, "nom_supreme::tag::complete::tag").len(),
+            1
+        );
+        assert_eq!(
+            super::candidates(&rows, "nom::bytes::complete::tag")
+                .keys()
+                .collect::<Vec<_>>(),
+            ["bytes::complete::tag"]
+        );
+    }
+
+    #[test]
+    fn test_format_rows() {
+        let row = row(0, "bytes::complete", "tag(\"hello\")");
+        assert_eq!(
+            format_rows(&[&row], &Style { color: false }),
+            "\
+bytes::complete::tag
+https://docs.rs/nom/latest/nom/bytes/complete/fn.tag.html
+In \"Basic elements\", for beginner users
+
+  Usage:  tag(\"hello\")
+  Input:  \"hello world\"
+  Output: Result: `\"hello\"`
+          Remainder: `\" world\"`
+
+  Recognizes a `tag`
+"
+        );
+    }
+
+    #[test]
+    fn test_search_rows() {
+        let mut rows = [
+            row(0, "bytes::complete", "tag(\"hello\")"),
+            row(1, "bytes::complete", "take_until(\"\\\"\")"),
+            row(
+                2,
+                "sequence",
+                "delimited(char('\"'), is_not(\"\\\"\"), char('\"'))",
+            ),
+        ];
+        rows[1].combinators[0].name = "take_until";
+        rows[1].description = "Returns the input until the next quote";
+        rows[2].description = "Everything between quotes";
+        let ids = |query| {
+            search_rows(&rows, query, 5)
+                .iter()
+                .map(|row| row.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids("until the next quote"), [1, 2]);
+        assert_eq!(ids("TAG"), [0, 2]);
+        assert!(ids("separated").is_empty());
+        assert_eq!(search_rows(&rows, "quote", 1).len(), 1);
+    }
+}
@@ -0,0 +1,180 @@
+//! Writes the sheet as a site with a page per section, in `site/`, which
+//! loads a lot faster on phones than the single page does. What comes
+//! before the first section goes on the index page, and a sidebar on every
+//! page links to all of them.
+
+use crate::{
+    html::{self, Artifact},
+    sheet::{self, Row, Section},
+};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufWriter, Result, Write},
+    path::{Path, PathBuf},
+};
+
+/// The file that the page of a section gets written to
+fn file_name(section: &Section) -> String {
+    format!("{}.html", section.anchor)
+}
+
+/// Links to the index and to every section, marking the one at `current`
+fn sidebar(sections: &[Section], current: Option<usize>) -> String {
+    let mut sidebar = format!(
+        "<nav class=\"sidebar\">\n<p><a href=\"index.html\">{}</a></p>\n<ul>\n",
+        html::title_html(html::TITLE)
+    );
+    for (index, section) in sections.iter().enumerate() {
+        let current = if current == Some(index) {
+            r#" aria-current="page""#
+        } else {
+            ""
+        };
+        sidebar.push_str(&format!(
+            "<li><a href=\"{}\"{current}>{}</a></li>\n",
+            file_name(section),
+            html::title_html(&section.title)
+        ));
+    }
+    sidebar.push_str("</ul>\n</nav>\n");
+    sidebar
+}
+
+/// The ids of the elements in `html`
+pub(crate) fn ids(html: &str) -> impl Iterator<Item = &str> {
+    html.split(" id=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+}
+
+/// Points the links to anchors on other pages at those pages, both in HTML
+/// and in markdown. `pages` has the page that each id is on.
+pub(crate) fn link_across_pages(text: &str, page: &str, pages: &HashMap<&str, &str>) -> String {
+    let mut linked = String::new();
+    let mut rest = text;
+    while let Some(start) = ["href=\"#", "](#"]
+        .iter()
+        .filter_map(|link| Some(rest.find(link)? + link.len() - 1))
+        .min()
+    {
+        let (before, after) = rest.split_at(start);
+        linked.push_str(before);
+        let end = after.find(['"', ')']).unwrap_or(after.len());
+        if let Some(other) = pages.get(&after[1..end]).filter(|other| **other != page) {
+            linked.push_str(other);
+        }
+        rest = after;
+    }
+    linked.push_str(rest);
+    linked
+}
+
+/// Writes the pages for `markdown`, which is the sheet as the HTML has it,
+/// to `dist/site/`, and returns the path of the index page
+pub fn write_site(
+    dist: &Path,
+    markdown: &str,
+    rows: &[Row],
+    artifacts: &[Artifact],
+    provenance: &str,
+) -> Result<PathBuf> {
+    let dir = dist.join("site");
+    fs::create_dir_all(&dir)?;
+    let (intro, sections) = sheet::sections(markdown);
+    let mut pages = vec![("index.html".to_string(), html::render(&intro))];
+    for section in &sections {
+        pages.push((file_name(section), html::render(&section.markdown)));
+    }
+    let mut page_of_id = HashMap::new();
+    for (file_name, html) in &pages {
+        for id in ids(html) {
+            page_of_id.entry(id).or_insert(file_name.as_str());
+        }
+    }
+
+    for (index, (file_name, html)) in pages.iter().enumerate() {
+        let path = dir.join(file_name);
+        let mut html_file = BufWriter::new(File::create(&path)?);
+        let section = index.checked_sub(1).map(|index| &sections[index]);
+        let title = match section {
+            Some(section) => format!(
+                "{} - {}",
+                html::title_html(&section.title),
+                html::title_html(html::TITLE)
+            ),
+            None => html::title_html(html::TITLE),
+        };
+        html::write_page_start(&mut html_file, &title)?;
+        html_file.write_all(sidebar(&sections, index.checked_sub(1)).as_bytes())?;
+        html_file.write_all("<article>\n".as_bytes())?;
+        // The index has no rows, so it has nothing for the scripts to do
+        let page_rows = section.map(|section| {
+            rows.iter()
+                .filter(|row| {
+                    section
+                        .markdown
+                        .contains(&format!("<!-- row = {} -->", row.id))
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+        if let Some(rows) = &page_rows {
+            html::write_level_selector(&mut html_file)?;
+            html::write_tag_bar(&mut html_file, rows)?;
+        }
+        html_file.write_all(link_across_pages(html, file_name, &page_of_id).as_bytes())?;
+        html_file.write_all("</article>\n".as_bytes())?;
+        html::write_footer(
+            &mut html_file,
+            &path,
+            "../",
+            artifacts,
+            &html::footer_provenance(provenance),
+        )?;
+        match &page_rows {
+            Some(rows) => html::write_page_end(&mut html_file, rows)?,
+            None => html_file.write_all("</body>\n</html>\n".as_bytes())?,
+        }
+        html_file.flush()?;
+    }
+    Ok(dir.join("index.html"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_across_pages() {
+        let pages = HashMap::from([("alt", "choice.html"), ("tag", "basic.html")]);
+        assert_eq!(
+            link_across_pages(
+                r##"<a href="#alt">alt</a>, <a href="#tag">tag</a>, <a href="#gone">?</a>"##,
+                "basic.html",
+                &pages
+            ),
+            r##"<a href="choice.html#alt">alt</a>, <a href="#tag">tag</a>, <a href="#gone">?</a>"##
+        );
+        assert_eq!(
+            link_across_pages("[`alt`](#alt) or [`tag`](#tag)", "basic.md", &pages),
+            "[`alt`](choice.html#alt) or [`tag`](basic.html#tag)"
+        );
+        assert_eq!(
+            ids(r#"<h2><a class="anchor" id="tag"></a></h2><a id="nom-tag"></a>"#)
+                .collect::<Vec<_>>(),
+            ["tag", "nom-tag"]
+        );
+    }
+
+    #[test]
+    fn test_sidebar() {
+        let (_, sections) = sheet::sections("## Basic `tag`\n\n## Choice\n");
+        assert_eq!(
+            sidebar(&sections, Some(1)),
+            "<nav class=\"sidebar\">\n<p><a href=\"index.html\">Nom Cheatsheet</a></p>\n<ul>\n\
+             <li><a href=\"basic-tag.html\">Basic tag</a></li>\n\
+             <li><a href=\"choice.html\" aria-current=\"page\">Choice</a></li>\n</ul>\n</nav>\n"
+        );
+    }
+}
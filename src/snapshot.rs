@@ -0,0 +1,237 @@
+//! Checks that the generated sheets are the same as the ones that are checked
+//! in, like `nom-cheatsheet generate --check`. After a nom upgrade, rows
+//! whose outputs changed show up as a diff, instead of going into `dist`
+//! without anyone noticing.
+
+use crate::show::Style;
+use std::{
+    fs,
+    io::{ErrorKind, Result},
+    path::Path,
+    process::ExitCode,
+};
+
+/// How many unchanged lines to show around each change
+const CONTEXT: usize = 3;
+
+#[derive(Debug, PartialEq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// The lines of `old` and `new`, with the fewest removed and added lines
+/// that turn one into the other. Generated sheets mostly change in a few
+/// rows, so the common start and end are taken off before the expensive
+/// part. Where a line was replaced, the old one comes first.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // The length of the longest common subsequence of every pair of tails
+    let width = new_middle.len() + 1;
+    let mut lengths = vec![0_usize; (old_middle.len() + 1) * width];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lengths[i * width + j] = if old_middle[i] == new_middle[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines = old[..prefix]
+        .iter()
+        .map(|line| Line::Same(line))
+        .collect::<Vec<_>>();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            lines.push(Line::Same(old_middle[i]));
+            i += 1;
+            j += 1;
+        } else if i < old_middle.len()
+            && (j == new_middle.len() || lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            lines.push(Line::Removed(old_middle[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(new_middle[j]));
+            j += 1;
+        }
+    }
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| Line::Same(line)),
+    );
+    lines
+}
+
+/// The changes as a unified diff, with the removed lines in red and the
+/// added ones in green
+fn format_diff(lines: &[Line], style: &Style) -> String {
+    let changed = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Line::Same(_)))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    // Changes that are close together share a hunk
+    let mut hunks = Vec::<(usize, usize)>::new();
+    for index in changed {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut text = String::new();
+    for (start, end) in hunks {
+        // Line numbers in the old and new file where the hunk starts
+        let before = &lines[..start];
+        let old_start = before
+            .iter()
+            .filter(|line| !matches!(line, Line::Added(_)))
+            .count();
+        let new_start = before
+            .iter()
+            .filter(|line| !matches!(line, Line::Removed(_)))
+            .count();
+        let hunk = &lines[start..end];
+        let old_count = hunk
+            .iter()
+            .filter(|line| !matches!(line, Line::Added(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|line| !matches!(line, Line::Removed(_)))
+            .count();
+        let header = format!(
+            "@@ -{},{old_count} +{},{new_count} @@",
+            old_start + 1,
+            new_start + 1
+        );
+        text.push_str(&style.paint("36", &header));
+        text.push('\n');
+        for line in hunk {
+            let line = match line {
+                Line::Same(line) => format!(" {line}"),
+                Line::Removed(line) => style.paint("31", &format!("-{line}")),
+                Line::Added(line) => style.paint("32", &format!("+{line}")),
+            };
+            text.push_str(&line);
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// The changes that turn `old` into `new`, as a unified diff
+pub(crate) fn diff(old: &str, new: &str, style: &Style) -> String {
+    format_diff(&diff_lines(old, new), style)
+}
+
+/// Compares `generated` with the file at `path`, printing the differences
+/// and failing if there are any
+pub fn check(path: &Path, generated: &str) -> Result<ExitCode> {
+    let committed = match fs::read_to_string(path) {
+        Ok(committed) => committed,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            eprintln!("{} doesn't exist, generate it first", path.display());
+            return Ok(ExitCode::FAILURE);
+        }
+        Err(e) => return Err(e),
+    };
+    if committed == generated {
+        println!("{} is up to date", path.display());
+        return Ok(ExitCode::SUCCESS);
+    }
+    let style = Style::stdout();
+    println!("{}", style.bold(&format!("--- {}", path.display())));
+    println!("{}", style.bold("+++ generated"));
+    print!("{}", diff(&committed, generated, &style));
+    if committed.lines().eq(generated.lines()) {
+        println!("The line endings differ");
+    }
+    eprintln!(
+        "{} is out of date, run `cargo run -- generate` to update it",
+        path.display()
+    );
+    Ok(ExitCode::FAILURE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(
+            diff_lines("a\nb\nc\nd\n", "a\nc\nx\nd\n"),
+            [
+                Line::Same("a"),
+                Line::Removed("b"),
+                Line::Same("c"),
+                Line::Added("x"),
+                Line::Same("d"),
+            ]
+        );
+        assert_eq!(diff_lines("", "a\n"), [Line::Added("a")]);
+        assert_eq!(diff_lines("a\n", ""), [Line::Removed("a")]);
+    }
+
+    #[test]
+    fn test_format_diff() {
+        let old = (1..=20).map(|n| format!("{n}\n")).collect::<String>();
+        let new = (1..=20)
+            .filter(|n| *n != 18)
+            .map(|n| match n {
+                2 => "two\n".to_string(),
+                n => format!("{n}\n"),
+            })
+            .collect::<String>();
+        let lines = diff_lines(&old, &new);
+        assert_eq!(
+            format_diff(&lines, &Style { color: false }),
+            "\
+@@ -1,5 +1,5 @@
+ 1
+-2
++two
+ 3
+ 4
+ 5
+@@ -15,6 +15,5 @@
+ 15
+ 16
+ 17
+-18
+ 19
+ 20
+"
+        );
+        assert_eq!(
+            format_diff(&lines, &Style { color: true }),
+            format_diff(&lines, &Style { color: false })
+                .replace("@@ -1,5 +1,5 @@", "\x1b[36m@@ -1,5 +1,5 @@\x1b[0m")
+                .replace("@@ -15,6 +15,5 @@", "\x1b[36m@@ -15,6 +15,5 @@\x1b[0m")
+                .replace("-2\n", "\x1b[31m-2\x1b[0m\n")
+                .replace("+two\n", "\x1b[32m+two\x1b[0m\n")
+                .replace("-18\n", "\x1b[31m-18\x1b[0m\n")
+        );
+    }
+}
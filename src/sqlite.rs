@@ -0,0 +1,248 @@
+//! Writes SQLite databases with a single table, which is all that the search
+//! index of a docset needs. The whole table is written at once, so there's no
+//! free space to keep track of, and no need for SQLite itself.
+//!
+//! See https://www.sqlite.org/fileformat.html for the format.
+
+const PAGE_SIZE: usize = 4096;
+/// The most that a cell of a table leaf can hold without overflow pages,
+/// which aren't written here
+const MAX_LOCAL_PAYLOAD: usize = PAGE_SIZE - 35;
+const INTERIOR_TABLE: u8 = 0x05;
+const LEAF_TABLE: u8 = 0x0d;
+
+pub enum Value {
+    /// What an `INTEGER PRIMARY KEY` column holds, as its value is the rowid
+    Null,
+    Integer(i64),
+    Text(String),
+}
+
+/// A variable-length integer, most significant group first
+fn varint(value: u64, bytes: &mut Vec<u8>) {
+    // Larger values have a different last byte, and aren't needed here
+    assert!(value < 1 << 56, "varint too large");
+    let mut groups = vec![(value & 0x7f) as u8];
+    let mut rest = value >> 7;
+    while rest > 0 {
+        groups.push((rest & 0x7f) as u8 | 0x80);
+        rest >>= 7;
+    }
+    bytes.extend(groups.iter().rev());
+}
+
+fn record(values: &[Value]) -> Vec<u8> {
+    let mut types = Vec::new();
+    let mut body = Vec::<u8>::new();
+    for value in values {
+        match value {
+            Value::Null => varint(0, &mut types),
+            Value::Integer(0) => varint(8, &mut types),
+            Value::Integer(1) => varint(9, &mut types),
+            Value::Integer(integer) => {
+                let (serial_type, size) = [(1, 1), (2, 2), (3, 3), (4, 4), (5, 6), (6, 8)]
+                    .into_iter()
+                    .find(|(_, size)| {
+                        let bits = size * 8 - 1;
+                        bits == 63 || (-(1 << bits)..1 << bits).contains(integer)
+                    })
+                    .unwrap();
+                varint(serial_type, &mut types);
+                body.extend(&integer.to_be_bytes()[8 - size..]);
+            }
+            Value::Text(text) => {
+                varint(text.len() as u64 * 2 + 13, &mut types);
+                body.extend(text.as_bytes());
+            }
+        }
+    }
+    // The header's size counts itself, which is a single byte for the few
+    // columns of the tables here
+    assert!(types.len() < 127, "too many columns");
+    let mut record = vec![types.len() as u8 + 1];
+    record.extend(types);
+    record.extend(body);
+    record
+}
+
+fn leaf_cell(rowid: i64, values: &[Value]) -> Vec<u8> {
+    let payload = record(values);
+    assert!(
+        payload.len() <= MAX_LOCAL_PAYLOAD,
+        "row {rowid} needs overflow pages"
+    );
+    let mut cell = Vec::new();
+    varint(payload.len() as u64, &mut cell);
+    varint(rowid as u64, &mut cell);
+    cell.extend(payload);
+    cell
+}
+
+/// A b-tree page with the cells in order. Page 1 has the database header in
+/// front of that.
+fn page(kind: u8, cells: &[Vec<u8>], right_most: Option<u32>, offset: usize) -> Vec<u8> {
+    let mut page = vec![0; PAGE_SIZE];
+    let header_size = if kind == INTERIOR_TABLE { 12 } else { 8 };
+    let mut pointer = offset + header_size;
+    let mut content = PAGE_SIZE;
+    for cell in cells {
+        content -= cell.len();
+        page[content..content + cell.len()].copy_from_slice(cell);
+        page[pointer..pointer + 2].copy_from_slice(&(content as u16).to_be_bytes());
+        pointer += 2;
+    }
+    assert!(pointer <= content, "cells don't fit in the page");
+    let header = &mut page[offset..];
+    header[0] = kind;
+    header[3..5].copy_from_slice(&(cells.len() as u16).to_be_bytes());
+    header[5..7].copy_from_slice(&(content as u16).to_be_bytes());
+    if let Some(right_most) = right_most {
+        header[8..12].copy_from_slice(&right_most.to_be_bytes());
+    }
+    page
+}
+
+/// Splits the cells into runs that fit in a page each, keeping at least two
+/// in every run so that interior pages always have a cell
+fn pack<T>(cells: Vec<(Vec<u8>, T)>, header_size: usize) -> Vec<Vec<(Vec<u8>, T)>> {
+    let mut pages = vec![Vec::new()];
+    let mut used = header_size;
+    for cell in cells {
+        if used + cell.0.len() + 2 > PAGE_SIZE {
+            pages.push(Vec::new());
+            used = header_size;
+        }
+        used += cell.0.len() + 2;
+        pages.last_mut().unwrap().push(cell);
+    }
+    if pages.len() > 1 && pages.last().unwrap().len() < 2 {
+        let [.., previous, last] = &mut pages[..] else {
+            unreachable!()
+        };
+        last.insert(0, previous.pop().unwrap());
+    }
+    pages
+}
+
+/// The pages of a table, numbered from `first_page`, and the number of its
+/// root page, which comes last
+fn table_pages(rows: &[Vec<Value>], first_page: u32) -> (Vec<Vec<u8>>, u32) {
+    let cells = rows
+        .iter()
+        .enumerate()
+        .map(|(index, values)| {
+            let rowid = index as i64 + 1;
+            (leaf_cell(rowid, values), rowid)
+        })
+        .collect();
+    let mut pages = Vec::new();
+    // The pages of the level that's being written, with their largest rowid
+    let mut children = Vec::new();
+    for leaf in pack(cells, 8) {
+        let page_number = first_page + pages.len() as u32;
+        children.push((page_number, leaf.last().map_or(0, |(_, rowid)| *rowid)));
+        let cells = leaf.into_iter().map(|(cell, _)| cell).collect::<Vec<_>>();
+        pages.push(page(LEAF_TABLE, &cells, None, 0));
+    }
+    while children.len() > 1 {
+        let cells = children
+            .drain(..)
+            .map(|(child, rowid)| {
+                let mut cell = child.to_be_bytes().to_vec();
+                varint(rowid as u64, &mut cell);
+                (cell, (child, rowid))
+            })
+            .collect();
+        for mut interior in pack(cells, 12) {
+            let (_, (right_most, largest)) = interior.pop().unwrap();
+            let page_number = first_page + pages.len() as u32;
+            children.push((page_number, largest));
+            let cells = interior
+                .into_iter()
+                .map(|(cell, _)| cell)
+                .collect::<Vec<_>>();
+            pages.push(page(INTERIOR_TABLE, &cells, Some(right_most), 0));
+        }
+    }
+    let root = first_page + pages.len() as u32 - 1;
+    (pages, root)
+}
+
+/// A database with the table `name`, created by `sql`, that has `rows`
+pub fn database(name: &str, sql: &str, rows: &[Vec<Value>]) -> Vec<u8> {
+    let (table_pages, root) = table_pages(rows, 2);
+    let schema = leaf_cell(
+        1,
+        &[
+            Value::Text("table".to_string()),
+            Value::Text(name.to_string()),
+            Value::Text(name.to_string()),
+            Value::Integer(root.into()),
+            Value::Text(sql.to_string()),
+        ],
+    );
+    let mut database = page(LEAF_TABLE, &[schema], None, 100);
+    let page_count = table_pages.len() as u32 + 1;
+    let header = &mut database[..100];
+    header[..16].copy_from_slice(b"SQLite format 3\0");
+    header[16..18].copy_from_slice(&(PAGE_SIZE as u16).to_be_bytes());
+    // File format versions, no reserved space, and the payload fractions
+    // that are required
+    header[18..24].copy_from_slice(&[1, 1, 0, 64, 32, 32]);
+    // The change counter, which the version-valid-for number has to match
+    header[24..28].copy_from_slice(&1_u32.to_be_bytes());
+    header[92..96].copy_from_slice(&1_u32.to_be_bytes());
+    header[28..32].copy_from_slice(&page_count.to_be_bytes());
+    // The schema cookie and format
+    header[40..44].copy_from_slice(&1_u32.to_be_bytes());
+    header[44..48].copy_from_slice(&4_u32.to_be_bytes());
+    // UTF-8
+    header[56..60].copy_from_slice(&1_u32.to_be_bytes());
+    header[96..100].copy_from_slice(&3_046_000_u32.to_be_bytes());
+    for page in table_pages {
+        database.extend(page);
+    }
+    database
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut bytes = Vec::new();
+        varint(300, &mut bytes);
+        assert_eq!(bytes, [0x82, 0x2c]);
+        assert_eq!(
+            record(&[
+                Value::Null,
+                Value::Integer(1),
+                Value::Integer(-2),
+                Value::Integer(1000),
+                Value::Text("ab".to_string()),
+            ]),
+            [6, 0, 9, 1, 2, 17, 0xfe, 0x03, 0xe8, b'a', b'b']
+        );
+    }
+
+    #[test]
+    fn test_database() {
+        let rows = (0..1000)
+            .map(|index| vec![Value::Null, Value::Text(format!("row {index}"))])
+            .collect::<Vec<_>>();
+        let database = database(
+            "t",
+            "CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)",
+            &rows,
+        );
+        assert_eq!(database.len() % PAGE_SIZE, 0);
+        let page_count = u32::from_be_bytes(database[28..32].try_into().unwrap());
+        assert_eq!(page_count as usize, database.len() / PAGE_SIZE);
+        // The root of the table is the last page, and an interior one
+        assert_eq!(
+            database[(page_count as usize - 1) * PAGE_SIZE],
+            INTERIOR_TABLE
+        );
+    }
+}
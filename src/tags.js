@@ -0,0 +1,36 @@
+// Shows only the rows with the tag that's picked in the tag bar, going by
+// the index that was written into the page when it was generated. Picking
+// the same tag again shows all rows again. Rows carry their id in an HTML
+// comment like `<!-- row = 12 -->`.
+(() => {
+    const bar = document.querySelector(".tags");
+    if (!bar) {
+        return;
+    }
+    const index = JSON.parse(document.getElementById("search-index").textContent);
+    const tags = new Map(index.map((entry) => [String(entry.row), entry.tags]));
+    const rows = [];
+    const comments = document.createTreeWalker(
+        document.querySelector("article"),
+        NodeFilter.SHOW_COMMENT,
+    );
+    while (comments.nextNode()) {
+        const match = comments.currentNode.data.match(/row = (\d+)/);
+        const row = comments.currentNode.parentElement.closest("tr");
+        if (match && row && tags.has(match[1])) {
+            rows.push([row, tags.get(match[1])]);
+        }
+    }
+    const buttons = bar.querySelectorAll("button");
+    for (const button of buttons) {
+        button.addEventListener("click", () => {
+            const picked = button.getAttribute("aria-pressed") === "true" ? null : button.dataset.tag;
+            for (const other of buttons) {
+                other.setAttribute("aria-pressed", String(other.dataset.tag === picked));
+            }
+            for (const [row, rowTags] of rows) {
+                row.classList.toggle("tag-miss", picked !== null && !rowTags.includes(picked));
+            }
+        });
+    }
+})();
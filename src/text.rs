@@ -0,0 +1,529 @@
+//! Writes the cheatsheet as text for the terminal, like
+//! `nom-cheatsheet text | less -R`, with the prose wrapped and the tables
+//! laid out to fit the width of the terminal. Tables that can't fit their
+//! columns side by side get a block per row instead, with the headers as
+//! labels.
+
+use crate::{
+    cache::Cache,
+    generated::generate,
+    sheet::{self, Level},
+    show::Style,
+};
+use clap::ValueEnum;
+use comrak::{
+    nodes::{AstNode, ListType, NodeValue},
+    parse_document, Arena, Options,
+};
+use std::{
+    env,
+    io::{self, Result, Write},
+    process::ExitCode,
+    str,
+};
+
+/// The narrowest that columns get before the table goes to a block per row
+const MIN_COLUMN_WIDTH: usize = 12;
+static COLUMN_SEPARATOR: &str = " │ ";
+
+/// When to color the output
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Color {
+    /// When it goes to a terminal, and `NO_COLOR` isn't set
+    Auto,
+    /// Also when it's piped, like into `less -R`
+    Always,
+    Never,
+}
+
+/// A word of the text, or a line break
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Word {
+        text: String,
+        /// The ANSI codes to paint it with, if any
+        code: &'static str,
+        /// Whether there's a space in front of it, which is where lines can
+        /// be broken
+        space: bool,
+    },
+    Break,
+}
+
+/// The pieces of the inline markdown in `node`
+fn pieces<'a>(node: &'a AstNode<'a>) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut space = false;
+    for child in node.children() {
+        inline(child, "", &mut space, &mut pieces);
+    }
+    pieces
+}
+
+fn inline<'a>(
+    node: &'a AstNode<'a>,
+    code: &'static str,
+    space: &mut bool,
+    pieces: &mut Vec<Piece>,
+) {
+    let value = node.data.borrow().value.clone();
+    let mut word = |text: &str, code, space: &mut bool| {
+        pieces.push(Piece::Word {
+            text: text.to_string(),
+            code,
+            space: *space,
+        });
+        *space = false;
+    };
+    match value {
+        NodeValue::Text(text) => {
+            for (index, text) in text.split(' ').enumerate() {
+                *space |= index > 0;
+                if !text.is_empty() {
+                    word(text, code, space);
+                }
+            }
+        }
+        NodeValue::SoftBreak => *space = true,
+        NodeValue::LineBreak => {
+            pieces.push(Piece::Break);
+            *space = false;
+        }
+        NodeValue::Code(inline_code) => word(&format!("`{}`", inline_code.literal), "36", space),
+        NodeValue::HtmlInline(html) if html == "<br>" => {
+            pieces.push(Piece::Break);
+            *space = false;
+        }
+        // Anchors and comments don't show
+        NodeValue::HtmlInline(_) => {}
+        NodeValue::Emph => {
+            for child in node.children() {
+                inline(child, "3", space, pieces);
+            }
+        }
+        NodeValue::Strong => {
+            for child in node.children() {
+                inline(child, "1", space, pieces);
+            }
+        }
+        NodeValue::Link(_) => {
+            for child in node.children() {
+                inline(child, "4", space, pieces);
+            }
+        }
+        _ => {
+            for child in node.children() {
+                inline(child, code, space, pieces);
+            }
+        }
+    }
+}
+
+/// How wide text is on the screen, which is close enough to its number of
+/// characters for the sheet
+fn text_width(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// The widths of the lines of the pieces if they weren't wrapped
+fn line_widths(pieces: &[Piece]) -> Vec<usize> {
+    let mut widths = vec![0];
+    for piece in pieces {
+        match piece {
+            Piece::Word { text, space, .. } => {
+                let width = widths.last_mut().unwrap();
+                *width += text_width(text) + usize::from(*space && *width > 0);
+            }
+            Piece::Break => widths.push(0),
+        }
+    }
+    widths
+}
+
+/// Wraps the pieces to `width`, and returns the painted lines with how wide
+/// they are. Words that are wider than that on their own get split.
+fn wrap(pieces: &[Piece], width: usize, style: &Style) -> Vec<(String, usize)> {
+    let mut lines = vec![(String::new(), 0)];
+    for piece in pieces {
+        let Piece::Word { text, code, space } = piece else {
+            lines.push((String::new(), 0));
+            continue;
+        };
+        let (line, line_width) = lines.last_mut().unwrap();
+        let space = *space && *line_width > 0;
+        let word_width = text_width(text);
+        // Words that are right after another, like a path after its module,
+        // only go on the next line if they'd fit there
+        let fits_next_line = space || word_width <= width;
+        if *line_width > 0
+            && *line_width + usize::from(space) + word_width > width
+            && fits_next_line
+        {
+            lines.push((String::new(), 0));
+        } else if space {
+            line.push(' ');
+            *line_width += 1;
+        }
+        let mut rest = text.as_str();
+        loop {
+            let (line, line_width) = lines.last_mut().unwrap();
+            let room = width.saturating_sub(*line_width).max(1);
+            let split = rest
+                .char_indices()
+                .nth(room)
+                .map_or(rest.len(), |(index, _)| index);
+            let (part, more) = rest.split_at(split);
+            line.push_str(&paint(style, code, part));
+            *line_width += text_width(part);
+            if more.is_empty() {
+                break;
+            }
+            lines.push((String::new(), 0));
+            rest = more;
+        }
+    }
+    lines
+}
+
+fn paint(style: &Style, code: &str, text: &str) -> String {
+    if code.is_empty() {
+        text.to_string()
+    } else {
+        style.paint(code, text)
+    }
+}
+
+/// The widths of the columns of a table, which only get narrower than what
+/// they need when everything doesn't fit. Columns that need less than an
+/// even share of the room get what they need, and the others share the rest
+/// by how much they need. `None` if that makes columns too narrow to read.
+fn column_widths(needed: &[usize], room: usize) -> Option<Vec<usize>> {
+    let mut widths = needed.to_vec();
+    if needed.iter().sum::<usize>() <= room {
+        return Some(widths);
+    }
+    let mut open = (0..needed.len()).collect::<Vec<_>>();
+    let mut left = room;
+    loop {
+        let share = left / open.len();
+        let (fitting, wide) = open
+            .iter()
+            .partition::<Vec<usize>, _>(|&&column| needed[column] <= share);
+        if fitting.is_empty() {
+            let total = wide.iter().map(|&column| needed[column]).sum::<usize>();
+            for column in wide {
+                widths[column] = left * needed[column] / total;
+                if widths[column] < MIN_COLUMN_WIDTH {
+                    return None;
+                }
+            }
+            return Some(widths);
+        }
+        left -= fitting.iter().map(|&column| needed[column]).sum::<usize>();
+        open = wide;
+    }
+}
+
+struct Writer {
+    width: usize,
+    style: Style,
+    text: String,
+}
+
+impl Writer {
+    fn line(&mut self, indent: &str, line: &str) {
+        self.text.push_str(indent);
+        self.text.push_str(line);
+        // Indents of empty lines would only be trailing whitespace
+        if line.is_empty() {
+            self.text.truncate(self.text.trim_end_matches(' ').len());
+        }
+        self.text.push('\n');
+    }
+
+    fn paragraph(&mut self, indent: &str, pieces: &[Piece]) {
+        let width = self.width.saturating_sub(text_width(indent));
+        for (line, _) in wrap(pieces, width, &self.style) {
+            self.line(indent, &line);
+        }
+    }
+
+    /// Writes the blocks in `node`, with blank lines between them unless
+    /// they're in an item of a tight list
+    fn blocks<'a>(&mut self, node: &'a AstNode<'a>, indent: &str, tight: bool) {
+        // Comments and anchors are all that the HTML of the sheet has
+        let children = node
+            .children()
+            .filter(|child| !matches!(child.data.borrow().value, NodeValue::HtmlBlock(_)));
+        for (index, child) in children.enumerate() {
+            if index > 0 && !tight {
+                self.line("", "");
+            }
+            self.block(child, indent);
+        }
+    }
+
+    fn block<'a>(&mut self, node: &'a AstNode<'a>, indent: &str) {
+        let value = node.data.borrow().value.clone();
+        match value {
+            NodeValue::Paragraph => self.paragraph(indent, &pieces(node)),
+            NodeValue::Heading(heading) => {
+                let pieces = pieces(node);
+                let title = wrap(&pieces, usize::MAX, &Style { color: false })
+                    .remove(0)
+                    .0;
+                let code = if heading.level <= 2 { "1;34" } else { "1" };
+                self.line(indent, &self.style.paint(code, &title));
+                let underline = match heading.level {
+                    1 => "═",
+                    2 => "─",
+                    _ => return,
+                };
+                let underline = underline.repeat(text_width(&title));
+                self.line(indent, &self.style.paint("34", &underline));
+            }
+            NodeValue::CodeBlock(code_block) => {
+                let indent = format!("{indent}    ");
+                for line in code_block.literal.trim_end().lines() {
+                    self.line(&indent, &self.style.paint("32", line));
+                }
+            }
+            NodeValue::BlockQuote => {
+                self.blocks(
+                    node,
+                    &format!("{indent}{}", self.style.paint("2", "│ ")),
+                    false,
+                );
+            }
+            NodeValue::List(list) => {
+                for (index, item) in node.children().enumerate() {
+                    if index > 0 && !list.tight {
+                        self.line("", "");
+                    }
+                    let marker = match list.list_type {
+                        ListType::Bullet => "• ".to_string(),
+                        ListType::Ordered => format!("{}. ", list.start + index),
+                    };
+                    let mut item_writer = Writer {
+                        width: self.width,
+                        style: Style {
+                            color: self.style.color,
+                        },
+                        text: String::new(),
+                    };
+                    let hanging = format!("{indent}{}", " ".repeat(text_width(&marker)));
+                    item_writer.blocks(item, &hanging, list.tight);
+                    // The marker goes where the indent of the first line is
+                    let text = item_writer
+                        .text
+                        .strip_prefix(&hanging)
+                        .unwrap_or(&item_writer.text);
+                    self.text.push_str(indent);
+                    self.text.push_str(&marker);
+                    self.text.push_str(text);
+                }
+            }
+            NodeValue::ThematicBreak => {
+                let rule = "─".repeat(self.width.saturating_sub(text_width(indent)));
+                self.line(indent, &self.style.paint("2", &rule));
+            }
+            NodeValue::Table(_) => self.table(node, indent),
+            _ => self.blocks(node, indent, false),
+        }
+    }
+
+    fn table<'a>(&mut self, node: &'a AstNode<'a>, indent: &str) {
+        let rows = node
+            .children()
+            .map(|row| row.children().map(pieces).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let Some((header, body)) = rows.split_first() else {
+            return;
+        };
+        let columns = header.len();
+        let needed = (0..columns)
+            .map(|column| {
+                rows.iter()
+                    .filter_map(|row| row.get(column))
+                    .flat_map(|cell| line_widths(cell))
+                    .max()
+                    .unwrap_or(0)
+                    .max(1)
+            })
+            .collect::<Vec<_>>();
+        let room = self
+            .width
+            .saturating_sub(text_width(indent) + (columns - 1) * text_width(COLUMN_SEPARATOR));
+        match column_widths(&needed, room) {
+            Some(widths) => {
+                self.table_row(indent, header, &widths, "1");
+                let rule = widths
+                    .iter()
+                    .map(|width| "─".repeat(*width))
+                    .collect::<Vec<_>>()
+                    .join("─┼─");
+                self.line(indent, &self.style.paint("2", &rule));
+                for row in body {
+                    self.table_row(indent, row, &widths, "");
+                }
+            }
+            None => self.table_blocks(indent, header, body),
+        }
+    }
+
+    fn table_row(&mut self, indent: &str, cells: &[Vec<Piece>], widths: &[usize], code: &str) {
+        let wrapped = widths
+            .iter()
+            .enumerate()
+            .map(|(column, width)| {
+                cells
+                    .get(column)
+                    .map_or_else(Vec::new, |cell| wrap(cell, *width, &self.style))
+            })
+            .collect::<Vec<_>>();
+        let height = wrapped.iter().map(Vec::len).max().unwrap_or(0);
+        let separator = self.style.paint("2", COLUMN_SEPARATOR);
+        for index in 0..height {
+            let mut line = String::new();
+            for (column, width) in widths.iter().enumerate() {
+                if column > 0 {
+                    line.push_str(&separator);
+                }
+                let (text, text_width) = wrapped[column]
+                    .get(index)
+                    .map_or((String::new(), 0), |(text, width)| (text.clone(), *width));
+                line.push_str(&paint(&self.style, code, &text));
+                line.push_str(&" ".repeat(width.saturating_sub(text_width)));
+            }
+            self.line(indent, line.trim_end());
+        }
+    }
+
+    /// A block per row, for tables that are too wide, with the cells under
+    /// each other after the headers of their columns
+    fn table_blocks(&mut self, indent: &str, header: &[Vec<Piece>], body: &[Vec<Vec<Piece>>]) {
+        let labels = header
+            .iter()
+            .map(|cell| wrap(cell, usize::MAX, &Style { color: false }).remove(0).0)
+            .collect::<Vec<_>>();
+        let label_width = labels
+            .iter()
+            .map(|label| text_width(label))
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let width = self.width.saturating_sub(text_width(indent) + label_width);
+        for (index, row) in body.iter().enumerate() {
+            if index > 0 {
+                self.line("", "");
+            }
+            for (label, cell) in labels.iter().zip(row) {
+                for (line_index, (line, _)) in wrap(cell, width, &self.style).iter().enumerate() {
+                    let label = match line_index {
+                        0 => format!("{label}:"),
+                        _ => String::new(),
+                    };
+                    let padding = " ".repeat(label_width - text_width(&label));
+                    let label = self.style.bold(&label);
+                    if line.is_empty() && line_index == 0 {
+                        continue;
+                    }
+                    self.line(indent, &format!("{label}{padding}{line}"));
+                }
+            }
+        }
+    }
+}
+
+/// The markdown as text that fits in `width` columns
+fn render(markdown: &str, width: usize, style: Style) -> String {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    let root = parse_document(&arena, markdown, &options);
+    let mut writer = Writer {
+        width,
+        style,
+        text: String::new(),
+    };
+    writer.blocks(root, "", false);
+    writer.text
+}
+
+/// The width of the terminal, which `less` also goes by, or otherwise
+/// `COLUMNS`, or 100 columns
+fn terminal_width() -> usize {
+    ratatui::crossterm::terminal::size()
+        .ok()
+        .map(|(columns, _)| columns as usize)
+        .or_else(|| env::var("COLUMNS").ok()?.parse().ok())
+        .unwrap_or(100)
+}
+
+/// Prints the sheet as text, `width` columns wide or as wide as the terminal
+pub fn text(width: Option<usize>, color: Color) -> Result<ExitCode> {
+    let markdown = generate(Level::Advanced, false, &mut Cache::new())?.markdown;
+    let markdown = sheet::markdown_output(str::from_utf8(&markdown).unwrap());
+    let style = match color {
+        Color::Auto => Style::stdout(),
+        Color::Always => Style { color: true },
+        Color::Never => Style { color: false },
+    };
+    let text = render(&markdown, width.unwrap_or_else(terminal_width), style);
+    let mut stdout = io::stdout().lock();
+    // `less` going away before everything is written isn't a failure
+    match stdout.write_all(text.as_bytes()) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        result => result?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(markdown: &str, width: usize) -> String {
+        render(markdown, width, Style { color: false })
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            plain(
+                "## Basic elements\n\nThese parse **the lowest** level of `the grammar`.\n\n- One\n- Two\n",
+                24
+            ),
+            "Basic elements\n──────────────\n\nThese parse the lowest\nlevel of `the grammar`.\n\n• One\n• Two\n"
+        );
+        assert_eq!(
+            render("Use `tag`", 80, Style { color: true }),
+            "Use \x1b[36m`tag`\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_render_table() {
+        let markdown =
+            "| parser | description |\n|---|---|\n| `tag` | Matches a fixed string<br>Or bytes |\n";
+        assert_eq!(
+            plain(markdown, 40),
+            "parser │ description\n───────┼───────────────────────\n`tag`  │ Matches a fixed string\n       │ Or bytes\n"
+        );
+        assert_eq!(
+            plain(markdown, 26),
+            "parser │ description\n───────┼──────────────────\n`tag`  │ Matches a fixed\n       │ string\n       │ Or bytes\n"
+        );
+        assert_eq!(
+            plain(markdown, 20),
+            "parser:      `tag`\ndescription: Matches\n             a fixed\n             string\n             Or\n             bytes\n"
+        );
+    }
+
+    #[test]
+    fn test_column_widths() {
+        assert_eq!(column_widths(&[5, 10], 20), Some(vec![5, 10]));
+        assert_eq!(column_widths(&[5, 40, 40], 45), Some(vec![5, 20, 20]));
+        assert_eq!(column_widths(&[5, 40, 40], 25), None);
+    }
+}
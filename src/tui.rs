@@ -0,0 +1,351 @@
+//! Browses the sheet in the terminal, like `nom-cheatsheet tui`, with the
+//! sections on the left, their combinators next to them, and the rows of the
+//! picked combinator on the right. `/` searches all combinators with the
+//! same fuzzy matching as the palette of the HTML.
+
+use crate::{
+    cache::Cache,
+    generated::generate,
+    sheet::{Level, Row},
+    show::{self, format_rows},
+};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, List, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+use std::{io::Result, process::ExitCode};
+
+/// A combinator and the rows that show it
+struct Entry<'a> {
+    path: String,
+    section: &'static str,
+    rows: Vec<&'a Row>,
+}
+
+/// The combinators in the order of the sheet, by their paths, like
+/// `bytes::complete::tag`
+fn entries(rows: &[Row]) -> Vec<Entry<'_>> {
+    let mut entries = Vec::<Entry>::new();
+    for row in rows {
+        for link in &row.combinators {
+            let path = format!("{}::{}", link.module, link.name);
+            match entries.iter_mut().find(|entry| entry.path == path) {
+                Some(entry) => {
+                    if !entry.rows.iter().any(|known| known.id == row.id) {
+                        entry.rows.push(row);
+                    }
+                }
+                None => entries.push(Entry {
+                    path,
+                    section: row.section,
+                    rows: vec![row],
+                }),
+            }
+        }
+    }
+    entries
+}
+
+/// How well `label` matches `query`, the same way as the palette of the HTML
+/// does it. Every character of the query has to be in the label, in order.
+/// Matches at the start of a word and runs of consecutive characters score
+/// higher.
+fn score(query: &str, label: &str) -> Option<f64> {
+    let label = label.to_lowercase().chars().collect::<Vec<_>>();
+    let mut total = 0.0;
+    let mut position = 0;
+    let mut previous = None;
+    for c in query.to_lowercase().chars() {
+        let found = position + label[position..].iter().position(|&other| other == c)?;
+        total += if previous == Some(found.wrapping_sub(1)) {
+            3.0
+        } else {
+            1.0
+        };
+        if found == 0 || [' ', ':', '_'].contains(&label[found - 1]) {
+            total += 2.0;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(total - label.len() as f64 / 100.0)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pane {
+    Sections,
+    Combinators,
+}
+
+struct Browser<'a> {
+    sections: Vec<&'static str>,
+    entries: Vec<Entry<'a>>,
+    query: String,
+    searching: bool,
+    focus: Pane,
+    section: ListState,
+    combinator: ListState,
+    /// How far the rows are scrolled down
+    scroll: u16,
+}
+
+impl<'a> Browser<'a> {
+    fn new(rows: &'a [Row]) -> Self {
+        let mut sections = Vec::new();
+        for row in rows {
+            if !sections.contains(&row.section) {
+                sections.push(row.section);
+            }
+        }
+        Browser {
+            sections,
+            entries: entries(rows),
+            query: String::new(),
+            searching: false,
+            focus: Pane::Sections,
+            section: ListState::default().with_selected(Some(0)),
+            combinator: ListState::default().with_selected(Some(0)),
+            scroll: 0,
+        }
+    }
+
+    /// The combinators of the picked section, or the ones that match the
+    /// search, best first
+    fn visible(&self) -> Vec<&Entry<'a>> {
+        if self.query.is_empty() {
+            let section = self
+                .section
+                .selected()
+                .and_then(|index| self.sections.get(index));
+            return self
+                .entries
+                .iter()
+                .filter(|entry| Some(&entry.section) == section)
+                .collect();
+        }
+        let mut matches = self
+            .entries
+            .iter()
+            .filter_map(|entry| Some((score(&self.query, &entry.path)?, entry)))
+            .collect::<Vec<_>>();
+        matches.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        matches.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    fn selected(&self) -> Option<&Entry<'a>> {
+        self.visible().get(self.combinator.selected()?).copied()
+    }
+
+    /// Moves the selection of the focused list by `by`, staying in the list
+    fn step(&mut self, by: isize) {
+        let (state, len) = match self.focus {
+            Pane::Sections => (&mut self.section, self.sections.len()),
+            Pane::Combinators => {
+                let len = self.visible().len();
+                (&mut self.combinator, len)
+            }
+        };
+        let index = state.selected().unwrap_or(0).saturating_add_signed(by);
+        state.select(Some(index.min(len.saturating_sub(1))));
+        if self.focus == Pane::Sections {
+            self.combinator.select(Some(0));
+        }
+        self.scroll = 0;
+    }
+
+    /// Handles a key, and returns whether to quit
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if self.searching {
+            match key.code {
+                KeyCode::Char(c) => self.query.push(c),
+                KeyCode::Backspace => {
+                    self.query.pop();
+                }
+                KeyCode::Esc => {
+                    self.query.clear();
+                    self.searching = false;
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Up => self.step(-1),
+                KeyCode::Down => self.step(1),
+                _ => {}
+            }
+            if matches!(
+                key.code,
+                KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Esc
+            ) {
+                self.combinator.select(Some(0));
+                self.scroll = 0;
+            }
+            return false;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.focus = Pane::Combinators;
+            }
+            KeyCode::Tab | KeyCode::Left | KeyCode::Right | KeyCode::Char('h' | 'l') => {
+                self.focus = match self.focus {
+                    Pane::Sections => Pane::Combinators,
+                    Pane::Combinators => Pane::Sections,
+                };
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.step(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.step(1),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            _ => {}
+        }
+        false
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search, main, help] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [sections, combinators, rows] = Layout::horizontal([
+            Constraint::Percentage(25),
+            Constraint::Percentage(30),
+            Constraint::Percentage(45),
+        ])
+        .areas(main);
+
+        let cursor = if self.searching { "█" } else { "" };
+        frame.render_widget(
+            Paragraph::new(format!("{}{cursor}", self.query))
+                .block(Block::bordered().title("Search")),
+            search,
+        );
+        let border = |pane| {
+            if self.focus == pane {
+                Style::new().bold()
+            } else {
+                Style::new().dim()
+            }
+        };
+        frame.render_stateful_widget(
+            List::new(self.sections.iter().copied())
+                .block(
+                    Block::bordered()
+                        .title("Sections")
+                        .border_style(border(Pane::Sections)),
+                )
+                .highlight_style(Style::new().reversed()),
+            sections,
+            &mut self.section,
+        );
+        let paths = self
+            .visible()
+            .iter()
+            .map(|entry| entry.path.clone())
+            .collect::<Vec<_>>();
+        let title = if self.query.is_empty() {
+            "Combinators".to_string()
+        } else {
+            format!("Combinators matching `{}`", self.query)
+        };
+        let list = List::new(paths)
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .border_style(border(Pane::Combinators)),
+            )
+            .highlight_style(Style::new().reversed());
+        let text = self
+            .selected()
+            .map(|entry| format_rows(&entry.rows, &show::Style { color: false }))
+            .unwrap_or_default();
+        frame.render_stateful_widget(list, combinators, &mut self.combinator);
+        frame.render_widget(
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0))
+                .block(Block::bordered().title("Rows")),
+            rows,
+        );
+        let keys = if self.searching {
+            "Type to search  ↑↓ pick  Enter done  Esc clear"
+        } else {
+            "/ search  ↑↓ pick  Tab switch pane  PgUp/PgDn scroll  q quit"
+        };
+        frame.render_widget(Line::from(keys).dim(), help);
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && self.key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Browses the rows of the sheet until `q` is pressed
+pub fn tui() -> Result<ExitCode> {
+    let rows = generate(Level::Advanced, false, &mut Cache::new())?.rows;
+    let mut browser = Browser::new(&rows);
+    let mut terminal = ratatui::init();
+    let result = browser.run(&mut terminal);
+    ratatui::restore();
+    result.map(|()| ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet::Link;
+
+    #[test]
+    fn test_score() {
+        assert_eq!(score("xyz", "bytes::complete::tag"), None);
+        assert!(score("tag", "bytes::complete::tag") > score("tag", "bytes::complete::take_until"));
+        assert!(score("tu", "bytes::complete::take_until") > score("tu", "multi::count"));
+    }
+
+    #[test]
+    fn test_browser() {
+        let row = |id, section, name| Row {
+            id,
+            combinators: vec![Link {
+                module: "bytes::complete",
+                name,
+                url: "".into(),
+                summary: None,
+                signature: None,
+            }],
+            ..Row::for_test(section)
+        };
+        let rows = [
+            row(0, "Basic elements", "tag"),
+            row(1, "Basic elements", "tag"),
+            row(2, "Errors", "take_until"),
+        ];
+        let mut browser = Browser::new(&rows);
+        assert_eq!(browser.sections, ["Basic elements", "Errors"]);
+        assert_eq!(browser.selected().unwrap().rows.len(), 2);
+        browser.key(KeyCode::Down.into());
+        assert_eq!(
+            browser.selected().unwrap().path,
+            "bytes::complete::take_until"
+        );
+        for code in [KeyCode::Char('/'), KeyCode::Char('t'), KeyCode::Char('g')] {
+            browser.key(code.into());
+        }
+        assert_eq!(browser.selected().unwrap().path, "bytes::complete::tag");
+        browser.key(KeyCode::Esc.into());
+        assert!(browser.query.is_empty());
+        assert!(!browser.key(KeyCode::Tab.into()));
+        assert!(browser.key(KeyCode::Char('q').into()));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{
+    cache::Cache,
+    generated::{generate, ROW_LABELS},
+    links, lint,
+    sheet::{Level, Row},
+};
+use nom_cheatsheet_shared::expand_includes;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    io::{BufRead, Result},
+    path::Path,
+    process::{Command, ExitCode, Stdio},
+    time::Duration,
+};
+
+/// Examples are tiny, so one that takes longer than this to run most likely
+/// has an input that makes its parser do far more work than it should
+const SLOW_ROW: Duration = Duration::from_millis(10);
+
+/// Which part of the generated code a line belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Location {
+    Row(usize),
+    Fixtures,
+    Other,
+}
+
+/// Works out which part of a generated file each line belongs to, by looking
+/// for the start of every row function and of the fixtures module. This
+/// relies on `prettyplease` putting each item at the start of a line.
+fn locations(generated: &str) -> Vec<Location> {
+    let mut current = Location::Other;
+    generated
+        .lines()
+        .map(|line| {
+            // Each row has a function that writes it, and one after that
+            // which runs its example
+            if let Some(rest) = line
+                .strip_prefix("fn row_")
+                .or_else(|| line.strip_prefix("pub(super) fn row_"))
+            {
+                let index = rest.split(['(', '_']).next().unwrap();
+                current = Location::Row(index.parse().unwrap());
+            } else if line.starts_with("mod fixtures") {
+                current = Location::Fixtures;
+            } else if line.starts_with("pub ") {
+                current = Location::Other;
+            }
+            current
+        })
+        .collect()
+}
+
+/// Prints the rows whose examples took suspiciously long to run, slowest
+/// first. Timings vary too much between machines to fail on, so these are
+/// only pointed out.
+fn report_slow_rows(rows: &[Row]) {
+    let mut slow = rows
+        .iter()
+        .map(|row| (row, Duration::from_nanos(row.elapsed_ns)))
+        .filter(|(_, elapsed)| *elapsed > SLOW_ROW)
+        .collect::<Vec<_>>();
+    if slow.is_empty() {
+        return;
+    }
+    slow.sort_by_key(|(_, elapsed)| std::cmp::Reverse(*elapsed));
+    println!("Examples that took over {SLOW_ROW:?} to run:");
+    for (row, elapsed) in slow {
+        println!("    {elapsed:.1?}: {}", ROW_LABELS[row.id]);
+    }
+}
+
+/// Builds the cheatsheet and reports any warnings in the generated examples,
+/// grouped by the template row that they came from. Also runs the examples,
+/// to point out slow ones. With a template to lint, problems in its prose are
+/// reported too, by line, and with `links`, links to docs.rs that go nowhere,
+/// checked against `rustdoc_json` if that's given.
+pub fn validate(lint: Option<&Path>, links: bool, rustdoc_json: Option<&Path>) -> Result<ExitCode> {
+    // Warnings get replayed by cargo even if nothing needs to be rebuilt, so
+    // this is cheap when run through `cargo run`.
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let output = Command::new(cargo)
+        .args(["build", "--message-format=json"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stderr(Stdio::inherit())
+        .output()?;
+
+    let mut locations_by_file = HashMap::<String, Vec<Location>>::new();
+    let mut warnings = BTreeMap::<Location, Vec<String>>::new();
+    for line in output.stdout.lines() {
+        let message: Value = serde_json::from_str(&line?)?;
+        if message["reason"] != "compiler-message" || message["message"]["level"] != "warning" {
+            continue;
+        }
+        let message = &message["message"];
+        let Some(span) = message["spans"]
+            .as_array()
+            .and_then(|spans| spans.iter().find(|span| span["is_primary"] == true))
+        else {
+            continue;
+        };
+        // The generated code is `include!`d from OUT_DIR, so its files are
+        // the only ones with absolute paths. The rows are in the files of
+        // their sections, next to `generated.rs`.
+        let file_name = span["file_name"].as_str().unwrap_or_default();
+        let path = Path::new(file_name);
+        if !path.is_absolute()
+            || !path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("generated"))
+        {
+            continue;
+        }
+        if !locations_by_file.contains_key(file_name) {
+            let generated = fs::read_to_string(file_name)?;
+            locations_by_file.insert(file_name.to_string(), locations(&generated));
+        }
+        let line_number = span["line_start"].as_u64().unwrap_or_default();
+        let location = usize::try_from(line_number)
+            .ok()
+            .and_then(|line_number| locations_by_file[file_name].get(line_number.checked_sub(1)?))
+            .copied()
+            .unwrap_or(Location::Other);
+        warnings
+            .entry(location)
+            .or_default()
+            .push(message["message"].as_str().unwrap_or_default().to_string());
+    }
+
+    if !output.status.success() {
+        eprintln!("Building the cheatsheet failed");
+        return Ok(ExitCode::FAILURE);
+    }
+    let sheet = generate(Level::Advanced, false, &mut Cache::disabled())?;
+    report_slow_rows(&sheet.rows);
+    let mut problems = false;
+    if let Some(path) = lint {
+        let template = expand_includes(path)?;
+        let lint_problems = lint::lint(&template.text)?;
+        lint::print_problems(&template, &lint_problems);
+        problems = !lint_problems.is_empty();
+    }
+    if links {
+        problems |= !links::report_links(&String::from_utf8_lossy(&sheet.markdown), rustdoc_json)?;
+    }
+    if warnings.is_empty() {
+        println!("No warnings in the generated examples");
+        return Ok(if problems {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        });
+    }
+    println!("Warnings in the generated examples:");
+    for (location, messages) in warnings {
+        match location {
+            Location::Row(index) => println!("\n{}", ROW_LABELS[index]),
+            Location::Fixtures => println!("\nFixtures"),
+            Location::Other => println!("\nOutside of any row"),
+        }
+        for message in messages {
+            println!("    warning: {message}");
+        }
+    }
+    Ok(ExitCode::FAILURE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locations() {
+        let generated = "\
+use std::io::Write;
+pub fn generate() -> Result<Sheet> {
+    row_0(&mut markdown, &mut rows)?;
+}
+#[allow(dead_code)]
+mod fixtures {
+    pub fn helper() {}
+}
+fn row_0(markdown: &mut Vec<u8>, rows: &mut Vec<Row>) -> Result<()> {
+    Ok(())
+}
+pub(super) fn row_0_output() -> Result<(String, Vec<String>)> {
+    Ok(output)
+}
+fn row_1(markdown: &mut Vec<u8>, rows: &mut Vec<Row>) -> Result<()> {
+";
+        assert_eq!(
+            locations(generated),
+            [
+                Location::Other,
+                Location::Other,
+                Location::Other,
+                Location::Other,
+                Location::Other,
+                Location::Fixtures,
+                Location::Fixtures,
+                Location::Fixtures,
+                Location::Row(0),
+                Location::Row(0),
+                Location::Row(0),
+                Location::Row(0),
+                Location::Row(0),
+                Location::Row(0),
+                Location::Row(1),
+            ]
+        );
+    }
+}
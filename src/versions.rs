@@ -0,0 +1,133 @@
+//! With `--versioned`, the output goes into `dist/<nom version>/` instead, so
+//! that the cheatsheets for several versions of nom can be hosted together.
+
+use crate::html;
+use std::{
+    fs,
+    io::Result,
+    path::{Path, PathBuf},
+};
+
+/// The version of nom that the examples were built with
+pub static NOM_VERSION: &str = env!("NOM_VERSION");
+
+/// The outputs that the landing page links to for each version, the ones
+/// that are in its directory
+static PAGES: &[(&str, &str)] = &[
+    ("HTML", "nom-cheatsheet.html"),
+    ("Markdown", "nom-cheatsheet.md"),
+    ("PDF", "nom-cheatsheet.pdf"),
+];
+
+/// Turns `7.1.3` into `[7, 1, 3]`, so versions sort numerically
+pub(crate) fn parse_version(name: &str) -> Option<Vec<u64>> {
+    name.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// The versions that have a directory in `dist`, newest first
+fn versions(dist: &Path) -> Result<Vec<String>> {
+    let mut versions = Vec::new();
+    for entry in fs::read_dir(dist)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_dir() && parse_version(&name).is_some() {
+            versions.push(name);
+        }
+    }
+    versions.sort_by_key(|version| std::cmp::Reverse(parse_version(version)));
+    Ok(versions)
+}
+
+pub fn versioned_dir(dist: &Path) -> Result<PathBuf> {
+    let dir = dist.join(NOM_VERSION);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// The label and file name of the outputs in `PAGES` that are in `dir`
+fn pages(dir: &Path) -> Vec<(&'static str, &'static str)> {
+    PAGES
+        .iter()
+        .copied()
+        .filter(|(_, name)| dir.join(name).is_file())
+        .collect()
+}
+
+/// Copies everything in `from` to `to`, including what's in directories like
+/// `site/` and `book/`
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Makes `dist/latest` a copy of the newest version, rather than a symlink,
+/// so that it also works on hosts that don't follow those. Then writes the
+/// landing page that links to the outputs of all of them.
+pub fn update_latest_and_index(dist: &Path) -> Result<()> {
+    let versions = versions(dist)?;
+    if let Some(newest) = versions.first() {
+        let latest = dist.join("latest");
+        if latest.exists() {
+            fs::remove_dir_all(&latest)?;
+        }
+        copy_dir(&dist.join(newest), &latest)?;
+    }
+    let index_path = dist.join("index.html");
+    println!("Landing page: {index_path:?}");
+    let versions = versions
+        .into_iter()
+        .map(|version| {
+            let pages = pages(&dist.join(&version));
+            (version, pages)
+        })
+        .collect::<Vec<_>>();
+    html::write_index(&index_path, &versions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("7.1.3"), Some(vec![7, 1, 3]));
+        assert_eq!(parse_version("latest"), None);
+        assert_eq!(parse_version("8.0.0-beta"), None);
+        assert!(parse_version("7.10.0") > parse_version("7.9.1"));
+    }
+
+    #[test]
+    fn test_pages() {
+        let dir = std::env::temp_dir().join(format!("nom-cheatsheet-pages-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("nom-cheatsheet.md"), "sheet").unwrap();
+        assert_eq!(pages(&dir), [("Markdown", "nom-cheatsheet.md")]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_copy_dir() {
+        let dir = std::env::temp_dir().join(format!("nom-cheatsheet-copy-{}", std::process::id()));
+        fs::create_dir_all(dir.join("from/site/css")).unwrap();
+        fs::write(dir.join("from/nom-cheatsheet.md"), "sheet").unwrap();
+        fs::write(dir.join("from/site/css/sheet.css"), "css").unwrap();
+        copy_dir(&dir.join("from"), &dir.join("to")).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("to/nom-cheatsheet.md")).unwrap(),
+            "sheet"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("to/site/css/sheet.css")).unwrap(),
+            "css"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
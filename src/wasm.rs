@@ -0,0 +1,59 @@
+use crate::html::Artifact;
+use std::{
+    env, fs,
+    io::Result,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+static TARGET: &str = "wasm32-unknown-unknown";
+
+/// Builds all example parsers into a WebAssembly module, and writes it to
+/// `dist` along with the JavaScript that wraps it. If the module can't be
+/// built, for instance because the target isn't installed, this only says
+/// so, as the rest of the cheatsheet is still useful without it.
+pub fn build(dist: &Path, artifacts: &mut Vec<Artifact>) -> Result<()> {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let crate_dir = manifest_dir.join("nom-cheatsheet-wasm");
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let mut command = Command::new(cargo);
+    command.args(["build", "--release", "--target", TARGET]);
+    // The module's examples are the sheet's, so with nom 8 it needs that too
+    if cfg!(feature = "nom8") {
+        command.args(["--features", "nom8"]);
+    }
+    let status = command
+        .current_dir(&crate_dir)
+        .env(
+            "NOM_CHEATSHEET_WASM_ROWS",
+            concat!(env!("OUT_DIR"), "/wasm_rows.rs"),
+        )
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()?;
+    if !status.success() {
+        eprintln!(
+            "Building the WebAssembly module failed, skipping it. Is the target installed? \
+             `rustup target add {TARGET}`"
+        );
+        return Ok(());
+    }
+
+    let wasm_path = dist.join("nom-cheatsheet.wasm");
+    fs::copy(
+        crate_dir.join(format!("target/{TARGET}/release/nom_cheatsheet_wasm.wasm")),
+        &wasm_path,
+    )?;
+    println!("WebAssembly module: {wasm_path:?}");
+    let js_path = dist.join("nom-cheatsheet.js");
+    fs::write(&js_path, include_str!("nom-cheatsheet.js"))?;
+    artifacts.push(Artifact {
+        label: "WebAssembly",
+        path: wasm_path,
+    });
+    artifacts.push(Artifact {
+        label: "JavaScript bindings",
+        path: js_path,
+    });
+    Ok(())
+}
@@ -0,0 +1,106 @@
+//! Regenerates the sheet when its sources change, or from other templates.
+//! The examples are compiled in, so that takes a rebuild, which `cargo run`
+//! does before generating with the new binary.
+
+use crate::manifest;
+use notify::{Event, RecursiveMode, Watcher};
+use std::{
+    env,
+    ffi::OsString,
+    io::{self, Result},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc,
+    time::Duration,
+};
+
+/// What the sheet gets generated from: the templates that it was built
+/// from, and the code that's shared with the build script, which formats
+/// the code in the sheet
+pub fn sources() -> Vec<PathBuf> {
+    env::split_paths(env!("NOM_CHEATSHEET_TEMPLATE_PATHS"))
+        .chain([Path::new(env!("CARGO_MANIFEST_DIR")).join("nom-cheatsheet-shared/src")])
+        .collect()
+}
+
+/// Rebuilds the tool and runs `generate` with `args`, returning whether
+/// both worked
+pub fn regenerate(args: &[OsString]) -> Result<bool> {
+    let status = cargo_run(args).status()?;
+    Ok(status.success())
+}
+
+/// Like `regenerate`, with the examples of `templates` built in instead of
+/// the ones that the tool was built with
+pub fn regenerate_from(templates: &[PathBuf], args: &[OsString]) -> Result<bool> {
+    // The build script is run in the crate's directory
+    let templates = templates
+        .iter()
+        .map(std::fs::canonicalize)
+        .collect::<Result<Vec<_>>>()?;
+    let status = cargo_run(args)
+        .env(
+            "NOM_CHEATSHEET_TEMPLATES",
+            env::join_paths(templates).map_err(io::Error::other)?,
+        )
+        .status()?;
+    Ok(status.success())
+}
+
+/// `cargo run` of the tool, built with the same features and profile as
+/// this one, so that a rebuild doesn't drop rows that need `nom8` or slow
+/// the examples down
+fn cargo_run(args: &[OsString]) -> Command {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let mut command = Command::new(cargo);
+    command
+        .args(["run", "--quiet", "--manifest-path"])
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"))
+        .arg("--no-default-features");
+    let features = manifest::features();
+    if !features.is_empty() {
+        command.args(["--features", &features.join(",")]);
+    }
+    if !cfg!(debug_assertions) {
+        command.arg("--release");
+    }
+    command.args(["--", "generate"]).args(args);
+    command
+}
+
+/// Calls `on_change` whenever something in `paths` changes, which can be
+/// files or directories, until that fails
+pub fn watch(paths: &[PathBuf], mut on_change: impl FnMut() -> Result<()>) -> Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(io::Error::other)?;
+    for path in paths {
+        // Editors often save by replacing the file, which a watch on the
+        // file itself doesn't survive
+        let (watched, mode) = if path.is_dir() {
+            (path.as_path(), RecursiveMode::Recursive)
+        } else {
+            (path.parent().unwrap(), RecursiveMode::NonRecursive)
+        };
+        watcher.watch(watched, mode).map_err(io::Error::other)?;
+    }
+    let affects = |event: &Event| {
+        !event.kind.is_access()
+            && event
+                .paths
+                .iter()
+                .any(|changed| paths.iter().any(|path| changed.starts_with(path)))
+    };
+    loop {
+        let event: Event = receiver
+            .recv()
+            .map_err(io::Error::other)?
+            .map_err(io::Error::other)?;
+        if !affects(&event) {
+            continue;
+        }
+        // Saving a file can take several events, which should only
+        // regenerate once
+        while receiver.recv_timeout(Duration::from_millis(200)).is_ok() {}
+        on_change()?;
+    }
+}